@@ -0,0 +1,347 @@
+//! Application-wide actions shared by the tray menu and window shortcuts
+
+use super::VibeProxyApp;
+use crate::log_viewer::show_logs;
+use crate::settings::show_settings;
+use crate::ui::{
+    choose_provider_import, confirm_restore_config, copy_diagnostics, offer_rollback,
+    show_about, show_server_error,
+};
+use gtk::prelude::*;
+use gtk::{gio, glib, Application};
+use std::rc::Rc;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+use vibeproxy_linux::autostart;
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::server_manager::{ApplyOutcome, ServerManager, Transition};
+
+impl VibeProxyApp {
+    /// Register application-wide actions and their accelerators
+    pub(super) fn register_actions(
+        app: &Application,
+        config_manager: &Arc<ConfigManager>,
+        server_manager: &Arc<ServerManager>,
+        runtime: &tokio::runtime::Handle,
+        show_window: Rc<dyn Fn()>,
+    ) {
+        let show_action = gio::SimpleAction::new("show-window", None);
+        show_action.connect_activate({
+            let show_window = show_window.clone();
+            move |_, _| show_window()
+        });
+        app.add_action(&show_action);
+        app.set_accels_for_action("app.show-window", &["<Primary><Shift>v"]);
+
+        // From the tray too, so the window comes up first as the dialog's parent
+        let settings_action = gio::SimpleAction::new("settings", None);
+        settings_action.connect_activate(glib::clone!(
+            @weak app,
+            @strong config_manager,
+            @strong server_manager,
+            @strong runtime
+            => move |_, _| {
+                show_window();
+                let Some(window) = app.active_window() else {
+                    return;
+                };
+                show_settings(&window, config_manager.clone(), server_manager.clone(), &runtime)
+            }
+        ));
+        app.add_action(&settings_action);
+        app.set_accels_for_action("app.settings", &["<Primary>comma"]);
+
+        let toggle_action = gio::SimpleAction::new("toggle-server", None);
+        toggle_action.connect_activate(glib::clone!(
+            @weak app,
+            @strong server_manager,
+            @strong runtime
+            => move |_, _| {
+                // A repeated shortcut shouldn't turn into the opposite request
+                if let Some(transition) = *server_manager.transition_updates().borrow() {
+                    info!("Ignoring toggle, backend is {:?}", transition);
+                    return;
+                }
+                let server_manager = server_manager.clone();
+                let request = runtime.spawn(async move {
+                    let stopping = server_manager.is_running().await;
+                    let result = if stopping {
+                        server_manager.stop().await
+                    } else {
+                        server_manager.start().await
+                    };
+                    // Publish the new state to the window and tray right away
+                    let _ = server_manager.status().await;
+                    (stopping, result)
+                });
+                glib::spawn_future_local(glib::clone!(@weak app => async move {
+                    match request.await {
+                        Ok((_, Ok(()))) => {}
+                        Ok((stopping, Err(e))) => {
+                            error!("Failed to toggle server: {:#}", e);
+                            let heading = if stopping {
+                                "Could Not Stop the Backend"
+                            } else {
+                                "Could Not Start the Backend"
+                            };
+                            show_server_error(&app, heading, &e);
+                        }
+                        Err(e) => error!("Toggle task failed: {}", e),
+                    }
+                }));
+            }
+        ));
+        app.add_action(&toggle_action);
+        app.set_accels_for_action("app.toggle-server", &["<Primary><Shift>s"]);
+
+        // The window's Start and Stop buttons and the header menu use these
+        let start_action = gio::SimpleAction::new("start-server", None);
+        let stop_action = gio::SimpleAction::new("stop-server", None);
+        for (action, start) in [(&start_action, true), (&stop_action, false)] {
+            action.connect_activate(glib::clone!(
+                @weak app,
+                @weak start_action,
+                @weak stop_action,
+                @strong server_manager,
+                @strong runtime
+                => move |_, _| {
+                    // Starting can take as long as the backend's startup timeout
+                    start_action.set_enabled(false);
+                    stop_action.set_enabled(false);
+                    let request = {
+                        let server_manager = server_manager.clone();
+                        runtime.spawn(async move {
+                            let result = if start {
+                                server_manager.start().await
+                            } else {
+                                server_manager.stop().await
+                            };
+                            // Publish the new state to the window and tray right away
+                            let _ = server_manager.status().await;
+                            result
+                        })
+                    };
+                    let running = server_manager.running_updates();
+                    let transitions = server_manager.transition_updates();
+                    glib::spawn_future_local(glib::clone!(
+                        @weak app,
+                        @weak start_action,
+                        @weak stop_action
+                        => async move {
+                            let result = request.await;
+                            let actions = [&start_action, &stop_action];
+                            sync_server_actions(&running, &transitions, actions);
+                            match result {
+                                Ok(Ok(())) => {}
+                                Ok(Err(e)) => {
+                                    let heading = if start {
+                                        "Could Not Start the Backend"
+                                    } else {
+                                        "Could Not Stop the Backend"
+                                    };
+                                    error!("{}: {:#}", heading, e);
+                                    show_server_error(&app, heading, &e);
+                                }
+                                Err(e) => error!("Server task failed: {}", e),
+                            }
+                        }
+                    ));
+                }
+            ));
+            app.add_action(action);
+        }
+        // Ctrl+R stays with checking the status now (win.refresh)
+        app.set_accels_for_action("app.start-server", &["<Primary><Shift>r"]);
+        app.set_accels_for_action("app.stop-server", &["<Primary>period"]);
+        Self::follow_server_state(server_manager, &start_action, &stop_action);
+
+        let reload_action = gio::SimpleAction::new("reload-credentials", None);
+        reload_action.connect_activate(glib::clone!(@strong server_manager => move |_, _| {
+            server_manager.reload_credentials();
+        }));
+        app.add_action(&reload_action);
+
+        // Backend profile by name, from the tray and the window header
+        let profile_action =
+            gio::SimpleAction::new("select-profile", Some(glib::VariantTy::STRING));
+        profile_action.connect_activate(glib::clone!(
+            @weak app,
+            @strong config_manager,
+            @strong server_manager,
+            @strong runtime
+            => move |_, parameter| {
+                let Some(name) = parameter.and_then(|p| p.get::<String>()) else {
+                    return;
+                };
+                let request = {
+                    let server_manager = server_manager.clone();
+                    runtime.spawn(async move { server_manager.select_profile(&name).await })
+                };
+                glib::spawn_future_local(glib::clone!(
+                    @weak app,
+                    @strong config_manager
+                    => async move {
+                        match request.await {
+                            Ok(Ok(ApplyOutcome::Applied)) => {}
+                            Ok(Ok(ApplyOutcome::Unreachable { previous, error })) => {
+                                match app.active_window() {
+                                    Some(window) => {
+                                        offer_rollback(&window, config_manager, previous, &error)
+                                    }
+                                    None => warn!("Profile backend unreachable: {}", error),
+                                }
+                            }
+                            Ok(Err(e)) => error!("Failed to switch profile: {:#}", e),
+                            Err(e) => error!("Profile switch task failed: {}", e),
+                        }
+                    }
+                ));
+            }
+        ));
+        app.add_action(&profile_action);
+
+        // Provider id and whether to route to it, from the tray
+        let provider_action = gio::SimpleAction::new(
+            "set-provider-enabled",
+            Some(glib::VariantTy::new("(sb)").unwrap()),
+        );
+        provider_action.connect_activate(glib::clone!(
+            @strong server_manager,
+            @strong runtime
+            => move |_, parameter| {
+                let Some((provider_id, enabled)) = parameter.and_then(|p| p.get::<(String, bool)>())
+                else {
+                    return;
+                };
+                let server_manager = server_manager.clone();
+                runtime.spawn(async move {
+                    let result = server_manager.set_provider_enabled(&provider_id, enabled).await;
+                    if let Err(e) = result {
+                        error!("Failed to update routing for '{}': {:#}", provider_id, e);
+                    }
+                });
+            }
+        ));
+        app.add_action(&provider_action);
+
+        // Also toggled from the settings window
+        for (name, enable) in [("enable-autostart", true), ("disable-autostart", false)] {
+            let action = gio::SimpleAction::new(name, None);
+            action.connect_activate(move |_, _| {
+                let result = if enable {
+                    autostart::Method::DesktopEntry.enable().map(drop)
+                } else {
+                    autostart::Method::DesktopEntry.disable()
+                };
+                if let Err(e) = result {
+                    error!("Failed to change autostart: {:#}", e);
+                }
+            });
+            app.add_action(&action);
+        }
+
+        let logs_action = gio::SimpleAction::new("show-logs", None);
+        logs_action.connect_activate(glib::clone!(
+            @weak app,
+            @strong server_manager
+            => move |_, _| show_logs(&app, server_manager.clone())
+        ));
+        app.add_action(&logs_action);
+
+        let diagnostics_action = gio::SimpleAction::new("copy-diagnostics", None);
+        diagnostics_action.connect_activate(glib::clone!(
+            @weak app,
+            @strong config_manager,
+            @strong server_manager,
+            @strong runtime
+            => move |_, _| copy_diagnostics(
+                &app,
+                config_manager.clone(),
+                server_manager.clone(),
+                runtime.clone(),
+            )
+        ));
+        app.add_action(&diagnostics_action);
+
+        let import_action = gio::SimpleAction::new("import-providers", None);
+        import_action.connect_activate(glib::clone!(
+            @weak app,
+            @strong config_manager,
+            @strong runtime
+            => move |_, _| choose_provider_import(&app, config_manager.clone(), runtime.clone())
+        ));
+        app.add_action(&import_action);
+
+        let restore_action = gio::SimpleAction::new("restore-config", None);
+        restore_action.connect_activate(glib::clone!(
+            @weak app,
+            @strong config_manager
+            => move |_, _| confirm_restore_config(&app, config_manager.clone())
+        ));
+        app.add_action(&restore_action);
+
+        let about_action = gio::SimpleAction::new("about", None);
+        about_action.connect_activate(glib::clone!(@weak app => move |_, _| {
+            show_about(app.active_window().as_ref());
+        }));
+        app.add_action(&about_action);
+
+        let quit_action = gio::SimpleAction::new("quit", None);
+        quit_action.connect_activate(glib::clone!(@weak app => move |_, _| {
+            info!("Quit requested");
+            app.quit();
+        }));
+        app.add_action(&quit_action);
+        app.set_accels_for_action("app.quit", &["<Primary>q"]);
+    }
+
+    /// Keep Start and Stop enabled to match the backend, whoever started or stopped it
+    fn follow_server_state(
+        server_manager: &ServerManager,
+        start_action: &gio::SimpleAction,
+        stop_action: &gio::SimpleAction,
+    ) {
+        let running = server_manager.running_updates();
+        let transitions = server_manager.transition_updates();
+        sync_server_actions(&running, &transitions, [start_action, stop_action]);
+
+        let mut running_changes = running.clone();
+        glib::spawn_future_local(glib::clone!(
+            @strong transitions,
+            @strong start_action,
+            @strong stop_action
+            => async move {
+                while running_changes.changed().await.is_ok() {
+                    let actions = [&start_action, &stop_action];
+                    sync_server_actions(&running_changes, &transitions, actions);
+                }
+            }
+        ));
+        let mut transition_changes = transitions;
+        glib::spawn_future_local(glib::clone!(
+            @strong start_action,
+            @strong stop_action
+            => async move {
+                while transition_changes.changed().await.is_ok() {
+                    let actions = [&start_action, &stop_action];
+                    sync_server_actions(&running, &transition_changes, actions);
+                }
+            }
+        ));
+    }
+}
+
+/// Enable Start or Stop to match whether the manager has the backend running
+///
+/// Both stay disabled while a start or stop is under way.
+fn sync_server_actions(
+    running: &watch::Receiver<bool>,
+    transitions: &watch::Receiver<Option<Transition>>,
+    [start_action, stop_action]: [&gio::SimpleAction; 2],
+) {
+    let idle = transitions.borrow().is_none();
+    let running = *running.borrow();
+    start_action.set_enabled(idle && !running);
+    stop_action.set_enabled(idle && running);
+}
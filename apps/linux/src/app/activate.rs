@@ -0,0 +1,201 @@
+//! First activation: config, tray, backend autostart and background services
+
+use super::{LaunchOptions, Ui, VibeProxyApp};
+use crate::system_tray::SystemTray;
+use crate::ui::{apply_theme, offer_secret_migration};
+use anyhow::Result;
+use gtk::prelude::*;
+use gtk::Application;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use vibeproxy_linux::config::TrayMode;
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::server_manager::ServerManager;
+use vibeproxy_linux::shutdown::ShutdownHooks;
+
+impl VibeProxyApp {
+    pub(super) fn on_activate(
+        app: &Application,
+        config_manager: &Arc<ConfigManager>,
+        server_manager: &Arc<ServerManager>,
+        runtime: &tokio::runtime::Handle,
+        shutdown: &ShutdownHooks,
+        options: LaunchOptions,
+        ui: &Rc<Ui>,
+    ) -> Result<()> {
+        info!("Activating VibeProxy application");
+
+        // Load configuration
+        let config = config_manager.load()?;
+        info!("Configuration loaded");
+        apply_theme(config.ui.theme);
+
+        // Creates the main window on first use, presents it afterwards
+        let show_window: Rc<dyn Fn()> = {
+            let app = app.clone();
+            let config_manager = config_manager.clone();
+            let server_manager = server_manager.clone();
+            let runtime = runtime.clone();
+            let ui = ui.clone();
+            Rc::new(move || {
+                Self::show_main_window(&app, &config_manager, &server_manager, &runtime, &ui)
+            })
+        };
+
+        // Actions shared by the tray menu and window shortcuts
+        Self::register_actions(
+            app,
+            config_manager,
+            server_manager,
+            runtime,
+            show_window.clone(),
+        );
+
+        if config_manager.is_safe_mode() {
+            // Just the window to fix settings in; no tray, socket or backend traffic
+            warn!("Safe mode: config file and keyring ignored, backend start disabled");
+            show_window();
+            return Ok(());
+        }
+
+        // Hand edits to the config file take effect without a restart
+        let configs = match config_manager.watch() {
+            Ok(watcher) => {
+                let mut configs = watcher.subscribe();
+                ui.config_watcher.replace(Some(watcher));
+                let server_manager = server_manager.clone();
+                let tray_configs = configs.clone();
+                runtime.spawn(async move {
+                    let mut previous = configs.borrow_and_update().clone();
+                    while configs.changed().await.is_ok() {
+                        let config = configs.borrow_and_update().clone();
+                        server_manager.config_reloaded(&previous, &config).await;
+                        previous = config;
+                    }
+                });
+                Some(tray_configs)
+            }
+            Err(e) => {
+                warn!("Config changes need a restart to take effect: {:#}", e);
+                None
+            }
+        };
+
+        // Create system tray (runs in background)
+        let statuses = if config.health.polling_enabled {
+            server_manager.watch_status(Duration::from_secs(config.health.interval_secs))
+        } else {
+            server_manager.status_updates()
+        };
+        Self::start_metrics(&config, server_manager, statuses.clone(), runtime, shutdown);
+        Self::start_status_socket(&config, statuses.clone(), runtime, shutdown);
+        let tray_mode = config.ui.tray_mode;
+        if tray_mode != TrayMode::Never {
+            let mut system_tray = SystemTray::new(
+                config_manager.clone(),
+                app.clone(),
+                statuses,
+                server_manager.running_updates(),
+                server_manager.transition_updates(),
+                configs,
+                server_manager.model_updates(),
+            )?;
+            system_tray.setup()?;
+            ui.system_tray.replace(Some(system_tray));
+        }
+
+        let tray_available = match tray_mode {
+            TrayMode::Auto => SystemTray::host_available(),
+            TrayMode::Always => true,
+            TrayMode::Never => false,
+        };
+        ui.tray_available.set(tray_available);
+        match tray_mode {
+            TrayMode::Auto if !tray_available => {
+                warn!("No system tray host found, closing the window will quit");
+                Self::notify_tray_unavailable(app, config_manager);
+            }
+            TrayMode::Never => info!("Tray icon turned off, closing the window will quit"),
+            _ => {}
+        }
+        if tray_mode == TrayMode::Auto {
+            Self::follow_tray_host(ui, show_window.clone());
+        }
+
+        // Without a tray, a held app with no window would be unreachable
+        let minimized = config.ui.tray_only || options.start_minimized;
+        if minimized && tray_available {
+            // Nothing keeps the application alive without a window
+            ui.hold_guard.replace(Some(app.hold()));
+            info!("Starting in the tray, main window will be created on demand");
+        } else {
+            show_window();
+        }
+
+        let resume = options.resume_server && server_manager.should_resume();
+        if resume && !options.autostart_server {
+            info!("Backend was running when VibeProxy last quit, starting it again");
+        }
+        if options.autostart_server || resume {
+            let server_manager = server_manager.clone();
+            runtime.spawn(async move {
+                if server_manager.is_running().await {
+                    info!("Backend already running, nothing to autostart");
+                } else if let Err(e) = server_manager.start().await {
+                    error!("Failed to autostart the backend: {:#}", e);
+                }
+            });
+        }
+
+        Self::notify_status_changes(app, &config, server_manager);
+
+        // Providers disabled while the backend was unreachable are still routed there
+        if config.health.polling_enabled {
+            let server_manager = server_manager.clone();
+            runtime.spawn(async move {
+                if let Err(e) = server_manager.reconcile_routing().await {
+                    warn!("Failed to reconcile backend routing: {:#}", e);
+                }
+            });
+        }
+
+        // Offer to move credentials pasted into the config file into the keyring
+        match config_manager.find_inline_secrets() {
+            Ok(secrets) if !secrets.is_empty() => offer_secret_migration(
+                app.active_window().as_ref(),
+                config_manager.clone(),
+                runtime.clone(),
+                secrets,
+            ),
+            Ok(_) => {}
+            Err(e) => warn!("Failed to scan config for secrets: {}", e),
+        }
+
+        info!("VibeProxy application activated");
+
+        Ok(())
+    }
+
+    /// Keep `tray_available` current while the tray host comes and goes
+    ///
+    /// A panel that crashes or restarts takes the icon with it. The window is
+    /// shown then, and the hold from starting in the tray released, so the
+    /// app never keeps running with no way to reach or quit it.
+    fn follow_tray_host(ui: &Rc<Ui>, show_window: Rc<dyn Fn()>) {
+        let ui = ui.clone();
+        SystemTray::watch_host(move |available| {
+            if ui.tray_available.replace(available) == available {
+                return;
+            }
+            if available {
+                info!("System tray host is back, closing the window hides it again");
+                return;
+            }
+            warn!("System tray host went away, showing the window; closing it will quit");
+            show_window();
+            ui.hold_guard.replace(None);
+        });
+    }
+}
@@ -0,0 +1,202 @@
+//! Main application structure
+
+mod actions;
+mod activate;
+mod notifications;
+mod services;
+
+use crate::system_tray::SystemTray;
+use crate::ui::MainWindow;
+use anyhow::Result;
+use gtk::prelude::*;
+use gtk::{gio, glib, Application};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tracing::{error, info, warn};
+use vibeproxy_linux::branding::Branding;
+use vibeproxy_linux::config_manager::{ConfigManager, ConfigWatcher};
+use vibeproxy_linux::server_manager::ServerManager;
+use vibeproxy_linux::shutdown::ShutdownHooks;
+
+pub struct VibeProxyApp {
+    app: Application,
+    runtime: Runtime,
+    config_manager: Arc<ConfigManager>,
+    server_manager: Arc<ServerManager>,
+    shutdown: Arc<ShutdownHooks>,
+    options: LaunchOptions,
+}
+
+/// Startup behavior chosen on the command line
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LaunchOptions {
+    /// Stay in the tray instead of showing the main window
+    pub start_minimized: bool,
+    /// Start the backend once the application is up
+    pub autostart_server: bool,
+    /// Start it only if it was running when the app last quit
+    pub resume_server: bool,
+}
+
+/// How long the backend gets to stop on quit
+pub(crate) const BACKEND_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long smaller subsystems get to clean up on quit
+const SUBSYSTEM_STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// UI objects that live for as long as the application runs
+#[derive(Default)]
+struct Ui {
+    system_tray: RefCell<Option<SystemTray>>,
+    main_window: RefCell<Option<MainWindow>>,
+    hold_guard: RefCell<Option<gio::ApplicationHoldGuard>>,
+    config_watcher: RefCell<Option<ConfigWatcher>>,
+    /// Set by the first activation; later ones come from a second launch
+    activated: Cell<bool>,
+    /// Whether closing the window can hide it to the tray, shared with the window
+    tray_available: Rc<Cell<bool>>,
+    /// The first activation failed; the process exits with an error
+    startup_failed: Cell<bool>,
+}
+
+impl VibeProxyApp {
+    /// A safe mode `config_manager` ignores the config file and keyring and never
+    /// starts the backend
+    pub fn new(config_manager: ConfigManager, options: LaunchOptions) -> Self {
+        let safe_mode = config_manager.is_safe_mode();
+
+        // Create GTK application
+        let branding = Branding::current();
+        let application_id = if gio::Application::id_is_valid(&branding.application_id) {
+            branding.application_id.as_str()
+        } else {
+            warn!(
+                "Invalid application id '{}' in branding, using the default",
+                branding.application_id
+            );
+            "com.vibeproxy.app"
+        };
+        glib::set_application_name(&branding.app_name);
+        // One instance per session owns the tray and the backend; launching again
+        // activates it. Safe mode stays separate so it works next to a stuck instance.
+        let flags = if safe_mode {
+            gio::ApplicationFlags::NON_UNIQUE
+        } else {
+            gio::ApplicationFlags::empty()
+        };
+        let app = Application::builder()
+            .application_id(application_id)
+            .flags(flags)
+            .build();
+
+        // Create async runtime
+        let runtime = Runtime::new().expect("Failed to create Tokio runtime");
+
+        // Initialize managers
+        let config_manager = Arc::new(config_manager);
+        let server_manager = Arc::new(
+            ServerManager::new(config_manager.clone(), runtime.handle().clone())
+                .expect("Failed to create server manager"),
+        );
+
+        // Registered first so it runs last, after everything that talks to the backend
+        let shutdown = Arc::new(ShutdownHooks::new());
+        shutdown.register("backend", BACKEND_STOP_TIMEOUT, {
+            let server_manager = server_manager.clone();
+            // Also when a start is still under way, whose backend would be orphaned
+            move || async move { server_manager.stop_for_exit().await }
+        });
+
+        Self {
+            app,
+            runtime,
+            config_manager,
+            server_manager,
+            shutdown,
+            options,
+        }
+    }
+
+    /// Run until the application quits; fails if it couldn't start
+    pub fn run(&self) -> Result<()> {
+        // Connect activate signal
+        let config_manager = self.config_manager.clone();
+        let server_manager = self.server_manager.clone();
+        let runtime_handle = self.runtime.handle().clone();
+        let shutdown = self.shutdown.clone();
+        let options = self.options;
+        let ui = Rc::new(Ui::default());
+
+        self.app.connect_activate(glib::clone!(@strong ui => move |app| {
+            // A second launch only brings the running instance to the front
+            if ui.activated.replace(true) {
+                info!("Activated by another launch, presenting the window");
+                app.activate_action("show-window", None);
+                return;
+            }
+            if let Err(e) = Self::on_activate(
+                app,
+                &config_manager,
+                &server_manager,
+                &runtime_handle,
+                &shutdown,
+                options,
+                &ui,
+            ) {
+                error!("Failed to activate application: {:#}", e);
+                ui.startup_failed.set(true);
+                app.quit();
+            }
+        }));
+
+        let runtime_handle = self.runtime.handle().clone();
+        let shutdown = self.shutdown.clone();
+        self.app.connect_shutdown(move |_| {
+            info!("Shutting down");
+            runtime_handle.block_on(shutdown.run());
+        });
+
+        // The remote instance forwards its activation to the primary and exits
+        match self.app.register(gio::Cancellable::NONE) {
+            Ok(()) if self.app.is_remote() => {
+                info!("VibeProxy is already running, activating the existing instance")
+            }
+            Ok(()) => {}
+            Err(e) => warn!("Failed to register application: {}", e),
+        }
+
+        // Arguments are parsed in main; GTK would reject the ones it doesn't know
+        self.app.run_with_args::<&str>(&[]);
+
+        if ui.startup_failed.get() {
+            anyhow::bail!("VibeProxy failed to start, see the log for details");
+        }
+        Ok(())
+    }
+
+    fn show_main_window(
+        app: &Application,
+        config_manager: &Arc<ConfigManager>,
+        server_manager: &Arc<ServerManager>,
+        runtime: &tokio::runtime::Handle,
+        ui: &Ui,
+    ) {
+        let mut main_window = ui.main_window.borrow_mut();
+        // A window closed without a tray to hide into is destroyed; build a new one
+        if main_window.as_ref().is_some_and(|window| !window.is_open()) {
+            *main_window = None;
+        }
+        let window = main_window.get_or_insert_with(|| {
+            MainWindow::new(
+                app,
+                config_manager.clone(),
+                server_manager.clone(),
+                runtime,
+                ui.tray_available.clone(),
+            )
+        });
+        window.present();
+    }
+}
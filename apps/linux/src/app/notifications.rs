@@ -0,0 +1,111 @@
+//! Desktop notifications about the tray and the backend
+
+use super::VibeProxyApp;
+use gtk::prelude::*;
+use gtk::{gio, glib, Application};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::server_manager::ServerManager;
+
+impl VibeProxyApp {
+    /// Explain the missing tray icon, once per install
+    pub(super) fn notify_tray_unavailable(app: &Application, config_manager: &ConfigManager) {
+        let Ok(mut config) = config_manager.load() else {
+            return;
+        };
+        if config.ui.tray_notice_shown {
+            return;
+        }
+
+        let notification = gio::Notification::new("Tray icon unavailable");
+        notification.set_body(Some(
+            "Your desktop has no system tray, so closing the window quits the app. \
+             On GNOME, install the AppIndicator extension to get the tray icon.",
+        ));
+        app.send_notification(Some("tray-unavailable"), &notification);
+
+        config.ui.tray_notice_shown = true;
+        if let Err(e) = config_manager.save(&config) {
+            warn!("Failed to save config: {}", e);
+        }
+    }
+
+    /// Desktop notification when the backend goes down or comes back up
+    ///
+    /// The first status seen is the baseline, not a change. While the
+    /// cooldown runs further changes are held back; once it ends, only the
+    /// state the backend settled in is reported, if it differs.
+    pub(super) fn notify_status_changes(
+        app: &Application,
+        config: &Config,
+        server_manager: &Arc<ServerManager>,
+    ) {
+        if !config.ui.notifications_enabled {
+            return;
+        }
+
+        let mut statuses = if config.health.polling_enabled {
+            server_manager.watch_status(Duration::from_secs(config.health.interval_secs))
+        } else {
+            server_manager.status_updates()
+        };
+        let cooldown = config.ui.notification_cooldown();
+        let app = app.downgrade();
+
+        glib::spawn_future_local(async move {
+            let mut reported: Option<bool> = None;
+            let mut last_sent: Option<Instant> = None;
+
+            while statuses.changed().await.is_ok() {
+                let running = match statuses.borrow_and_update().as_ref() {
+                    Some(status) => status.running,
+                    None => continue,
+                };
+                if reported.is_none() {
+                    reported = Some(running);
+                    continue;
+                }
+                if reported == Some(running) {
+                    continue;
+                }
+
+                if let Some(sent) = last_sent {
+                    let wait = cooldown.saturating_sub(sent.elapsed());
+                    if !wait.is_zero() {
+                        glib::timeout_future(wait).await;
+                    }
+                }
+                let Some(status) = statuses.borrow_and_update().clone() else {
+                    continue;
+                };
+                if reported == Some(status.running) {
+                    continue;
+                }
+                let Some(app) = app.upgrade() else {
+                    break;
+                };
+
+                let notification = if status.running {
+                    let notification = gio::Notification::new("Backend is up");
+                    notification.set_body(Some(&format!(
+                        "Responding in {} ms",
+                        status.latency_ms
+                    )));
+                    notification
+                } else {
+                    let notification = gio::Notification::new("Backend is down");
+                    notification.set_body(status.message.as_deref());
+                    notification
+                };
+                // Same id, so a newer state replaces an unread older one
+                app.send_notification(Some("server-status"), &notification);
+
+                reported = Some(status.running);
+                last_sent = Some(Instant::now());
+            }
+        });
+    }
+}
@@ -0,0 +1,70 @@
+//! Local status socket and metrics endpoint, shared with headless mode
+
+use super::{VibeProxyApp, SUBSYSTEM_STOP_TIMEOUT};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{error, warn};
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::server_manager::{ServerManager, ServerStatus};
+use vibeproxy_linux::shutdown::ShutdownHooks;
+use vibeproxy_linux::{metrics, status_socket};
+
+impl VibeProxyApp {
+    /// Serve the JSON status socket for local monitoring scripts
+    pub(crate) fn start_status_socket(
+        config: &Config,
+        statuses: watch::Receiver<Option<ServerStatus>>,
+        runtime: &tokio::runtime::Handle,
+        shutdown: &ShutdownHooks,
+    ) {
+        let Some(path) = status_socket::socket_path(&config.status_socket) else {
+            if config.status_socket.enabled {
+                warn!("No runtime directory, status socket disabled");
+            }
+            return;
+        };
+
+        let task = runtime.spawn({
+            let path = path.clone();
+            async move {
+                if let Err(e) = status_socket::serve(statuses, path).await {
+                    error!("Status socket stopped: {:#}", e);
+                }
+            }
+        });
+
+        shutdown.register("status socket", SUBSYSTEM_STOP_TIMEOUT, move || async move {
+            task.abort();
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        });
+    }
+
+    /// Serve Prometheus metrics when `metrics.enabled` is set
+    pub(crate) fn start_metrics(
+        config: &Config,
+        server_manager: &Arc<ServerManager>,
+        statuses: watch::Receiver<Option<ServerStatus>>,
+        runtime: &tokio::runtime::Handle,
+        shutdown: &ShutdownHooks,
+    ) {
+        if !config.metrics.enabled {
+            return;
+        }
+
+        let server_manager = server_manager.clone();
+        let settings = config.metrics.clone();
+        let task = runtime.spawn(async move {
+            if let Err(e) = metrics::serve(server_manager, statuses, settings).await {
+                error!("Metrics endpoint stopped: {:#}", e);
+            }
+        });
+
+        shutdown.register("metrics endpoint", SUBSYSTEM_STOP_TIMEOUT, move || async move {
+            task.abort();
+            Ok(())
+        });
+    }
+}
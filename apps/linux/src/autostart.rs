@@ -0,0 +1,174 @@
+//! Starting VibeProxy at login
+//!
+//! Either an XDG autostart entry (`~/.config/autostart/vibeproxy.desktop`),
+//! which every desktop honors, or a systemd user unit tied to the graphical
+//! session. Both launch the running executable with `--start-minimized`.
+
+use crate::branding::Branding;
+use anyhow::{bail, Context, Result};
+use directories::BaseDirs;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::info;
+
+const DESKTOP_FILE: &str = "vibeproxy.desktop";
+const UNIT_FILE: &str = "vibeproxy.service";
+const SESSION_TARGET: &str = "graphical-session.target";
+const LAUNCH_ARGS: &str = "--start-minimized";
+
+/// How VibeProxy gets started at login
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    DesktopEntry,
+    SystemdUnit,
+}
+
+impl Method {
+    /// Where this method's file lives
+    pub fn path(self) -> Option<PathBuf> {
+        let config_dir = BaseDirs::new()?.config_dir().to_path_buf();
+        Some(match self {
+            Method::DesktopEntry => config_dir.join("autostart").join(DESKTOP_FILE),
+            Method::SystemdUnit => config_dir.join("systemd/user").join(UNIT_FILE),
+        })
+    }
+
+    /// Whether VibeProxy will start at the next login this way
+    pub fn is_enabled(self) -> bool {
+        let Some(path) = self.path() else {
+            return false;
+        };
+        match self {
+            Method::DesktopEntry => path.exists(),
+            // The unit only runs once `systemctl --user enable` linked it
+            Method::SystemdUnit => path
+                .parent()
+                .map(|dir| dir.join(format!("{}.wants", SESSION_TARGET)).join(UNIT_FILE))
+                .is_some_and(|link| link.exists()),
+        }
+    }
+
+    /// Write the file for the current executable and enable it
+    pub fn enable(self) -> Result<PathBuf> {
+        let path = self.path().context("Could not determine the config directory")?;
+        let exe = std::env::current_exe().context("Could not find the VibeProxy executable")?;
+        let branding = Branding::current();
+        let content = match self {
+            Method::DesktopEntry => desktop_entry(&exe, &branding.app_name, &branding.icon_name),
+            Method::SystemdUnit => systemd_unit(&exe, &branding.app_name),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        }
+        fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))?;
+
+        if self == Method::SystemdUnit {
+            systemctl(&["daemon-reload"])?;
+            systemctl(&["enable", UNIT_FILE])?;
+        }
+        info!("Autostart enabled: {:?}", path);
+        Ok(path)
+    }
+
+    /// Remove the file; nothing to do if it isn't there
+    pub fn disable(self) -> Result<()> {
+        let path = self.path().context("Could not determine the config directory")?;
+        if !path.exists() {
+            return Ok(());
+        }
+
+        if self == Method::SystemdUnit {
+            systemctl(&["disable", UNIT_FILE])?;
+        }
+        fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+        if self == Method::SystemdUnit {
+            systemctl(&["daemon-reload"])?;
+        }
+        info!("Autostart disabled: {:?}", path);
+        Ok(())
+    }
+}
+
+fn desktop_entry(exe: &Path, name: &str, icon: &str) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={name}\n\
+         Exec={} {LAUNCH_ARGS}\n\
+         Icon={icon}\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        quote_exec(exe)
+    )
+}
+
+/// `Type=exec` rather than `notify`: readiness waits for the backend, which
+/// `--start-minimized` doesn't start
+fn systemd_unit(exe: &Path, name: &str) -> String {
+    format!(
+        "[Unit]\n\
+         Description={name}\n\
+         PartOf={SESSION_TARGET}\n\
+         After={SESSION_TARGET}\n\
+         \n\
+         [Service]\n\
+         Type=exec\n\
+         ExecStart={} {LAUNCH_ARGS}\n\
+         Restart=on-failure\n\
+         \n\
+         [Install]\n\
+         WantedBy={SESSION_TARGET}\n",
+        quote_exec(exe)
+    )
+}
+
+/// Double-quote a path for an `Exec=`/`ExecStart=` line
+///
+/// `%` is doubled since both formats expand it.
+fn quote_exec(path: &Path) -> String {
+    let mut quoted = String::from("\"");
+    for c in path.to_string_lossy().chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '%' => quoted.push_str("%%"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn systemctl(args: &[&str]) -> Result<()> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .context("Failed to run systemctl")?;
+    if !output.status.success() {
+        bail!(
+            "systemctl --user {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exec_line_quotes_install_path() {
+        let entry = desktop_entry(Path::new("/opt/Vibe Proxy/100%/vibeproxy"), "VibeProxy", "vp");
+        assert!(entry.contains("Exec=\"/opt/Vibe Proxy/100%%/vibeproxy\" --start-minimized\n"));
+
+        let unit = systemd_unit(Path::new("/home/me/bin/\"vp\""), "VibeProxy");
+        assert!(unit.contains("ExecStart=\"/home/me/bin/\\\"vp\\\"\" --start-minimized\n"));
+    }
+}
@@ -0,0 +1,61 @@
+//! Streaming test chat completions
+
+use super::{BackendApi, LAST_FAILED_REQUEST};
+use crate::completion_stream::{parse_event, reply_text, SseDecoder, StreamEvent};
+use anyhow::{bail, Context, Result};
+use reqwest::header::CONTENT_TYPE;
+use tracing::warn;
+
+impl BackendApi {
+    /// Stream a reply to `prompt` from `model`, handing each piece to `on_text` as it arrives
+    ///
+    /// A minimal `/v1/chat/completions` request. An error status, or an error
+    /// event partway through the stream, fails with the provider's message as
+    /// sent. A backend that ignores `stream` and answers with one JSON body
+    /// works too.
+    pub async fn test_completion(
+        &self,
+        model: &str,
+        prompt: &str,
+        mut on_text: impl FnMut(&str),
+    ) -> Result<()> {
+        let body = serde_json::json!({
+            "model": model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "stream": true,
+        });
+        let request = self.http.post(self.url("/v1/chat/completions")).json(&body);
+        let (request_id, mut response) = self.dispatch("test completion", request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            warn!(request_id = %request_id, "Backend test completion request failed: {}", status);
+            *LAST_FAILED_REQUEST.lock().unwrap() = Some(request_id);
+            bail!("HTTP {}: {}", status.as_u16(), body.trim());
+        }
+
+        let streamed = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        if !streamed {
+            let body = response.text().await.context("Failed to read the completion")?;
+            on_text(&reply_text(&body)?);
+            return Ok(());
+        }
+
+        let mut decoder = SseDecoder::default();
+        while let Some(bytes) = response.chunk().await.context("The completion stream broke off")? {
+            for data in decoder.push(&bytes) {
+                match parse_event(&data)? {
+                    StreamEvent::Text(text) if !text.is_empty() => on_text(&text),
+                    StreamEvent::Text(_) => {}
+                    StreamEvent::Done => return Ok(()),
+                }
+            }
+        }
+        Ok(())
+    }
+}
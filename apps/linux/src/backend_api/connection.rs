@@ -0,0 +1,50 @@
+//! HTTP client settings shared by every management request
+
+use crate::config::{check_header, ClientSettings};
+use anyhow::{bail, Context, Result};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Certificate, Identity, Proxy};
+
+/// `client.extra_headers` as default headers, checked like `Config::validate` does
+pub(super) fn extra_headers(client: &ClientSettings) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &client.extra_headers {
+        check_header(name, value).with_context(|| format!("Extra header '{}'", name))?;
+        headers.insert(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?);
+    }
+    Ok(headers)
+}
+
+/// Apply the configured proxy, CA and client certificate, read fresh from disk
+pub(super) fn with_connection_settings(
+    mut builder: reqwest::ClientBuilder,
+    settings: &ClientSettings,
+) -> Result<reqwest::ClientBuilder> {
+    if let Some(url) = &settings.http_proxy {
+        let proxy = Proxy::all(url).with_context(|| format!("Invalid proxy URL '{}'", url))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = &settings.ca_cert_path {
+        let pem = std::fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+        let cert = Certificate::from_pem(&pem)
+            .with_context(|| format!("Invalid CA certificate {:?}", path))?;
+        builder = builder.add_root_certificate(cert);
+    }
+
+    match (&settings.client_cert_path, &settings.client_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            let cert = std::fs::read(cert_path)
+                .with_context(|| format!("Failed to read {:?}", cert_path))?;
+            let key = std::fs::read(key_path)
+                .with_context(|| format!("Failed to read {:?}", key_path))?;
+            let identity = Identity::from_pkcs8_pem(&cert, &key)
+                .with_context(|| format!("Invalid client certificate {:?}", cert_path))?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => bail!("client_cert_path and client_key_path must be set together"),
+    }
+
+    Ok(builder)
+}
@@ -0,0 +1,220 @@
+//! Backend management API
+//!
+//! Endpoints beyond the health check offered by `vibeproxy_core::BackendClient`,
+//! called over plain HTTP the same way the Windows app does.
+
+mod completion;
+mod connection;
+mod models;
+mod routing;
+mod status;
+mod usage;
+
+pub use models::{DiscoveredModel, ModelDiscovery};
+pub use routing::Routing;
+pub use status::{BackendDetails, ProviderReachability};
+pub use usage::{ProviderUsage, UsageMetrics};
+
+use crate::backend_connection::Health;
+use crate::config::{unix_socket_path, Config};
+use anyhow::{bail, Context, Result};
+use connection::{extra_headers, with_connection_settings};
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::error::Error as _;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tracing::{debug, warn};
+use uuid::Uuid;
+use vibeproxy_core::ClientError;
+
+/// Header carrying a per-request id the backend can log
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+static LAST_FAILED_REQUEST: Mutex<Option<String>> = Mutex::new(None);
+
+/// The backend's certificate was rejected, so no request can get through
+///
+/// Attached to the request error; find it with `downcast_ref`.
+#[derive(Debug, Clone, Error)]
+#[error(
+    "TLS verification failed for {url}: the backend's certificate isn't trusted. If it is \
+     signed by a private CA (or a proxy re-signs traffic), set client.ca_cert_path to that \
+     CA's PEM file"
+)]
+pub struct TlsVerificationError {
+    pub url: String,
+}
+
+/// The backend turned the API key down, as opposed to not answering
+///
+/// Attached to the request error like [`TlsVerificationError`]; the core
+/// `ClientError` has no variant for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum AuthError {
+    #[error("The backend rejected the API key (HTTP 401)")]
+    Unauthorized,
+    #[error("The API key isn't allowed to do this (HTTP 403)")]
+    Forbidden,
+}
+
+impl AuthError {
+    pub fn from_status(status: u16) -> Option<Self> {
+        match status {
+            401 => Some(Self::Unauthorized),
+            403 => Some(Self::Forbidden),
+            _ => None,
+        }
+    }
+}
+
+pub struct BackendApi {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl BackendApi {
+    pub fn new(config: &Config) -> Result<Self> {
+        let backend = &config.core.backend;
+        if unix_socket_path(&backend.url)?.is_some() {
+            bail!(
+                "The management API needs a TCP backend; {} only answers health checks",
+                backend.url
+            );
+        }
+
+        let base_url = format!("{}:{}", backend.url.trim_end_matches('/'), backend.port);
+        Self::build(config, base_url, backend.api_key.as_deref())
+    }
+
+    /// Call `base_url` directly instead of the backend, e.g. a provider's local mock
+    ///
+    /// Request paths start with `/v1`, so a trailing `/v1` on `base_url` is
+    /// dropped. Uses the backend's timeout and the client settings.
+    pub fn with_base_url(config: &Config, base_url: &str, api_key: Option<&str>) -> Result<Self> {
+        let base_url = base_url.trim_end_matches('/');
+        let base_url = base_url.strip_suffix("/v1").unwrap_or(base_url);
+        Self::build(config, base_url.to_string(), api_key)
+    }
+
+    fn build(config: &Config, base_url: String, api_key: Option<&str>) -> Result<Self> {
+        let mut headers = extra_headers(&config.client)?;
+        if let Some(api_key) = api_key {
+            let value = HeaderValue::from_str(&format!("Bearer {}", api_key))
+                .context("API key is not a valid header value")?;
+            headers.insert(AUTHORIZATION, value);
+        }
+
+        let builder = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(Duration::from_secs(config.core.backend.timeout_secs));
+        let http = with_connection_settings(builder, &config.client)?
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        Ok(Self { http, base_url })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// `GET /health` like the core client, but with this client's headers and TLS settings
+    pub async fn health_check(&self) -> Result<Health, ClientError> {
+        let started = Instant::now();
+        let response = self.http.get(self.url("/health")).send().await.map_err(|e| {
+            debug!("Health check of {} failed: {}", self.base_url, e);
+            ClientError::Unavailable
+        })?;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        Ok(Health::from_status(response.status().as_u16(), latency_ms))
+    }
+
+    /// Send `request` and fail on an error status
+    async fn send(&self, operation: &str, request: RequestBuilder) -> Result<Response> {
+        let (request_id, response) = self.dispatch(operation, request).await?;
+        check_status(operation, &request_id, response)
+    }
+
+    /// Send `request` tagged with a fresh `X-Request-Id`, returning the id with the response
+    async fn dispatch(
+        &self,
+        operation: &str,
+        request: RequestBuilder,
+    ) -> Result<(String, Response)> {
+        let request_id = Uuid::new_v4().to_string();
+        debug!(request_id = %request_id, "Backend {} request", operation);
+
+        match request.header(REQUEST_ID_HEADER, &request_id).send().await {
+            Ok(response) => Ok((request_id, response)),
+            Err(e) => {
+                let untrusted = is_certificate_error(&e);
+                let error = request_failed(operation, &request_id, e, "Failed to reach backend");
+                if untrusted {
+                    Err(error.context(TlsVerificationError {
+                        url: self.base_url.clone(),
+                    }))
+                } else {
+                    Err(error)
+                }
+            }
+        }
+    }
+}
+
+/// Id of the most recent backend request that failed, to quote when comparing with backend logs
+pub fn last_failed_request_id() -> Option<String> {
+    LAST_FAILED_REQUEST.lock().unwrap().clone()
+}
+
+/// Whether `error` is the backend answering that it doesn't have the endpoint
+fn is_unsupported(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        .is_some_and(|status| {
+            matches!(status, StatusCode::NOT_FOUND | StatusCode::NOT_IMPLEMENTED)
+        })
+}
+
+fn check_status(operation: &str, request_id: &str, response: Response) -> Result<Response> {
+    let auth = AuthError::from_status(response.status().as_u16());
+    response.error_for_status().map_err(|e| {
+        let error = request_failed(operation, request_id, e, "Backend rejected request");
+        match auth {
+            Some(auth) => error.context(auth),
+            None => error,
+        }
+    })
+}
+
+fn request_failed(
+    operation: &str,
+    request_id: &str,
+    error: reqwest::Error,
+    summary: &str,
+) -> anyhow::Error {
+    warn!(request_id = %request_id, "Backend {} request failed: {}", operation, error);
+    *LAST_FAILED_REQUEST.lock().unwrap() = Some(request_id.to_string());
+    anyhow::Error::new(error).context(format!(
+        "{} ({} request {})",
+        summary, operation, request_id
+    ))
+}
+
+/// Whether `error` came from the backend's certificate failing verification
+///
+/// reqwest doesn't classify TLS errors, so the causes are checked for the
+/// wording rustls and OpenSSL use.
+fn is_certificate_error(error: &reqwest::Error) -> bool {
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let message = cause.to_string().to_lowercase();
+        if message.contains("certificate") || message.contains("unknownissuer") {
+            return true;
+        }
+        source = cause.source();
+    }
+    false
+}
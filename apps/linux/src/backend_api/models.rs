@@ -0,0 +1,133 @@
+//! Model discovery through the OpenAI-compatible `/v1/models`
+
+use super::{is_unsupported, BackendApi};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+/// Safety net against a backend that keeps reporting `has_more`
+const MAX_MODEL_PAGES: usize = 50;
+
+impl BackendApi {
+    /// Discover the models the backend serves via its OpenAI-compatible `/v1/models`
+    ///
+    /// Follows `has_more`/`last_id` pagination. Entries that can't be
+    /// attributed to a provider are skipped and counted.
+    pub async fn discover_models(&self) -> Result<ModelDiscovery> {
+        let mut discovery = ModelDiscovery::default();
+        let mut after: Option<String> = None;
+
+        for _ in 0..MAX_MODEL_PAGES {
+            let mut request = self.http.get(self.url("/v1/models"));
+            if let Some(after) = &after {
+                request = request.query(&[("after", after)]);
+            }
+
+            let page = match self.fetch_model_page(request).await {
+                Ok(page) => page,
+                // Keep what earlier pages returned
+                Err(e) if after.is_some() => {
+                    warn!("Model discovery incomplete: {:#}", e);
+                    discovery.truncated = true;
+                    return Ok(discovery);
+                }
+                Err(e) => return Err(e),
+            };
+
+            for entry in &page.data {
+                match DiscoveredModel::from_entry(entry) {
+                    Some(model) => discovery.models.push(model),
+                    None => discovery.skipped += 1,
+                }
+            }
+
+            match (
+                page.has_more,
+                page.last_id.or_else(|| page.data.last()?.id.clone()),
+            ) {
+                (true, Some(last)) => after = Some(last),
+                _ => {
+                    info!("Discovered {} models", discovery.models.len());
+                    return Ok(discovery);
+                }
+            }
+        }
+
+        warn!("Model discovery stopped after {} pages", MAX_MODEL_PAGES);
+        discovery.truncated = true;
+        Ok(discovery)
+    }
+
+    /// The models the backend serves, or `None` if it has no models endpoint
+    pub async fn list_models(&self) -> Result<Option<ModelDiscovery>> {
+        match self.discover_models().await {
+            Ok(discovery) => Ok(Some(discovery)),
+            Err(e) if is_unsupported(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn fetch_model_page(&self, request: reqwest::RequestBuilder) -> Result<ModelPage> {
+        self.send("model discovery", request)
+            .await?
+            .json()
+            .await
+            .context("Invalid model list response")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelPage {
+    #[serde(default)]
+    data: Vec<ModelEntry>,
+    #[serde(default)]
+    has_more: bool,
+    #[serde(default)]
+    last_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelEntry {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    owned_by: Option<String>,
+    #[serde(default, alias = "context_window")]
+    context_length: Option<u64>,
+}
+
+/// Result of [`BackendApi::discover_models`]
+#[derive(Debug, Clone, Default)]
+pub struct ModelDiscovery {
+    pub models: Vec<DiscoveredModel>,
+    /// Entries without an id or a recognizable provider
+    pub skipped: usize,
+    /// Pagination was cut short or a later page failed
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredModel {
+    pub provider: String,
+    pub model: String,
+    /// Context window in tokens, if the backend reports it
+    pub context_length: Option<u64>,
+}
+
+impl DiscoveredModel {
+    /// Use `owned_by`, falling back to a `provider/model` id prefix
+    fn from_entry(entry: &ModelEntry) -> Option<Self> {
+        let id = entry.id.as_deref().filter(|id| !id.is_empty())?;
+        let provider = entry
+            .owned_by
+            .as_deref()
+            .filter(|owner| !owner.is_empty())
+            .or_else(|| id.split_once('/').map(|(prefix, _)| prefix))?;
+
+        Some(Self {
+            provider: provider.to_string(),
+            model: id.to_string(),
+            context_length: entry.context_length,
+        })
+    }
+}
@@ -0,0 +1,40 @@
+//! Which providers the backend routes to
+
+use super::BackendApi;
+use crate::config::RoutingRule;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+impl BackendApi {
+    /// Providers the backend currently routes to, with their base URLs and rules
+    pub async fn routing(&self) -> Result<Routing> {
+        let routing: Routing = self
+            .send("routing", self.http.get(self.url("/api/v1/routing")))
+            .await?
+            .json()
+            .await
+            .context("Invalid routing response")?;
+        Ok(routing)
+    }
+
+    /// Replace the backend's routing
+    pub async fn update_routing(&self, routing: &Routing) -> Result<()> {
+        let request = self.http.put(self.url("/api/v1/routing")).json(routing);
+        self.send("routing update", request).await?;
+        Ok(())
+    }
+}
+
+/// Which providers the backend routes to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Routing {
+    #[serde(default)]
+    pub enabled_providers: Vec<String>,
+    /// Where to send each provider's traffic instead of its usual endpoint
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub base_urls: BTreeMap<String, String>,
+    /// Model aliases pinned to a provider or split by weight, first match wins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RoutingRule>,
+}
@@ -0,0 +1,91 @@
+//! Backend readiness and diagnostics
+
+use super::{check_status, BackendApi};
+use anyhow::{Context, Result};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+impl BackendApi {
+    /// Whether the backend has finished loading, or `None` if it has no readiness endpoint
+    pub async fn readiness(&self) -> Result<Option<bool>> {
+        let (request_id, response) = self
+            .dispatch("readiness", self.http.get(self.url("/api/v1/ready")))
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(Some(true)),
+            StatusCode::SERVICE_UNAVAILABLE => Ok(Some(false)),
+            StatusCode::NOT_FOUND | StatusCode::NOT_IMPLEMENTED => Ok(None),
+            _ => check_status("readiness", &request_id, response).map(|_| None),
+        }
+    }
+
+    /// Version, uptime and provider reachability, or `None` if the backend has no status endpoint
+    pub async fn details(&self) -> Result<Option<BackendDetails>> {
+        let (request_id, response) = self
+            .dispatch("status", self.http.get(self.url("/api/v1/status")))
+            .await?;
+
+        if matches!(
+            response.status(),
+            StatusCode::NOT_FOUND | StatusCode::NOT_IMPLEMENTED
+        ) {
+            return Ok(None);
+        }
+
+        let details = check_status("status", &request_id, response)?
+            .json()
+            .await
+            .context("Invalid status response")?;
+        Ok(Some(details))
+    }
+}
+
+/// Diagnostics from `/api/v1/status`
+///
+/// Every field is optional; older backends report less or nothing at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendDetails {
+    pub version: Option<String>,
+    pub uptime_secs: Option<u64>,
+    /// Whether the backend can reach each upstream provider
+    pub providers: Vec<ProviderReachability>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderReachability {
+    #[serde(alias = "name")]
+    pub id: String,
+    pub reachable: bool,
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_details_tolerate_older_backends() {
+        let details: BackendDetails = serde_json::from_str(r#"{"active_connections": 2}"#).unwrap();
+        assert_eq!(details, BackendDetails::default());
+
+        let details: BackendDetails = serde_json::from_str(
+            r#"{
+                "version": "1.4.0",
+                "uptime_secs": 3600,
+                "providers": [
+                    {"name": "openai", "reachable": true, "latency_ms": 120},
+                    {"id": "anthropic", "reachable": false, "error": "timeout"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(details.version.as_deref(), Some("1.4.0"));
+        assert_eq!(details.providers[0].id, "openai");
+        assert_eq!(details.providers[1].error.as_deref(), Some("timeout"));
+    }
+}
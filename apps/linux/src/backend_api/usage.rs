@@ -0,0 +1,60 @@
+//! Token usage and spend reported by the backend
+
+use super::{check_status, BackendApi};
+use anyhow::{Context, Result};
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+impl BackendApi {
+    /// Token usage and spend per provider, or `None` if the backend doesn't report cost
+    pub async fn metrics(&self) -> Result<Option<UsageMetrics>> {
+        let (request_id, response) = self
+            .dispatch("metrics", self.http.get(self.url("/api/v1/metrics")))
+            .await?;
+
+        if matches!(
+            response.status(),
+            StatusCode::NOT_FOUND | StatusCode::NOT_IMPLEMENTED
+        ) {
+            return Ok(None);
+        }
+
+        let metrics: UsageMetrics = check_status("metrics", &request_id, response)?
+            .json()
+            .await
+            .context("Invalid metrics response")?;
+
+        Ok(metrics.has_cost_data().then_some(metrics))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UsageMetrics {
+    #[serde(default)]
+    pub providers: Vec<ProviderUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderUsage {
+    pub provider: String,
+    #[serde(default)]
+    pub cost_today_usd: Option<f64>,
+    #[serde(default)]
+    pub cost_month_usd: Option<f64>,
+}
+
+impl UsageMetrics {
+    fn has_cost_data(&self) -> bool {
+        self.providers
+            .iter()
+            .any(|p| p.cost_today_usd.is_some() || p.cost_month_usd.is_some())
+    }
+
+    pub fn total_today_usd(&self) -> f64 {
+        self.providers.iter().filter_map(|p| p.cost_today_usd).sum()
+    }
+
+    pub fn total_month_usd(&self) -> f64 {
+        self.providers.iter().filter_map(|p| p.cost_month_usd).sum()
+    }
+}
@@ -0,0 +1,196 @@
+//! Health checks over TCP or a Unix domain socket
+//!
+//! `vibeproxy_core::BackendClient` only speaks TCP. A backend configured as
+//! `unix:///path/to/backend.sock` gets the same `GET /health` as a plain
+//! HTTP/1.1 request over the socket, with the same bearer token and the same
+//! result, so the rest of the app can't tell the two apart.
+//!
+//! The core client can't send `client.extra_headers` either, and doesn't say
+//! which status code an unhealthy answer had, so a rejected API key would
+//! look like a sick backend. TCP backends are checked through [`BackendApi`]
+//! instead, falling back to the core client only if that can't be built.
+
+use crate::backend_api::{AuthError, BackendApi};
+use crate::config::{unix_socket_path, Config};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tracing::{debug, warn};
+use vibeproxy_core::{BackendClient, BackendConfig, ClientError};
+
+const HEALTH_PATH: &str = "/health";
+/// Enough for a status line, headers and a small JSON body
+const MAX_RESPONSE_BYTES: u64 = 64 * 1024;
+
+/// Outcome of a health check, whichever way the backend was reached
+#[derive(Debug, Clone)]
+pub struct Health {
+    pub healthy: bool,
+    pub latency_ms: u64,
+    pub message: Option<String>,
+    /// Set when the backend answered but turned the API key down
+    pub auth: Option<AuthError>,
+}
+
+impl Health {
+    /// The outcome of a health check the backend answered with HTTP `code`
+    pub fn from_status(code: u16, latency_ms: u64) -> Self {
+        let healthy = (200..300).contains(&code);
+        let auth = AuthError::from_status(code);
+        let message = match auth {
+            _ if healthy => None,
+            Some(auth) => Some(auth.to_string()),
+            None => Some(format!("Backend answered HTTP {}", code)),
+        };
+        Self {
+            healthy,
+            latency_ms,
+            message,
+            auth,
+        }
+    }
+}
+
+pub enum BackendConnection {
+    /// Without extra headers or the answer's status code
+    Tcp(BackendClient),
+    Unix(UnixClient),
+    Api(BackendApi),
+}
+
+impl BackendConnection {
+    /// A socket client for a `unix://` URL, otherwise a [`BackendApi`]
+    pub fn new(config: &Config, backend: &BackendConfig) -> Self {
+        let headers = &config.client.extra_headers;
+        if let Ok(Some(path)) = unix_socket_path(&backend.url) {
+            return Self::Unix(UnixClient::new(path, backend.api_key.clone(), headers.clone()));
+        }
+
+        let mut endpoint_config = config.clone();
+        endpoint_config.core.backend = backend.clone();
+        match BackendApi::new(&endpoint_config) {
+            Ok(api) => Self::Api(api),
+            Err(e) => {
+                warn!("Checking health with the core client: {:#}", e);
+                Self::Tcp(BackendClient::new(backend))
+            }
+        }
+    }
+
+    pub async fn health_check(&self) -> Result<Health, ClientError> {
+        match self {
+            Self::Tcp(client) => client.health_check().await.map(|status| Health {
+                healthy: status.healthy,
+                latency_ms: status.latency_ms,
+                message: status.message,
+                auth: None,
+            }),
+            Self::Unix(client) => client.health_check().await,
+            Self::Api(api) => api.health_check().await,
+        }
+    }
+}
+
+pub struct UnixClient {
+    path: PathBuf,
+    api_key: Option<String>,
+    /// Checked by `Config::validate`, so safe to write as they are
+    headers: BTreeMap<String, String>,
+}
+
+impl UnixClient {
+    pub fn new(path: PathBuf, api_key: Option<String>, headers: BTreeMap<String, String>) -> Self {
+        Self {
+            path,
+            api_key,
+            headers,
+        }
+    }
+
+    /// `GET /health` over the socket; a socket nothing answers on is `Unavailable`
+    pub async fn health_check(&self) -> Result<Health, ClientError> {
+        let started = Instant::now();
+        let response = self.get(HEALTH_PATH).await.map_err(|e| {
+            debug!("Health check over {:?} failed: {}", self.path, e);
+            ClientError::Unavailable
+        })?;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let Some(code) = status_code(&response) else {
+            debug!("Unparseable health response over {:?}", self.path);
+            return Err(ClientError::Unavailable);
+        };
+        Ok(Health::from_status(code, latency_ms))
+    }
+
+    /// The raw response to a one-off `GET path`
+    async fn get(&self, path: &str) -> std::io::Result<Vec<u8>> {
+        let mut stream = UnixStream::connect(&self.path).await?;
+
+        let mut request =
+            format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n", path);
+        for (name, value) in &self.headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        if let Some(api_key) = &self.api_key {
+            request.push_str(&format!("Authorization: Bearer {}\r\n", api_key));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut response = Vec::new();
+        stream
+            .take(MAX_RESPONSE_BYTES)
+            .read_to_end(&mut response)
+            .await?;
+        Ok(response)
+    }
+}
+
+/// The code from an `HTTP/1.x 200 OK` status line
+fn status_code(response: &[u8]) -> Option<u16> {
+    let line = response.split(|&byte| byte == b'\n').next()?;
+    let mut parts = std::str::from_utf8(line).ok()?.split_whitespace();
+    parts.next().filter(|version| version.starts_with("HTTP/"))?;
+    parts.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UnixListener;
+
+    #[tokio::test]
+    async fn test_health_check_over_socket() {
+        let path = std::env::temp_dir().join(format!("vibeproxy-uds-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let headers = BTreeMap::from([("X-Tenant".to_string(), "acme".to_string())]);
+        let client = UnixClient::new(path.clone(), Some("sk-test".to_string()), headers);
+        assert!(matches!(client.health_check().await, Err(ClientError::Unavailable)));
+
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 1024];
+            let read = stream.read(&mut request).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&request[..read]).into_owned()
+        });
+
+        let health = client.health_check().await.unwrap();
+        assert!(health.healthy);
+        let request = server.await.unwrap();
+        assert!(request.starts_with("GET /health HTTP/1.1\r\n"));
+        assert!(request.contains("Authorization: Bearer sk-test\r\n"));
+        assert!(request.contains("X-Tenant: acme\r\n"));
+
+        assert_eq!(health.auth, None);
+        assert_eq!(status_code(b"HTTP/1.1 503 Service Unavailable\r\n"), Some(503));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
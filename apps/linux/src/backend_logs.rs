@@ -0,0 +1,126 @@
+//! Output captured from the spawned backend
+//!
+//! The backend's stdout and stderr are read line by line into a bounded
+//! buffer, so the app can show why it failed without going to the journal.
+
+use crate::logging::redact;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::broadcast;
+use tracing::debug;
+
+/// Lines kept; older ones are dropped
+pub const MAX_LINES: usize = 1000;
+/// Longer lines are cut so a single runaway line can't grow without bound
+pub const MAX_LINE_BYTES: usize = 4096;
+/// New lines a slow subscriber can fall behind by before it misses some
+const UPDATES_CAPACITY: usize = 256;
+
+#[derive(Debug)]
+pub struct BackendLogs {
+    lines: Mutex<VecDeque<String>>,
+    updates: broadcast::Sender<String>,
+}
+
+impl Default for BackendLogs {
+    fn default() -> Self {
+        Self {
+            lines: Mutex::default(),
+            updates: broadcast::channel(UPDATES_CAPACITY).0,
+        }
+    }
+}
+
+impl BackendLogs {
+    pub fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == MAX_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line.clone());
+        // Nobody listening is fine
+        let _ = self.updates.send(line);
+    }
+
+    /// Captured lines, oldest first
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Each line as it is captured; a lagging receiver should re-read [`lines`](Self::lines)
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.updates.subscribe()
+    }
+
+    /// Forget the captured lines
+    pub fn clear(&self) {
+        self.lines.lock().unwrap().clear();
+    }
+
+    /// Read `output` until it closes, appending each line
+    pub async fn capture(&self, output: impl AsyncRead + Unpin) {
+        let mut reader = BufReader::new(output);
+        let mut line = Vec::new();
+
+        loop {
+            let buf = match reader.fill_buf().await {
+                Ok([]) | Err(_) => break,
+                Ok(buf) => buf,
+            };
+            let (chunk, consumed, complete) = match buf.iter().position(|&b| b == b'\n') {
+                Some(end) => (&buf[..end], end + 1, true),
+                None => (buf, buf.len(), false),
+            };
+
+            let room = MAX_LINE_BYTES.saturating_sub(line.len());
+            line.extend_from_slice(&chunk[..chunk.len().min(room)]);
+            reader.consume(consumed);
+
+            if complete {
+                self.push_bytes(&line);
+                line.clear();
+            }
+        }
+
+        if !line.is_empty() {
+            self.push_bytes(&line);
+        }
+    }
+
+    fn push_bytes(&self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        // Kept in memory and shown in the log window, so masked here as well
+        let line = redact(line.trim_end_matches('\r')).into_owned();
+        debug!(target: "vibeproxy::backend", "{}", line);
+        self.push(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bounds_lines_and_line_length() {
+        let logs = BackendLogs::default();
+        let mut output = "x".repeat(MAX_LINE_BYTES * 2);
+        output.push('\n');
+        for i in 0..MAX_LINES {
+            output.push_str(&format!("line {}\n", i));
+        }
+        output.push_str("no newline");
+
+        logs.capture(output.as_bytes()).await;
+
+        let lines = logs.lines();
+        assert_eq!(lines.len(), MAX_LINES);
+        assert_eq!(lines[0], "line 1");
+        assert_eq!(lines[MAX_LINES - 1], "no newline");
+
+        let logs = BackendLogs::default();
+        logs.capture(format!("{}\n", "y".repeat(MAX_LINE_BYTES * 2)).as_bytes())
+            .await;
+        assert_eq!(logs.lines()[0].len(), MAX_LINE_BYTES);
+    }
+}
@@ -0,0 +1,46 @@
+//! Fake Bifrost backend for `vibeproxy --self-test`
+//!
+//! Answers every HTTP request with a healthy JSON status until killed.
+//! Usage: `vibeproxy-fake-backend --port <port>`
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+const BODY: &str = r#"{"status":"ok","healthy":true}"#;
+
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let port = args
+        .iter()
+        .position(|a| a == "--port")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|p| p.parse::<u16>().ok())
+        .unwrap_or(8317);
+
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    eprintln!("fake backend listening on 127.0.0.1:{}", port);
+
+    for stream in listener.incoming().flatten() {
+        if let Err(e) = respond(stream) {
+            eprintln!("fake backend: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream) -> std::io::Result<()> {
+    // Drain the request head; bodies are ignored
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && line != "\r\n" {
+        line.clear();
+    }
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        BODY.len(),
+        BODY
+    )
+}
@@ -0,0 +1,106 @@
+//! App name, icon and application id
+//!
+//! Redistributions can rebrand without patching source: set
+//! `VIBEPROXY_APP_NAME`, `VIBEPROXY_APPLICATION_ID` or `VIBEPROXY_ICON_NAME`
+//! at build time, or ship a `branding.json` that overrides them at runtime.
+//! Missing fields keep the defaults.
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+/// System-wide branding file for packaged redistributions
+const SYSTEM_BRANDING_PATH: &str = "/etc/vibeproxy/branding.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Branding {
+    /// Shown in the window title, tray and About dialog
+    pub app_name: String,
+    /// GTK/D-Bus application id, e.g. `com.vibeproxy.app`
+    pub application_id: String,
+    /// Icon theme name, also used to look up `<icon_name>.png` in the usual locations
+    pub icon_name: String,
+    /// Explicit icon file, takes precedence over `icon_name`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_path: Option<PathBuf>,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Self {
+            app_name: option_env!("VIBEPROXY_APP_NAME")
+                .unwrap_or("VibeProxy")
+                .to_string(),
+            application_id: option_env!("VIBEPROXY_APPLICATION_ID")
+                .unwrap_or("com.vibeproxy.app")
+                .to_string(),
+            icon_name: option_env!("VIBEPROXY_ICON_NAME")
+                .unwrap_or("vibeproxy")
+                .to_string(),
+            icon_path: None,
+        }
+    }
+}
+
+impl Branding {
+    /// Branding for this process, loaded once
+    pub fn current() -> &'static Branding {
+        static CURRENT: OnceLock<Branding> = OnceLock::new();
+        CURRENT.get_or_init(Self::load)
+    }
+
+    /// Build-time defaults overridden by the first branding file found
+    ///
+    /// Looks at `$VIBEPROXY_BRANDING`, then `branding.json` in the config
+    /// directory, then `/etc/vibeproxy/branding.json`.
+    pub fn load() -> Self {
+        for path in Self::search_paths() {
+            if !path.exists() {
+                continue;
+            }
+            match Self::load_from(&path) {
+                Ok(branding) => {
+                    info!("Branding loaded from {:?}", path);
+                    return branding;
+                }
+                Err(e) => warn!("Ignoring branding file: {:#}", e),
+            }
+        }
+        Self::default()
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let contents =
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {:?}", path))
+    }
+
+    fn search_paths() -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Some(path) = std::env::var_os("VIBEPROXY_BRANDING") {
+            paths.push(PathBuf::from(path));
+        }
+        if let Some(dirs) = ProjectDirs::from("com", "vibeproxy", "VibeProxy") {
+            paths.push(dirs.config_dir().join("branding.json"));
+        }
+        paths.push(PathBuf::from(SYSTEM_BRANDING_PATH));
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_file_keeps_defaults() {
+        let branding: Branding = serde_json::from_str(r#"{"app_name": "Acme Proxy"}"#).unwrap();
+        assert_eq!(branding.app_name, "Acme Proxy");
+        assert_eq!(branding.application_id, Branding::default().application_id);
+        assert!(branding.icon_path.is_none());
+    }
+}
@@ -0,0 +1,117 @@
+//! Reading a streamed chat completion
+//!
+//! OpenAI-compatible backends stream server-sent events, each a `data:` line
+//! holding one JSON chunk, and finish with `data: [DONE]`. Reads off the
+//! network don't line up with events (or even characters), so bytes are
+//! buffered until a blank line completes an event.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+/// Splits a byte stream into the data of its events
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SseDecoder {
+    /// Add bytes as they arrive, returning the data of each event they complete
+    ///
+    /// Comments, `event:` and `id:` lines are dropped; an event with several
+    /// `data:` lines has them joined by newlines.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend(bytes.iter().filter(|&&b| b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|pair| pair == b"\n\n") {
+            let raw: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let event = String::from_utf8_lossy(&raw);
+            let data: Vec<&str> = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(|data| data.strip_prefix(' ').unwrap_or(data))
+                .collect();
+            if !data.is_empty() {
+                events.push(data.join("\n"));
+            }
+        }
+        events
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// The next piece of the reply, often empty for role or finish chunks
+    Text(String),
+    Done,
+}
+
+/// Read one event's data, failing with the provider's error if it sent one
+pub fn parse_event(data: &str) -> Result<StreamEvent> {
+    if data.trim() == "[DONE]" {
+        return Ok(StreamEvent::Done);
+    }
+    let chunk: Value = serde_json::from_str(data)
+        .with_context(|| format!("Unexpected data in the stream: {}", data))?;
+    if let Some(error) = chunk.get("error") {
+        bail!("{}", provider_error(error));
+    }
+    let text = chunk.pointer("/choices/0/delta/content").and_then(Value::as_str);
+    Ok(StreamEvent::Text(text.unwrap_or_default().to_string()))
+}
+
+/// The reply in a complete response, from a backend that doesn't stream
+pub fn reply_text(body: &str) -> Result<String> {
+    let response: Value = serde_json::from_str(body)
+        .with_context(|| format!("Unexpected completion response: {}", body))?;
+    if let Some(error) = response.get("error") {
+        bail!("{}", provider_error(error));
+    }
+    let text = response.pointer("/choices/0/message/content").and_then(Value::as_str);
+    Ok(text.unwrap_or_default().to_string())
+}
+
+/// The error's `message` as the provider wrote it, or the whole error if it has none
+fn provider_error(error: &Value) -> String {
+    match error.get("message").and_then(Value::as_str) {
+        Some(message) => message.to_string(),
+        None => error.as_str().map_or_else(|| error.to_string(), str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_split_across_reads() {
+        let stream = "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\r\n\r\n\
+                      : keep-alive\n\n\
+                      data: {\"choices\":[{\"delta\":{\"content\":\"Grüß \"}}]}\n\n\
+                      data: {\"choices\":[{\"delta\":{\"content\":\"dich\"}}]}\n\n\
+                      data: [DONE]\n\n";
+        let mut decoder = SseDecoder::default();
+        // Three bytes at a time splits the `ü` and `ß`
+        let events: Vec<StreamEvent> = stream
+            .as_bytes()
+            .chunks(3)
+            .flat_map(|chunk| decoder.push(chunk))
+            .map(|data| parse_event(&data).unwrap())
+            .collect();
+        let reply: String = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::Text(text) => Some(text.as_str()),
+                StreamEvent::Done => None,
+            })
+            .collect();
+        assert_eq!(reply, "Grüß dich");
+        assert_eq!(events.len(), 4);
+        assert_eq!(events.last(), Some(&StreamEvent::Done));
+
+        let error = r#"{"error":{"message":"The model `gpt-9` does not exist","code":404}}"#;
+        let message = parse_event(error).unwrap_err().to_string();
+        assert_eq!(message, "The model `gpt-9` does not exist");
+        assert_eq!(reply_text(r#"{"choices":[{"message":{"content":"Hi"}}]}"#).unwrap(), "Hi");
+    }
+}
@@ -0,0 +1,229 @@
+//! How the app connects to the backend, and where else it can turn
+
+use crate::failover::FailoverStrategy;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Headers `client.extra_headers` can't set: HTTP framing, the API key and the request id
+const RESERVED_HEADERS: &[&str] = &[
+    "host",
+    "authorization",
+    "connection",
+    "content-length",
+    "transfer-encoding",
+    "x-request-id",
+];
+/// Header names whose values are shown masked
+const SECRET_HEADER_WORDS: &[&str] = &["auth", "key", "token", "secret", "password", "cookie"];
+
+/// Check an extra header is valid HTTP and not one the app sets itself
+pub fn check_header(name: &str, value: &str) -> Result<()> {
+    let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+        .context("is not a valid header name")?;
+    if RESERVED_HEADERS.contains(&name.as_str()) {
+        bail!("is set by VibeProxy and can't be overridden");
+    }
+    reqwest::header::HeaderValue::from_str(value)
+        .context("has a value that isn't valid in a header")?;
+    Ok(())
+}
+
+/// How the app talks to the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    /// Maximum backend requests the app has in flight at once
+    pub max_concurrent_requests: usize,
+    /// Deadline for each health check, after which the backend counts as unavailable
+    pub request_timeout_ms: u64,
+    /// Outbound HTTP(S) proxy for backend requests, e.g. `http://proxy.corp:3128`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_proxy: Option<String>,
+    /// Extra CA certificate (PEM) to trust for the backend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_cert_path: Option<PathBuf>,
+    /// Client certificate (PEM) for mutual TLS, used with `client_key_path`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_cert_path: Option<PathBuf>,
+    /// PKCS#8 private key (PEM) for `client_cert_path`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_key_path: Option<PathBuf>,
+    /// Headers sent with every backend request, e.g. `X-Tenant` for an auth gateway
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra_headers: BTreeMap<String, String>,
+}
+
+impl ClientSettings {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms)
+    }
+
+    /// Whether `other` uses a different proxy or points at different TLS files
+    pub fn connection_changed(&self, other: &ClientSettings) -> bool {
+        self.http_proxy != other.http_proxy
+            || self.ca_cert_path != other.ca_cert_path
+            || self.client_cert_path != other.client_cert_path
+            || self.client_key_path != other.client_key_path
+            || self.extra_headers != other.extra_headers
+    }
+
+    /// `Name: value` for each extra header, with values that look like credentials masked
+    pub fn masked_headers(&self) -> Vec<String> {
+        self.extra_headers
+            .iter()
+            .map(|(name, value)| {
+                let lower = name.to_ascii_lowercase();
+                let secret = SECRET_HEADER_WORDS.iter().any(|word| lower.contains(word))
+                    || crate::logging::redact(value) != value.as_str();
+                format!("{}: {}", name, if secret { "***" } else { value })
+            })
+            .collect()
+    }
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: 4,
+            request_timeout_ms: 5000,
+            http_proxy: None,
+            ca_cert_path: None,
+            client_cert_path: None,
+            client_key_path: None,
+            extra_headers: BTreeMap::new(),
+        }
+    }
+}
+
+/// Additional backend endpoints to fail over to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailoverSettings {
+    pub strategy: FailoverStrategy,
+    /// `scheme://host:port` endpoints tried after the primary backend, in priority order
+    pub endpoints: Vec<String>,
+}
+
+/// Retrying a backend health check that could not connect or timed out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Tries per check, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after each
+    pub base_delay_ms: u64,
+}
+
+impl RetryPolicy {
+    /// Delay before the retry that follows failed attempt number `attempt`
+    pub fn delay(&self, attempt: u32) -> Duration {
+        Duration::from_millis(self.base_delay_ms)
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 200,
+        }
+    }
+}
+
+/// Backend endpoints offered for quick switching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecentEndpoints {
+    /// Most recently used first, capped at `max_recent`
+    pub recent: Vec<String>,
+    /// Favorites, always offered and never evicted
+    pub pinned: Vec<String>,
+    pub max_recent: usize,
+}
+
+impl Default for RecentEndpoints {
+    fn default() -> Self {
+        Self {
+            recent: Vec::new(),
+            pinned: Vec::new(),
+            max_recent: 5,
+        }
+    }
+}
+
+impl RecentEndpoints {
+    /// Move `endpoint` to the front of the recent list
+    pub fn record(&mut self, endpoint: &str) {
+        self.recent.retain(|e| e != endpoint);
+        self.recent.insert(0, endpoint.to_string());
+        self.recent.truncate(self.max_recent);
+    }
+
+    pub fn is_pinned(&self, endpoint: &str) -> bool {
+        self.pinned.iter().any(|e| e == endpoint)
+    }
+
+    pub fn set_pinned(&mut self, endpoint: &str, pinned: bool) {
+        if !pinned {
+            self.pinned.retain(|e| e != endpoint);
+        } else if !self.is_pinned(endpoint) {
+            self.pinned.push(endpoint.to_string());
+        }
+    }
+
+    /// Pinned endpoints, then recent ones, without duplicates
+    pub fn choices(&self) -> Vec<String> {
+        let mut choices = self.pinned.clone();
+        for endpoint in &self.recent {
+            if !choices.contains(endpoint) {
+                choices.push(endpoint.clone());
+            }
+        }
+        choices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_looking_headers_are_masked() {
+        let mut client = ClientSettings::default();
+        for (name, value) in [
+            ("X-Tenant", "acme"),
+            ("X-Gateway-Token", "abc"),
+            ("X-Upstream", "Bearer eyJhbGciOiJIUzI1NiJ9"),
+        ] {
+            client.extra_headers.insert(name.to_string(), value.to_string());
+        }
+        assert_eq!(
+            client.masked_headers(),
+            ["X-Gateway-Token: ***", "X-Tenant: acme", "X-Upstream: ***"]
+        );
+    }
+
+    #[test]
+    fn test_recent_endpoints_cap_and_pinning() {
+        let mut endpoints = RecentEndpoints {
+            max_recent: 2,
+            ..Default::default()
+        };
+        endpoints.record("http://a:1");
+        endpoints.record("http://b:1");
+        endpoints.record("http://a:1");
+        endpoints.record("http://c:1");
+        assert_eq!(endpoints.recent, ["http://c:1", "http://a:1"]);
+
+        endpoints.set_pinned("http://b:1", true);
+        endpoints.record("http://d:1");
+        assert_eq!(
+            endpoints.choices(),
+            ["http://b:1", "http://d:1", "http://c:1"]
+        );
+    }
+}
@@ -0,0 +1,324 @@
+//! Application configuration
+//!
+//! The on-disk file holds the shared core `AppConfig` plus the settings that
+//! only the Linux app cares about. The core fields are flattened so existing
+//! config files keep loading unchanged.
+
+mod client;
+mod monitoring;
+mod process;
+mod profiles;
+mod providers;
+mod storage;
+mod ui;
+mod urls;
+
+pub use client::{check_header, ClientSettings, FailoverSettings, RecentEndpoints, RetryPolicy};
+pub use monitoring::{
+    HealthSettings, HistorySettings, LogSettings, MetricsSettings, StatusSocketSettings,
+};
+pub use process::{BackendProcessSettings, RestartPolicy};
+pub use profiles::{profile_key_secret, Profile};
+pub use providers::{Provider, ProviderMerge, RoutingRule};
+pub use storage::{BackupSettings, KeyringSettings, SecretBackend};
+pub use ui::{Theme, TrayMode, UiSettings, WindowState};
+pub use urls::{check_backend_url, endpoint_backend, parse_http_url, unix_socket_path};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
+use vibeproxy_core::AppConfig;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(flatten)]
+    pub core: AppConfig,
+    #[serde(default)]
+    pub keyring: KeyringSettings,
+    #[serde(default)]
+    pub providers: Vec<Provider>,
+    /// Model aliases pinned to a provider, checked in order by the backend
+    #[serde(default)]
+    pub routing_rules: Vec<RoutingRule>,
+    #[serde(default)]
+    pub health: HealthSettings,
+    #[serde(default)]
+    pub ui: UiSettings,
+    #[serde(default)]
+    pub client: ClientSettings,
+    #[serde(default)]
+    pub failover: FailoverSettings,
+    #[serde(default)]
+    pub status_socket: StatusSocketSettings,
+    #[serde(default)]
+    pub metrics: MetricsSettings,
+    #[serde(default)]
+    pub recent_endpoints: RecentEndpoints,
+    #[serde(default)]
+    pub logging: LogSettings,
+    #[serde(default)]
+    pub history: HistorySettings,
+    #[serde(default)]
+    pub backups: BackupSettings,
+    #[serde(default)]
+    pub backend_process: BackendProcessSettings,
+    #[serde(default)]
+    pub restart_policy: RestartPolicy,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Named backend settings to switch between, e.g. `home` and `work`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// The profile `backend` was last switched to; its edits are kept there
+    #[serde(default)]
+    pub active_profile: Option<String>,
+}
+
+/// Everything [`Config::validate`] found wrong with a config
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid configuration: {}", join_problems(.problems))]
+pub struct ValidationError {
+    pub problems: Vec<Problem>,
+}
+
+/// One invalid field, named in dotted form (`backend.port`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
+}
+
+fn join_problems(problems: &[Problem]) -> String {
+    problems
+        .iter()
+        .map(Problem::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl Config {
+    /// Check the values a parseable file can still get wrong, reporting every problem at once
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut problems = Vec::new();
+        let mut problem = |field: &str, message: String| {
+            problems.push(Problem {
+                field: field.to_string(),
+                message,
+            })
+        };
+
+        let backend = &self.core.backend;
+        match unix_socket_path(&backend.url) {
+            // The port means nothing for a socket
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                match parse_http_url(&backend.url) {
+                    Ok(url) if url.host_str().unwrap_or_default().is_empty() => {
+                        problem("backend.url", "must include a host".to_string())
+                    }
+                    Ok(_) => {}
+                    Err(e) => problem("backend.url", format!("is invalid: {:#}", e)),
+                }
+                if backend.port == 0 {
+                    problem("backend.port", "must be between 1 and 65535".to_string());
+                }
+            }
+            Err(e) => problem("backend.url", format!("is invalid: {:#}", e)),
+        }
+
+        for (field, duration) in [
+            ("backend.timeout_secs", backend.timeout_secs),
+            ("client.request_timeout_ms", self.client.request_timeout_ms),
+            ("keyring.timeout_secs", self.keyring.timeout_secs),
+            ("health.apply_timeout_secs", self.health.apply_timeout_secs),
+            ("health.interval_secs", self.health.interval_secs),
+            ("health.down_interval_secs", self.health.down_interval_secs),
+            ("health.uptime_window_secs", self.health.uptime_window_secs),
+            (
+                "backend_process.startup_timeout_secs",
+                self.backend_process.startup_timeout_secs,
+            ),
+        ] {
+            if duration == 0 {
+                problem(field, "must be positive".to_string());
+            }
+        }
+
+        if let Some(proxy) = &self.client.http_proxy {
+            if let Err(e) = parse_http_url(proxy) {
+                problem("client.http_proxy", format!("is invalid: {:#}", e));
+            }
+        }
+
+        for (name, value) in &self.client.extra_headers {
+            if let Err(e) = check_header(name, value) {
+                problem("client.extra_headers", format!("'{}' {:#}", name, e));
+            }
+        }
+
+        let metrics = &self.metrics;
+        if metrics.enabled && !metrics.bind_address.ip().is_loopback() && !metrics.allow_remote {
+            problem(
+                "metrics.bind_address",
+                format!(
+                    "{} is reachable from other machines; set metrics.allow_remote = true \
+                     to serve metrics there",
+                    metrics.bind_address
+                ),
+            );
+        }
+
+        for (field, value, max) in [
+            ("logging.max_age_days", self.logging.max_age_days, LogSettings::MAX_AGE_DAYS),
+            ("logging.max_total_mb", self.logging.max_total_mb, LogSettings::MAX_TOTAL_MB),
+        ] {
+            if value > max {
+                problem(field, format!("must be at most {}", max));
+            }
+        }
+
+        if self.retry.max_attempts == 0 {
+            problem("retry.max_attempts", "must be at least 1".to_string());
+        }
+
+        for provider in &self.providers {
+            if let Err(e) = provider.validate() {
+                problem("providers", format!("{:#}", e));
+            }
+        }
+
+        let provider_ids: Vec<&str> = self.providers.iter().map(|p| p.id.as_str()).collect();
+        for rule in &self.routing_rules {
+            if rule.pattern.trim().is_empty() {
+                problem("routing_rules", "need a model pattern".to_string());
+            }
+            if !provider_ids.contains(&rule.provider.as_str()) {
+                problem(
+                    "routing_rules",
+                    format!("'{}' routes to unknown provider '{}'", rule.pattern, rule.provider),
+                );
+            }
+            if let Err(e) = rule.validate_weights(&provider_ids) {
+                problem("routing_rules", format!("{:#}", e));
+            }
+        }
+
+        for (name, profile) in &self.profiles {
+            for provider in &profile.providers {
+                if !self.providers.iter().any(|p| &p.id == provider) {
+                    problem(
+                        "profiles",
+                        format!("'{}' lists unknown provider '{}'", name, provider),
+                    );
+                }
+            }
+        }
+
+        if let Some(name) = &self.active_profile {
+            if !self.profiles.contains_key(name) {
+                problem("active_profile", format!("'{}' is not in profiles", name));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { problems })
+        }
+    }
+
+    /// The primary backend as a `scheme://host:port` endpoint, or its `unix://` URL
+    pub fn backend_endpoint(&self) -> String {
+        let backend = &self.core.backend;
+        if matches!(unix_socket_path(&backend.url), Ok(Some(_))) {
+            return backend.url.clone();
+        }
+        format!("{}:{}", backend.url.trim_end_matches('/'), backend.port)
+    }
+
+    /// Point the primary backend at another `scheme://host:port` endpoint
+    pub fn switch_backend(&mut self, endpoint: &str) -> Result<()> {
+        self.core.backend = endpoint_backend(&self.core.backend, endpoint)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_validate_reports_each_invalid_field() {
+        assert!(Config::default().validate().is_ok());
+
+        let invalid: [(&str, fn(&mut Config)); 17] = [
+            ("backend.url", |c| c.core.backend.url = "http://".to_string()),
+            ("backend.url", |c| c.core.backend.url = "localhost".to_string()),
+            ("backend.port", |c| c.core.backend.port = 0),
+            ("backend.timeout_secs", |c| c.core.backend.timeout_secs = 0),
+            ("client.request_timeout_ms", |c| c.client.request_timeout_ms = 0),
+            ("client.http_proxy", |c| c.client.http_proxy = Some("proxy:3128".to_string())),
+            ("client.extra_headers", |c| {
+                c.client.extra_headers.insert("Host".to_string(), "gateway".to_string());
+            }),
+            ("client.extra_headers", |c| {
+                c.client.extra_headers.insert("X-Tenant".to_string(), "a\r\nb".to_string());
+            }),
+            ("keyring.timeout_secs", |c| c.keyring.timeout_secs = 0),
+            ("health.apply_timeout_secs", |c| c.health.apply_timeout_secs = 0),
+            ("health.interval_secs", |c| c.health.interval_secs = 0),
+            ("health.uptime_window_secs", |c| c.health.uptime_window_secs = 0),
+            ("backend_process.startup_timeout_secs", |c| {
+                c.backend_process.startup_timeout_secs = 0
+            }),
+            ("logging.max_age_days", |c| c.logging.max_age_days = u64::MAX),
+            ("logging.max_total_mb", |c| c.logging.max_total_mb = u64::MAX / 1024),
+            ("retry.max_attempts", |c| c.retry.max_attempts = 0),
+            ("metrics.bind_address", |c| {
+                c.metrics.enabled = true;
+                c.metrics.bind_address = SocketAddr::from(([0, 0, 0, 0], 9464));
+            }),
+        ];
+        for (field, break_config) in invalid {
+            let mut config = Config::default();
+            break_config(&mut config);
+            let problems = config.validate().unwrap_err().problems;
+            assert_eq!(problems.len(), 1, "{:?}", problems);
+            assert_eq!(problems[0].field, field);
+        }
+
+        let mut config = Config::default();
+        config.core.backend.port = 0;
+        config.core.backend.timeout_secs = 0;
+        assert_eq!(config.validate().unwrap_err().problems.len(), 2);
+    }
+
+    #[test]
+    fn test_unix_socket_backend_url() {
+        let mut config = Config::default();
+        config.core.backend.url = "unix:///run/bifrost.sock".to_string();
+        config.core.backend.port = 0;
+        assert!(config.validate().is_ok());
+        assert_eq!(config.backend_endpoint(), "unix:///run/bifrost.sock");
+        assert_eq!(
+            unix_socket_path(&config.core.backend.url).unwrap(),
+            Some(PathBuf::from("/run/bifrost.sock"))
+        );
+
+        config.core.backend.url = "unix://localhost:8317/run/bifrost.sock".to_string();
+        let problems = config.validate().unwrap_err().problems;
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].message.contains("mixes a TCP host and a socket path"));
+        assert_eq!(unix_socket_path("http://localhost").unwrap(), None);
+    }
+}
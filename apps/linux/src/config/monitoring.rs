@@ -0,0 +1,155 @@
+//! Health checks, metrics, the status socket, logs and history
+
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Backend health polling settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthSettings {
+    /// Make background health checks at all; when off, status is only refreshed on demand
+    pub polling_enabled: bool,
+    /// Open a connection before the first measured health check so it doesn't include connect/TLS time
+    pub warmup_enabled: bool,
+    /// Seconds between health checks while the backend is reachable
+    pub interval_secs: u64,
+    /// Consecutive failures before the backend is considered down
+    pub failure_threshold: u32,
+    /// Seconds between "is it back yet" probes once the backend is down
+    pub down_interval_secs: u64,
+    /// Seconds after resuming from suspend during which failed checks aren't reported
+    pub resume_grace_secs: u64,
+    /// Latency above which a reachable provider is shown as degraded
+    pub degraded_latency_ms: u64,
+    /// Latency above which a reachable provider is shown as critical
+    pub critical_latency_ms: u64,
+    /// How long a newly applied config has to reach the backend before a revert is offered
+    pub apply_timeout_secs: u64,
+    /// Health checks kept in memory for the reliability strip; 0 turns it off
+    pub recent_checks: usize,
+    /// Seconds of recent checks the uptime percentage covers
+    pub uptime_window_secs: u64,
+}
+
+impl HealthSettings {
+    pub fn uptime_window(&self) -> Duration {
+        Duration::from_secs(self.uptime_window_secs)
+    }
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            polling_enabled: true,
+            warmup_enabled: true,
+            interval_secs: 5,
+            failure_threshold: 3,
+            down_interval_secs: 60,
+            resume_grace_secs: 15,
+            degraded_latency_ms: 1000,
+            critical_latency_ms: 5000,
+            apply_timeout_secs: 10,
+            recent_checks: 120,
+            uptime_window_secs: 3600,
+        }
+    }
+}
+
+/// HTTP endpoint serving the app's own metrics in Prometheus text format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    /// Where `/metrics` is served; loopback unless something else should scrape it
+    pub bind_address: SocketAddr,
+    /// Allow a `bind_address` other machines can reach, such as `0.0.0.0:9464`
+    pub allow_remote: bool,
+    /// Answer only requests with `Authorization: Bearer <token>`, the token
+    /// being stored in the keyring as `metrics.token`
+    pub require_token: bool,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: SocketAddr::from((Ipv4Addr::LOCALHOST, 9464)),
+            allow_remote: false,
+            require_token: false,
+        }
+    }
+}
+
+/// Unix socket that answers each connection with the current status as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusSocketSettings {
+    pub enabled: bool,
+    /// Socket location; defaults to `$XDG_RUNTIME_DIR/vibeproxy/status.sock`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl Default for StatusSocketSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+        }
+    }
+}
+
+/// Log file output and retention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    /// Also write logs to daily files in the data directory
+    pub file_enabled: bool,
+    pub max_files: usize,
+    pub max_total_mb: u64,
+    pub max_age_days: u64,
+}
+
+impl LogSettings {
+    /// Upper bounds checked by `Config::validate`, far above any real retention
+    pub const MAX_AGE_DAYS: u64 = 100 * 365;
+    pub const MAX_TOTAL_MB: u64 = 1024 * 1024;
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            file_enabled: true,
+            max_files: 7,
+            max_total_mb: 50,
+            max_age_days: 14,
+        }
+    }
+}
+
+/// Health history kept across restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// Persist latency samples and start/stop/up/down events in the data directory
+    pub enabled: bool,
+    /// Records older than this are dropped
+    pub retention_days: u64,
+}
+
+impl HistorySettings {
+    pub fn retention(&self) -> Duration {
+        Duration::from_secs(self.retention_days * 24 * 60 * 60)
+    }
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: 7,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Launching and restarting a backend VibeProxy starts itself
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How to launch the Bifrost backend when it isn't already running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendProcessSettings {
+    /// Bifrost binary; a bare name is looked up on `PATH`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<PathBuf>,
+    /// Arguments; `{port}` is replaced with the backend port
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// When the backend port is taken, start on a free port and save it instead of failing
+    pub auto_port: bool,
+    /// How long a freshly spawned backend has to pass a health check
+    pub startup_timeout_secs: u64,
+    /// How long the backend has to exit after SIGTERM before it is killed
+    pub stop_grace_secs: u64,
+    /// Start the backend at launch if it was running when the app last quit
+    pub resume_on_launch: bool,
+}
+
+impl BackendProcessSettings {
+    /// `args` for a backend listening on `port`
+    pub fn args_for_port(&self, port: u16) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|arg| arg.replace("{port}", &port.to_string()))
+            .collect()
+    }
+
+    pub fn startup_timeout(&self) -> Duration {
+        Duration::from_secs(self.startup_timeout_secs)
+    }
+
+    pub fn stop_grace(&self) -> Duration {
+        Duration::from_secs(self.stop_grace_secs)
+    }
+}
+
+impl Default for BackendProcessSettings {
+    fn default() -> Self {
+        Self {
+            binary_path: None,
+            args: Vec::new(),
+            auto_port: false,
+            startup_timeout_secs: 15,
+            stop_grace_secs: 5,
+            resume_on_launch: true,
+        }
+    }
+}
+
+/// Restarting a backend VibeProxy started after it crashes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    /// Restart attempts in a row before giving up; 0 disables restarts
+    pub max_retries: u32,
+    /// Delay before the first restart, doubled after each failed attempt
+    pub base_delay_ms: u64,
+}
+
+impl RestartPolicy {
+    /// Delay before restart attempt number `failures + 1`
+    pub fn delay(&self, failures: u32) -> Duration {
+        Duration::from_millis(self.base_delay_ms)
+            .saturating_mul(2u32.saturating_pow(failures))
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_delay_ms: 1000,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_args_take_the_port() {
+        let settings = BackendProcessSettings {
+            args: vec!["-port".to_string(), "{port}".to_string(), "-v".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(settings.args_for_port(41234), ["-port", "41234", "-v"]);
+    }
+}
@@ -0,0 +1,145 @@
+//! Named backend profiles to switch between
+
+use super::Config;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use vibeproxy_core::BackendConfig;
+
+/// Backend settings saved under a name, e.g. `home` and `work`
+///
+/// The backend fields are flattened, so a profile is written like `backend`
+/// with an optional `providers` list next to them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(flatten)]
+    pub backend: BackendConfig,
+    /// Providers this backend routes to; empty uses every enabled provider
+    ///
+    /// Only their API keys are read from the keyring when the backend is
+    /// started with this profile active.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<String>,
+}
+
+impl Profile {
+    pub fn new(backend: BackendConfig) -> Self {
+        Self {
+            backend,
+            providers: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Add or replace the profile `name`, keeping the provider list of one it replaces
+    pub fn add_profile(&mut self, name: &str, backend: BackendConfig) -> Result<()> {
+        if name.trim().is_empty() {
+            bail!("Profile name is empty");
+        }
+        if self.active_profile.as_deref() == Some(name) {
+            self.core.backend = backend.clone();
+        }
+        match self.profiles.get_mut(name) {
+            Some(profile) => profile.backend = backend,
+            None => {
+                self.profiles.insert(name.to_string(), Profile::new(backend));
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the profile `name`; removing the active one keeps its backend in use
+    pub fn remove_profile(&mut self, name: &str) -> Result<()> {
+        if self.profiles.remove(name).is_none() {
+            bail!("No profile named '{}'", name);
+        }
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = None;
+        }
+        Ok(())
+    }
+
+    /// Make the profile `name` the backend in use
+    ///
+    /// Changes made to `backend` since the last switch are saved to the
+    /// profile that was active.
+    pub fn select_profile(&mut self, name: &str) -> Result<()> {
+        if !self.profiles.contains_key(name) {
+            bail!("No profile named '{}'", name);
+        }
+        if let Some(active) = self.active_profile.take() {
+            if let Some(profile) = self.profiles.get_mut(&active) {
+                profile.backend = self.core.backend.clone();
+            }
+        }
+        self.core.backend = self.profiles[name].backend.clone();
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Enabled providers the backend in use routes to
+    ///
+    /// Those the active profile lists, or all of them if it lists none or
+    /// no profile is active.
+    pub fn active_provider_ids(&self) -> Vec<String> {
+        let enabled = self.enabled_provider_ids();
+        match self.active_profile.as_ref().and_then(|name| self.profiles.get(name)) {
+            Some(profile) if !profile.providers.is_empty() => enabled
+                .into_iter()
+                .filter(|id| profile.providers.contains(id))
+                .collect(),
+            _ => enabled,
+        }
+    }
+}
+
+/// Keyring key (and config path) of the backend API key of profile `name`
+///
+/// Listed in `keyring.stored_secrets` once the key is in the keyring.
+pub fn profile_key_secret(name: &str) -> String {
+    format!("profiles.{}.api_key", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Provider;
+
+    #[test]
+    fn test_select_profile_keeps_edits_to_the_active_one() {
+        let mut config = Config::default();
+        let home = config.core.backend.clone();
+        let mut work = home.clone();
+        work.url = "https://proxy.work.example".to_string();
+        config.add_profile("home", home).unwrap();
+        config.add_profile("work", work).unwrap();
+        assert!(config.select_profile("gym").is_err());
+
+        config.select_profile("home").unwrap();
+        config.core.backend.port = 9000;
+        config.select_profile("work").unwrap();
+        assert_eq!(config.core.backend.url, "https://proxy.work.example");
+        assert_eq!(config.profiles["home"].backend.port, 9000);
+
+        config.remove_profile("work").unwrap();
+        assert_eq!(config.active_profile, None);
+        assert_eq!(config.core.backend.url, "https://proxy.work.example");
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_active_provider_ids_follow_the_profile() {
+        let mut config = Config::default();
+        config.providers = vec![Provider::new("openai"), Provider::new("anthropic")];
+        config.add_profile("work", config.core.backend.clone()).unwrap();
+        assert_eq!(config.active_provider_ids(), vec!["openai", "anthropic"]);
+
+        config.profiles.get_mut("work").unwrap().providers = vec!["anthropic".to_string()];
+        assert_eq!(config.active_provider_ids(), vec!["openai", "anthropic"]);
+        config.select_profile("work").unwrap();
+        assert_eq!(config.active_provider_ids(), vec!["anthropic"]);
+
+        config.providers[1].enabled = false;
+        assert!(config.active_provider_ids().is_empty());
+    }
+}
@@ -0,0 +1,329 @@
+//! Providers the backend routes to and the rules pinning models to them
+
+use super::{parse_http_url, Config};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// An upstream AI provider routed through the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provider {
+    /// Identifier shared with the backend's routing table
+    pub id: String,
+    /// Send this provider's traffic somewhere else, e.g. a local mock
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url_override: Option<String>,
+    /// Models routed to this provider; empty allows all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    /// Disabled providers are left out of the backend's routing
+    #[serde(default = "provider_enabled_default")]
+    pub enabled: bool,
+}
+
+fn provider_enabled_default() -> bool {
+    true
+}
+
+impl Provider {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            base_url_override: None,
+            models: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(url) = &self.base_url_override {
+            parse_http_url(url)
+                .with_context(|| format!("Invalid base URL override for provider '{}'", self.id))?;
+        }
+        Ok(())
+    }
+}
+
+/// Send requests for models matching `pattern` to `provider`
+///
+/// `pattern` is a model alias with `*` wildcards, e.g. `gpt-4*`; the backend
+/// uses the first matching rule. `model` renames the model for the provider.
+/// With `weights`, matching traffic is split across the providers listed
+/// there by relative weight instead, e.g. 70/30 for a gradual rollout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub pattern: String,
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Relative share of traffic per provider; empty sends it all to `provider`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub weights: BTreeMap<String, u32>,
+}
+
+impl RoutingRule {
+    /// Check the split against the configured `provider_ids`
+    ///
+    /// Weights must be positive and name known providers, `provider` among
+    /// them so the rule still reads right to a backend without splits.
+    pub fn validate_weights(&self, provider_ids: &[&str]) -> Result<()> {
+        if self.weights.is_empty() {
+            return Ok(());
+        }
+        for (provider, weight) in &self.weights {
+            if !provider_ids.contains(&provider.as_str()) {
+                bail!("'{}' splits traffic to unknown provider '{}'", self.pattern, provider);
+            }
+            if *weight == 0 {
+                bail!(
+                    "'{}' gives '{}' a weight of 0 (leave it out instead)",
+                    self.pattern,
+                    provider
+                );
+            }
+        }
+        if !self.weights.contains_key(&self.provider) {
+            bail!(
+                "'{}' splits traffic without its provider '{}'",
+                self.pattern,
+                self.provider
+            );
+        }
+        Ok(())
+    }
+
+    /// This rule split by `weights`, dropping providers weighted 0
+    ///
+    /// If `provider` is no longer among them the largest share takes its
+    /// place, and a split down to one provider becomes a plain rule.
+    pub fn with_weights(mut self, mut weights: BTreeMap<String, u32>) -> Self {
+        weights.retain(|_, weight| *weight > 0);
+        if !weights.contains_key(&self.provider) {
+            if let Some((provider, _)) = weights.iter().max_by_key(|(_, weight)| **weight) {
+                self.provider = provider.clone();
+            }
+        }
+        if weights.len() == 1 {
+            weights.clear();
+        }
+        self.weights = weights;
+        self
+    }
+
+    /// Share of matching traffic (0..=1) per provider, largest first
+    pub fn shares(&self) -> Vec<(String, f64)> {
+        if self.weights.is_empty() {
+            return vec![(self.provider.clone(), 1.0)];
+        }
+        let total: u64 = self.weights.values().map(|&weight| u64::from(weight)).sum();
+        let mut shares: Vec<(String, f64)> = self
+            .weights
+            .iter()
+            .map(|(provider, &weight)| (provider.clone(), weight as f64 / total as f64))
+            .collect();
+        shares.sort_by(|a, b| b.1.total_cmp(&a.1));
+        shares
+    }
+}
+
+impl fmt::Display for RoutingRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weights.is_empty() {
+            write!(f, "{} → {}", self.pattern, self.provider)?;
+        } else {
+            let split: Vec<String> = self
+                .shares()
+                .iter()
+                .map(|(provider, share)| format!("{} {:.0}%", provider, share * 100.0))
+                .collect();
+            write!(f, "{} → {}", self.pattern, split.join(", "))?;
+        }
+        if let Some(model) = &self.model {
+            write!(f, " ({})", model)?;
+        }
+        Ok(())
+    }
+}
+
+/// What merging discovered models into the config would add
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProviderMerge {
+    pub new_providers: Vec<String>,
+    /// `(provider, model)` pairs not yet in an allowlist
+    pub new_models: Vec<(String, String)>,
+}
+
+impl ProviderMerge {
+    pub fn is_empty(&self) -> bool {
+        self.new_providers.is_empty() && self.new_models.is_empty()
+    }
+}
+
+impl Config {
+    /// Ids of the providers the backend should route to
+    pub fn enabled_provider_ids(&self) -> Vec<String> {
+        self.providers
+            .iter()
+            .filter(|p| p.enabled)
+            .map(|p| p.id.clone())
+            .collect()
+    }
+
+    /// Base URL overrides of the enabled providers, keyed by provider id
+    pub fn provider_base_urls(&self) -> BTreeMap<String, String> {
+        self.providers
+            .iter()
+            .filter(|p| p.enabled)
+            .filter_map(|p| Some((p.id.clone(), p.base_url_override.clone()?)))
+            .collect()
+    }
+
+    /// The provider serving `model`: one listing it, or the one named in a `provider/model` id
+    pub fn provider_for_model(&self, model: &str) -> Option<&Provider> {
+        self.providers
+            .iter()
+            .find(|p| p.models.iter().any(|m| m == model))
+            .or_else(|| {
+                let (provider_id, _) = model.split_once('/')?;
+                self.providers.iter().find(|p| p.id == provider_id)
+            })
+    }
+
+    /// Work out what [`Config::merge_providers`] would change, without changing anything
+    pub fn preview_provider_merge<'a>(
+        &self,
+        discovered: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> ProviderMerge {
+        let mut preview = self.clone();
+        preview.merge_providers(discovered)
+    }
+
+    /// Add discovered providers and models, keeping everything already configured
+    pub fn merge_providers<'a>(
+        &mut self,
+        discovered: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> ProviderMerge {
+        let mut merge = ProviderMerge::default();
+
+        for (provider_id, model) in discovered {
+            let index = match self.providers.iter().position(|p| p.id == provider_id) {
+                Some(index) => index,
+                None => {
+                    self.providers.push(Provider::new(provider_id));
+                    merge.new_providers.push(provider_id.to_string());
+                    self.providers.len() - 1
+                }
+            };
+
+            let provider = &mut self.providers[index];
+            if !provider.models.iter().any(|m| m == model) {
+                provider.models.push(model.to_string());
+                merge
+                    .new_models
+                    .push((provider_id.to_string(), model.to_string()));
+            }
+        }
+
+        merge
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routing_rules_need_a_known_provider() {
+        let mut config = Config::default();
+        config.providers.push(Provider::new("openai"));
+        config.routing_rules.push(RoutingRule {
+            pattern: "gpt-4*".to_string(),
+            provider: "openai".to_string(),
+            model: None,
+            weights: BTreeMap::new(),
+        });
+        assert!(config.validate().is_ok());
+
+        config.routing_rules.push(RoutingRule {
+            pattern: "claude-*".to_string(),
+            provider: "anthropic".to_string(),
+            model: Some("claude-sonnet".to_string()),
+            weights: BTreeMap::new(),
+        });
+        let problems = config.validate().unwrap_err().problems;
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].field, "routing_rules");
+        assert!(problems[0].message.contains("'anthropic'"));
+    }
+
+    #[test]
+    fn test_rule_weights_split_traffic() {
+        let mut rule = RoutingRule {
+            pattern: "gpt-4*".to_string(),
+            provider: "openai".to_string(),
+            model: None,
+            weights: BTreeMap::new(),
+        };
+        let providers = ["openai", "azure"];
+        assert!(rule.validate_weights(&providers).is_ok());
+        assert_eq!(rule.shares(), vec![("openai".to_string(), 1.0)]);
+
+        rule = rule.with_weights(BTreeMap::from([
+            ("openai".to_string(), 70),
+            ("azure".to_string(), 30),
+            ("mistral".to_string(), 0),
+        ]));
+        assert_eq!(rule.weights.len(), 2);
+        assert!(rule.validate_weights(&providers).is_ok());
+        assert_eq!(rule.shares()[0], ("openai".to_string(), 0.7));
+        assert_eq!(rule.to_string(), "gpt-4* → openai 70%, azure 30%");
+
+        rule.weights.insert("azure".to_string(), 0);
+        assert!(rule.validate_weights(&providers).is_err());
+        rule.weights.remove("openai");
+        rule.weights.insert("azure".to_string(), 30);
+        assert!(rule.validate_weights(&providers).is_err());
+        rule.weights.insert("mistral".to_string(), 10);
+        assert!(rule.validate_weights(&providers).is_err());
+
+        // Moving all traffic elsewhere makes that provider the rule's own
+        let rule = rule.with_weights(BTreeMap::from([("azure".to_string(), 1)]));
+        assert_eq!(rule.provider, "azure");
+        assert!(rule.weights.is_empty());
+    }
+
+    #[test]
+    fn test_merge_providers_keeps_existing_entries() {
+        let mut config = Config::default();
+        let mut openai = Provider::new("openai");
+        openai.models.push("gpt-4o".to_string());
+        openai.base_url_override = Some("http://localhost:9999".to_string());
+        config.providers.push(openai);
+
+        let merge = config.merge_providers([
+            ("openai", "gpt-4o"),
+            ("openai", "gpt-4o-mini"),
+            ("anthropic", "claude-sonnet"),
+        ]);
+
+        assert_eq!(merge.new_providers, vec!["anthropic".to_string()]);
+        assert_eq!(merge.new_models.len(), 2);
+        assert_eq!(config.providers.len(), 2);
+        assert_eq!(config.providers[0].models, vec!["gpt-4o", "gpt-4o-mini"]);
+        assert!(config.providers[0].base_url_override.is_some());
+    }
+
+    #[test]
+    fn test_provider_for_model_by_allowlist_or_prefix() {
+        let mut config = Config::default();
+        let mut openai = Provider::new("openai");
+        openai.models.push("gpt-4o".to_string());
+        config.providers = vec![openai, Provider::new("anthropic")];
+
+        assert_eq!(config.provider_for_model("gpt-4o").unwrap().id, "openai");
+        assert_eq!(config.provider_for_model("anthropic/claude").unwrap().id, "anthropic");
+        assert!(config.provider_for_model("mistral/large").is_none());
+        assert!(config.provider_for_model("claude").is_none());
+    }
+}
@@ -0,0 +1,74 @@
+//! Where secrets and config backups are kept
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Keyring (secret-service) settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyringSettings {
+    /// How long to wait on an unlock prompt or D-Bus call before giving up
+    pub timeout_secs: u64,
+    /// Config fields (dotted paths) whose values were moved into the keyring
+    pub stored_secrets: Vec<String>,
+    /// Keep retrieved secrets in memory this long; 0 asks the keyring every time
+    pub cache_ttl_secs: u64,
+    /// Where secrets are stored
+    pub backend: SecretBackend,
+    /// Encrypted vault file; defaults to `secrets.vault` in the data directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_path: Option<PathBuf>,
+    /// Environment variables an API key import reads; empty means `<PROVIDER>_API_KEY`
+    /// for each configured provider
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub import_env_vars: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    /// The secret service if one is running, otherwise the encrypted vault
+    #[default]
+    Auto,
+    SecretService,
+    /// Encrypted file, unlocked with `VIBEPROXY_VAULT_PASSPHRASE`
+    File,
+}
+
+impl KeyringSettings {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    pub fn cache_ttl(&self) -> Option<Duration> {
+        (self.cache_ttl_secs > 0).then(|| Duration::from_secs(self.cache_ttl_secs))
+    }
+}
+
+impl Default for KeyringSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 60,
+            stored_secrets: Vec::new(),
+            cache_ttl_secs: 0,
+            backend: SecretBackend::Auto,
+            vault_path: None,
+            import_env_vars: Vec::new(),
+        }
+    }
+}
+
+/// Copies of the config file kept on save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Previous versions kept as `config.<ext>.1.bak` (newest) and up; 0 keeps none
+    pub max_backups: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self { max_backups: 3 }
+    }
+}
@@ -0,0 +1,143 @@
+//! Desktop UI settings
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Desktop UI settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    /// Only show the tray icon; the main window is created on demand
+    pub tray_only: bool,
+    /// Warn in the cost panel when this month's estimated spend nears this amount
+    pub monthly_budget_usd: Option<f64>,
+    /// The "no system tray" notification was already shown
+    pub tray_notice_shown: bool,
+    /// Closing the window hides it to the tray instead of quitting
+    pub close_to_tray: bool,
+    /// Whether to rely on a tray icon, for desktops the host check gets wrong
+    pub tray_mode: TrayMode,
+    /// Desktop notification when the backend goes down or comes back
+    pub notifications_enabled: bool,
+    /// Minimum seconds between those notifications, so a flapping backend doesn't spam
+    pub notification_cooldown_secs: u64,
+    pub theme: Theme,
+    /// Main window size when it was last closed
+    pub window: WindowState,
+}
+
+/// Size of the main window, restored on the next launch
+///
+/// GTK 4 leaves window placement to the compositor, so there is no position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowState {
+    pub width: i32,
+    pub height: i32,
+    pub maximized: bool,
+}
+
+impl WindowState {
+    /// Smallest size restored, so a bad value can't leave an unusable sliver
+    pub const MIN_SIZE: (i32, i32) = (360, 300);
+
+    /// Width and height shrunk to fit a `max_width` x `max_height` monitor
+    pub fn clamped(&self, max_width: i32, max_height: i32) -> (i32, i32) {
+        let (min_width, min_height) = Self::MIN_SIZE;
+        (
+            self.width.min(max_width).max(min_width),
+            self.height.min(max_height).max(min_height),
+        )
+    }
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            width: 600,
+            height: 500,
+            maximized: false,
+        }
+    }
+}
+
+/// Light or dark appearance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    /// Follow the desktop's preference
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::System => "Follow system",
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+        }
+    }
+}
+
+/// Whether there is a tray icon to hide the window into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayMode {
+    /// Use the tray while a StatusNotifier host is running on the session bus
+    #[default]
+    Auto,
+    /// Assume a tray even if no host is found, e.g. one the check doesn't know
+    Always,
+    /// No tray icon; closing the window always quits
+    Never,
+}
+
+impl UiSettings {
+    pub fn notification_cooldown(&self) -> Duration {
+        Duration::from_secs(self.notification_cooldown_secs)
+    }
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            tray_only: false,
+            monthly_budget_usd: None,
+            tray_notice_shown: false,
+            close_to_tray: true,
+            tray_mode: TrayMode::Auto,
+            notifications_enabled: true,
+            notification_cooldown_secs: 60,
+            theme: Theme::System,
+            window: WindowState::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_size_fits_the_monitor() {
+        let state = WindowState {
+            width: 2560,
+            height: 1400,
+            maximized: false,
+        };
+        assert_eq!(state.clamped(1920, 1080), (1920, 1080));
+        assert_eq!(state.clamped(3840, 2160), (2560, 1400));
+
+        let state = WindowState {
+            width: 10,
+            height: -5,
+            maximized: false,
+        };
+        assert_eq!(state.clamped(1920, 1080), WindowState::MIN_SIZE);
+    }
+}
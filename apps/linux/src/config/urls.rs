@@ -0,0 +1,70 @@
+//! Backend URLs: http(s) endpoints and Unix domain sockets
+
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+use url::Url;
+use vibeproxy_core::BackendConfig;
+
+/// Scheme prefix of a backend reached over a Unix domain socket
+const UNIX_SCHEME: &str = "unix://";
+
+/// `primary` pointed at another `scheme://host:port` endpoint or `unix://` socket
+pub fn endpoint_backend(primary: &BackendConfig, endpoint: &str) -> Result<BackendConfig> {
+    if unix_socket_path(endpoint)?.is_some() {
+        let mut backend = primary.clone();
+        backend.url = endpoint.to_string();
+        return Ok(backend);
+    }
+
+    let url = parse_http_url(endpoint)?;
+    let host = url
+        .host_str()
+        .with_context(|| format!("Endpoint '{}' has no host", endpoint))?;
+
+    let mut backend = primary.clone();
+    backend.url = format!("{}://{}", url.scheme(), host);
+    backend.port = url.port_or_known_default().unwrap_or(primary.port);
+    Ok(backend)
+}
+
+/// The socket a `unix:///path/to.sock` backend URL points at; `None` for other schemes
+///
+/// A URL naming a host as well (`unix://localhost/run/x.sock`) is rejected
+/// rather than guessing which of the two was meant.
+pub fn unix_socket_path(input: &str) -> Result<Option<PathBuf>> {
+    if !input
+        .get(..UNIX_SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(UNIX_SCHEME))
+    {
+        return Ok(None);
+    }
+
+    let url = Url::parse(input).with_context(|| format!("'{}' is not a valid URL", input))?;
+    if url.host_str().is_some_and(|host| !host.is_empty()) || url.port().is_some() {
+        bail!(
+            "'{}' mixes a TCP host and a socket path; use unix:///path/to/backend.sock",
+            input
+        );
+    }
+    if url.path().len() <= 1 {
+        bail!("'{}' has no socket path", input);
+    }
+    Ok(Some(PathBuf::from(url.path())))
+}
+
+/// Parse `input` as an absolute http(s) URL
+pub fn parse_http_url(input: &str) -> Result<Url> {
+    let url = Url::parse(input).with_context(|| format!("'{}' is not a valid URL", input))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("'{}' must use http or https", input);
+    }
+    Ok(url)
+}
+
+/// Check a backend URL: http(s), or a `unix://` socket path
+pub fn check_backend_url(input: &str) -> Result<()> {
+    if unix_socket_path(input)?.is_none() {
+        parse_http_url(input)?;
+    }
+    Ok(())
+}
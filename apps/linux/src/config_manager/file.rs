@@ -0,0 +1,139 @@
+//! Reading and writing the config file: formats, schema versions, atomic saves
+
+use super::SCHEMA_VERSION;
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// On-disk layout: the schema version first, then the config
+#[derive(Serialize)]
+struct VersionedConfig<'a> {
+    schema_version: u64,
+    #[serde(flatten)]
+    config: &'a Config,
+}
+
+/// On-disk format, chosen by the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// TOML for `.toml` files, JSON otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+
+    pub(super) fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Toml => "toml",
+        }
+    }
+
+    /// Parse into a JSON value so migration and secret scanning work the same for both formats
+    pub(super) fn parse(self, content: &str) -> Result<Value> {
+        match self {
+            Self::Json => serde_json::from_str(content).context("Failed to parse config file"),
+            Self::Toml => toml::from_str(content).context("Failed to parse config file"),
+        }
+    }
+}
+
+pub(super) fn file_contents(config: &Config, format: ConfigFormat) -> Result<String> {
+    let versioned = VersionedConfig {
+        schema_version: SCHEMA_VERSION,
+        config,
+    };
+    match format {
+        ConfigFormat::Json => serde_json::to_string_pretty(&versioned).map_err(anyhow::Error::from),
+        ConfigFormat::Toml => toml::to_string_pretty(&versioned).map_err(anyhow::Error::from),
+    }
+    .context("Failed to serialize config")
+}
+
+/// Parse config file contents, upgrading older schema versions; also returns the file's version
+pub(super) fn parse_migrated(content: &str, format: ConfigFormat) -> Result<(Config, u64)> {
+    let value = format.parse(content)?;
+    let version = schema_version(&value);
+    check_supported(version)?;
+
+    // Version 0 files (no `schema_version`) need no field changes; upgrading
+    // them only fills in defaults for sections added since. Renames go here.
+    let config = serde_json::from_value(value).context("Failed to parse config file")?;
+    Ok((config, version))
+}
+
+/// Replace `path` with `content` so a crash leaves either the old or the new file
+///
+/// Writes a temporary file in the same directory, syncs it and renames it
+/// over the target. The target's permissions are kept.
+pub(super) fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let name = path
+        .file_name()
+        .with_context(|| format!("{:?} is not a file path", path))?;
+    let tmp_path = dir.join(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = (|| {
+        let mut file =
+            fs::File::create(&tmp_path).context("Failed to create temporary config file")?;
+        file.write_all(content.as_bytes())
+            .context("Failed to write config file")?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())
+                .context("Failed to copy config file permissions")?;
+        }
+        file.sync_all().context("Failed to flush config file")?;
+        fs::rename(&tmp_path, path).context("Failed to replace config file")
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result?;
+
+    // Persist the rename itself; not all filesystems support syncing a directory
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Version recorded in a parsed config file; files from before versioning are 0
+pub(super) fn schema_version(value: &Value) -> u64 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+/// Refuse files written by a newer VibeProxy rather than drop fields we don't know
+pub(super) fn check_supported(version: u64) -> Result<()> {
+    if version > SCHEMA_VERSION {
+        bail!(
+            "Config file has schema version {}, but this VibeProxy only understands up to {}; \
+             upgrade VibeProxy to use it (the file was left untouched)",
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    Ok(())
+}
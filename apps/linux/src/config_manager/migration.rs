@@ -0,0 +1,104 @@
+//! Upgrading a config file written by an older version
+
+use super::file::{file_contents, parse_migrated};
+use super::{ConfigManager, SCHEMA_VERSION};
+use crate::config::Config;
+use anyhow::{Context, Result};
+use similar::TextDiff;
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
+
+impl ConfigManager {
+    /// What [`load`](Self::load) would write when upgrading the config file, without writing it
+    pub fn preview_migration(&self) -> Result<MigrationPreview> {
+        let before = fs::read_to_string(&self.config_path)
+            .with_context(|| format!("Failed to read config file {:?}", self.config_path))?;
+        let (config, from_version) = parse_migrated(&before, self.format())?;
+        let after = file_contents(&config, self.format())?;
+
+        Ok(MigrationPreview {
+            file_name: self.file_name(),
+            from_version,
+            before,
+            after,
+        })
+    }
+
+    /// Upgrade the config file now; returns the backup path, or `None` if nothing changed
+    pub fn migrate(&self) -> Result<Option<PathBuf>> {
+        let preview = self.preview_migration()?;
+        if preview.is_noop() {
+            return Ok(None);
+        }
+
+        let (config, _) = parse_migrated(&preview.before, self.format())?;
+        self.write_migrated(&config, preview.from_version).map(Some)
+    }
+
+    /// Back up the config file and save the upgraded `config` in its place
+    ///
+    /// The backup is a copy, so it keeps the file's permissions; a config
+    /// holding credentials stays private.
+    pub(super) fn write_migrated(&self, config: &Config, from_version: u64) -> Result<PathBuf> {
+        let backup_path = self.backup_path(&format!("v{}", from_version));
+        fs::copy(&self.config_path, &backup_path).context("Failed to back up config file")?;
+        info!(
+            "Migrating configuration from schema version {} to {}, original kept at {:?}",
+            from_version, SCHEMA_VERSION, backup_path
+        );
+
+        self.save(config)?;
+        Ok(backup_path)
+    }
+}
+
+/// Before/after of upgrading a config file to [`SCHEMA_VERSION`]
+#[derive(Debug, Clone)]
+pub struct MigrationPreview {
+    pub file_name: String,
+    pub from_version: u64,
+    pub before: String,
+    pub after: String,
+}
+
+impl MigrationPreview {
+    /// Whether the file is already at the current schema version
+    ///
+    /// Decided by version, not by comparing contents: saving reformats a
+    /// hand-edited file, which is no reason to upgrade it.
+    pub fn is_noop(&self) -> bool {
+        self.from_version >= SCHEMA_VERSION
+    }
+
+    /// Unified diff of the file contents
+    pub fn diff(&self) -> String {
+        TextDiff::from_lines(&self.before, &self.after)
+            .unified_diff()
+            .header(
+                &format!("{} (current)", self.file_name),
+                &format!("{} (migrated)", self.file_name),
+            )
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_noop_follows_schema_version() {
+        let mut preview = MigrationPreview {
+            file_name: "config.toml".to_string(),
+            from_version: SCHEMA_VERSION - 1,
+            before: "same".to_string(),
+            after: "same".to_string(),
+        };
+        assert!(!preview.is_noop());
+
+        preview.from_version = SCHEMA_VERSION;
+        preview.after = "reformatted".to_string();
+        assert!(preview.is_noop());
+    }
+}
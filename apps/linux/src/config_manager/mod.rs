@@ -0,0 +1,385 @@
+//! Configuration management
+
+mod file;
+mod migration;
+mod paths;
+mod secrets;
+mod template;
+mod watcher;
+
+pub use file::ConfigFormat;
+pub use migration::MigrationPreview;
+pub(crate) use secrets::SECRET_FIELD_NAMES;
+pub use secrets::InlineSecret;
+pub use watcher::ConfigWatcher;
+
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+use directories::ProjectDirs;
+use file::{check_supported, file_contents, parse_migrated, schema_version, write_atomic};
+use paths::fallback_config_dir;
+use template::{commented_toml, example_config};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{info, warn};
+use vibeproxy_core::BackendConfig;
+
+/// Config file schema version written by this build
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Environment variable naming the config file to use instead of the standard location
+pub const CONFIG_PATH_ENV: &str = "VIBEPROXY_CONFIG";
+
+pub struct ConfigManager {
+    config_path: PathBuf,
+    safe_mode: bool,
+    /// Safe mode ignores the file until the user saves a fixed config
+    ignore_file: AtomicBool,
+}
+
+impl ConfigManager {
+    /// Use the standard location; fails only if there's no safe place for the file
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_path(Self::default_config_path()?))
+    }
+
+    /// Use an explicit config file instead of the standard location
+    pub fn with_path(config_path: PathBuf) -> Self {
+        Self {
+            config_path,
+            safe_mode: false,
+            ignore_file: AtomicBool::new(false),
+        }
+    }
+
+    /// Start from defaults without reading the config file, for `--safe-mode`
+    ///
+    /// Saving still writes the real file, and later loads read it back.
+    pub fn safe_mode() -> Result<Self> {
+        Ok(Self::new()?.into_safe_mode())
+    }
+
+    /// This manager's config file, in safe mode
+    pub fn into_safe_mode(self) -> Self {
+        Self {
+            safe_mode: true,
+            ignore_file: AtomicBool::new(true),
+            ..self
+        }
+    }
+
+    pub fn is_safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// `$VIBEPROXY_CONFIG`, else `config.toml` or an existing `config.json` in the config dir
+    ///
+    /// Only resolves the path; the directory is created on the first save.
+    /// Without a home directory it falls back to a private per-user
+    /// directory, and fails if there is none.
+    fn default_config_path() -> Result<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_PATH_ENV) {
+            return Ok(PathBuf::from(path));
+        }
+
+        let config_dir = match ProjectDirs::from("com", "vibeproxy", "VibeProxy") {
+            Some(proj_dirs) => proj_dirs.config_dir().to_path_buf(),
+            None => fallback_config_dir()?,
+        };
+
+        let toml = config_dir.join("config.toml");
+        let json = config_dir.join("config.json");
+        match (toml.exists(), json.exists()) {
+            (true, true) => {
+                warn!(
+                    "Both {:?} and {:?} exist, using the TOML file (set {} to pick one)",
+                    toml, json, CONFIG_PATH_ENV
+                );
+                Ok(toml)
+            }
+            // Installs from before TOML support keep their file
+            (false, true) => Ok(json),
+            _ => Ok(toml),
+        }
+    }
+
+    fn format(&self) -> ConfigFormat {
+        ConfigFormat::from_path(&self.config_path)
+    }
+
+    pub fn load(&self) -> Result<Config> {
+        if self.ignore_file.load(Ordering::Relaxed) {
+            info!("Safe mode, ignoring {:?}", self.config_path);
+            return Ok(Config::default());
+        }
+
+        info!("Loading configuration from: {:?}", self.config_path);
+
+        if !self.config_path.exists() {
+            info!("Config file not found, using defaults");
+            return Ok(Config::default());
+        }
+
+        let content = fs::read_to_string(&self.config_path)
+            .context("Failed to read config file")?;
+
+        let (config, version) = parse_migrated(&content, self.format())?;
+
+        config.validate()?;
+
+        if version < SCHEMA_VERSION {
+            // Still usable from memory if the upgraded file can't be written
+            if let Err(e) = self.write_migrated(&config, version) {
+                warn!("Failed to write migrated config: {:#}", e);
+            }
+        }
+
+        info!("Configuration loaded successfully");
+        Ok(config)
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        info!("Saving configuration to: {:?}", self.config_path);
+
+        // Even from defaults (safe mode, unreadable file) never overwrite a newer file
+        if self.config_path.exists() {
+            if let Ok(existing) = self.read_raw() {
+                check_supported(schema_version(&existing))?;
+            }
+        }
+
+        // Serialized up front so a failure leaves the existing file alone
+        let content = file_contents(config, self.format())?;
+
+        self.rotate_backups(&content, config.backups.max_backups)?;
+        write_atomic(&self.config_path, &content)?;
+        self.ignore_file.store(false, Ordering::Relaxed);
+
+        info!("Configuration saved successfully");
+        Ok(())
+    }
+
+    /// Replace the config with the newest backup [`save`](Self::save) kept
+    ///
+    /// The file being replaced becomes the newest backup in turn, so
+    /// restoring again goes back to it.
+    pub fn restore_previous(&self) -> Result<Config> {
+        let path = self
+            .latest_backup()
+            .context("There is no previous config to restore")?;
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        let (config, _) = parse_migrated(&content, self.format())?;
+        config.validate()?;
+
+        self.save(&config)?;
+        info!("Restored configuration from {:?}", path);
+        Ok(config)
+    }
+
+    /// The newest copy kept by [`save`](Self::save), if there is one
+    pub fn latest_backup(&self) -> Option<PathBuf> {
+        Some(self.backup_path("1")).filter(|path| path.exists())
+    }
+
+    /// Keep the file as it is now as `config.<ext>.1.bak`, shifting older copies up
+    ///
+    /// At most `keep` copies are kept. Nothing happens when there is no file
+    /// yet or `content` wouldn't change it, so repeated saves don't push the
+    /// useful backups out.
+    fn rotate_backups(&self, content: &str, keep: usize) -> Result<()> {
+        if keep == 0 {
+            return Ok(());
+        }
+        let current = match fs::read_to_string(&self.config_path) {
+            Ok(current) => current,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e).context("Failed to read config file for backup"),
+        };
+        if current == content {
+            return Ok(());
+        }
+
+        // Beyond `keep`, e.g. after lowering the setting
+        let mut stale = keep + 1;
+        while self.backup_path(&stale.to_string()).exists() {
+            fs::remove_file(self.backup_path(&stale.to_string()))
+                .context("Failed to remove old config backup")?;
+            stale += 1;
+        }
+        for n in (1..keep).rev() {
+            let from = self.backup_path(&n.to_string());
+            if from.exists() {
+                fs::rename(&from, self.backup_path(&(n + 1).to_string()))
+                    .context("Failed to rotate config backups")?;
+            }
+        }
+        // A copy keeps the file's permissions, which matter if it holds secrets
+        fs::copy(&self.config_path, self.backup_path("1"))
+            .context("Failed to back up config file")?;
+        Ok(())
+    }
+
+    /// Save the backend settings `backend` as the profile `name`
+    pub fn add_profile(&self, name: &str, backend: BackendConfig) -> Result<()> {
+        let mut config = self.load()?;
+        config.add_profile(name, backend)?;
+        self.save(&config)
+    }
+
+    pub fn remove_profile(&self, name: &str) -> Result<()> {
+        let mut config = self.load()?;
+        config.remove_profile(name)?;
+        self.save(&config)
+    }
+
+    /// Switch the saved config to the profile `name`
+    ///
+    /// Only the file changes; [`crate::ServerManager::select_profile`] also
+    /// reconnects and checks the backend answers.
+    pub fn select_profile(&self, name: &str) -> Result<()> {
+        let mut config = self.load()?;
+        config.select_profile(name)?;
+        self.save(&config)
+    }
+
+    /// Write a default configuration to `path` (or the standard location)
+    ///
+    /// The defaults come with an example provider and routing rule; in TOML
+    /// each section and the main fields are commented. Refuses to overwrite
+    /// an existing file unless `force` is set.
+    pub fn generate_default(&self, path: Option<&Path>, force: bool) -> Result<PathBuf> {
+        let target = path.unwrap_or(&self.config_path).to_path_buf();
+
+        if target.exists() && !force {
+            bail!(
+                "Config file already exists at {:?} (use --force to overwrite)",
+                target
+            );
+        }
+
+        let example = example_config();
+        let content = match ConfigFormat::from_path(&target) {
+            ConfigFormat::Toml => commented_toml(&example)?,
+            // JSON has no comments; the README documents every field
+            ConfigFormat::Json => file_contents(&example, ConfigFormat::Json)?,
+        };
+
+        write_atomic(&target, &content)?;
+
+        info!("Default configuration written to: {:?}", target);
+        Ok(target)
+    }
+
+    fn read_raw(&self) -> Result<Value> {
+        let content = fs::read_to_string(&self.config_path)
+            .context("Failed to read config file")?;
+        self.format().parse(&content)
+    }
+
+    /// `config.<ext>.<label>.bak` next to the config file
+    fn backup_path(&self, label: &str) -> PathBuf {
+        self.config_path
+            .with_extension(format!("{}.{}.bak", self.format().extension(), label))
+    }
+
+    fn file_name(&self) -> String {
+        self.config_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "config".to_string())
+    }
+
+    pub fn get_config_path(&self) -> &PathBuf {
+        &self.config_path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Provider;
+
+    #[test]
+    fn test_toml_round_trip_keeps_every_field() {
+        let path = std::env::temp_dir().join(format!("vibeproxy-config-{}.toml", std::process::id()));
+        let manager = ConfigManager::with_path(path.clone());
+
+        let mut config = Config::default();
+        config.core.backend.api_key = Some("sk-test".to_string());
+        config.providers.push(Provider {
+            id: "openai".to_string(),
+            base_url_override: None,
+            models: vec!["gpt-4o".to_string()],
+            enabled: false,
+        });
+        config.failover.endpoints.push("http://backup:8317".to_string());
+
+        manager.save(&config).unwrap();
+        let loaded = manager.load();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(
+            serde_json::to_value(loaded.unwrap()).unwrap(),
+            serde_json::to_value(&config).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_save_creates_missing_directory_or_fails() {
+        let dir = std::env::temp_dir().join(format!("vibeproxy-config-dir-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ConfigManager::with_path(dir.join("nested/config.toml"))
+            .save(&Config::default())
+            .unwrap();
+        assert!(dir.join("nested/config.toml").exists());
+
+        // A file where the directory should be is an error, not a panic
+        let blocked = ConfigManager::with_path(dir.join("nested/config.toml/config.toml"));
+        assert!(blocked.save(&Config::default()).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_rotates_backups_and_restores() {
+        let dir = std::env::temp_dir().join(format!("vibeproxy-backups-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let manager = ConfigManager::with_path(dir.join("config.toml"));
+        assert!(manager.restore_previous().is_err());
+
+        let mut config = Config::default();
+        config.backups.max_backups = 2;
+        for port in [9001, 9002, 9003, 9003] {
+            config.core.backend.port = port;
+            manager.save(&config).unwrap();
+        }
+        // The repeated save changed nothing, so it didn't push 9001 out
+        assert!(dir.join("config.toml.1.bak").exists());
+        assert!(dir.join("config.toml.2.bak").exists());
+        assert!(!dir.join("config.toml.3.bak").exists());
+
+        assert_eq!(manager.restore_previous().unwrap().core.backend.port, 9002);
+        assert_eq!(manager.load().unwrap().core.backend.port, 9002);
+        assert_eq!(manager.restore_previous().unwrap().core.backend.port, 9003);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_newer_schema_is_never_overwritten() {
+        let path = std::env::temp_dir().join(format!("vibeproxy-future-{}.json", std::process::id()));
+        let future = format!(r#"{{"schema_version": {}, "new_field": true}}"#, SCHEMA_VERSION + 1);
+        fs::write(&path, &future).unwrap();
+
+        let manager = ConfigManager::with_path(path.clone());
+        let loaded = manager.load();
+        let saved = manager.save(&Config::default());
+        let on_disk = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert!(loaded.is_err());
+        assert!(saved.is_err());
+        assert_eq!(on_disk, future);
+    }
+}
@@ -0,0 +1,94 @@
+//! Where the config file lives when the XDG config directory is unknown
+
+use super::CONFIG_PATH_ENV;
+use anyhow::{bail, Context, Result};
+use std::fs::{self, DirBuilder};
+use std::io;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Config directory when the XDG one can't be determined (no usable home directory)
+///
+/// Never the working directory, so launches from different places share one
+/// file. Without a home directory the config, which can hold credentials,
+/// goes to `$XDG_RUNTIME_DIR` or a temp directory only this user can open.
+pub(super) fn fallback_config_dir() -> Result<PathBuf> {
+    if let Some(home) = absolute_env("HOME") {
+        let dir = home.join(".vibeproxy");
+        warn!(
+            "Could not determine the XDG config directory, using {:?} (set {} to pick a file)",
+            dir, CONFIG_PATH_ENV
+        );
+        return Ok(dir);
+    }
+
+    let dir = match absolute_env("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => runtime_dir.join("vibeproxy"),
+        None => private_temp_dir(&std::env::temp_dir())?,
+    };
+    warn!(
+        "No home directory, keeping the config in {:?}, which may not survive a reboot \
+         (set {} to pick a file)",
+        dir, CONFIG_PATH_ENV
+    );
+    Ok(dir)
+}
+
+fn absolute_env(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// `vibeproxy-<uid>` in `base`, created readable by this user only
+///
+/// Temp directories are shared, so one that already exists is used only if
+/// it is a real directory owned by this user that no one else can open.
+fn private_temp_dir(base: &Path) -> Result<PathBuf> {
+    let uid = fs::metadata("/proc/self")
+        .map(|metadata| metadata.uid())
+        .context("Cannot determine the current user")?;
+    let dir = base.join(format!("vibeproxy-{}", uid));
+
+    match DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e).with_context(|| format!("Failed to create {:?}", dir)),
+    }
+
+    // Not followed, so a symlink someone else planted there is refused too
+    let metadata =
+        fs::symlink_metadata(&dir).with_context(|| format!("Failed to inspect {:?}", dir))?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        bail!(
+            "{:?} is not a private directory of this user, refusing to keep the config there \
+             (set {} to pick a file)",
+            dir,
+            CONFIG_PATH_ENV
+        );
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_private_temp_dir_refuses_open_directory() {
+        let base = std::env::temp_dir().join(format!("vibeproxy-private-{}", std::process::id()));
+        fs::create_dir_all(&base).unwrap();
+
+        let dir = private_temp_dir(&base).unwrap();
+        assert_eq!(fs::metadata(&dir).unwrap().mode() & 0o777, 0o700);
+        // Reused as long as it stays private
+        assert_eq!(private_temp_dir(&base).unwrap(), dir);
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(private_temp_dir(&base).is_err());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,114 @@
+//! Credentials written in plain text in the config file
+
+use super::ConfigManager;
+use crate::config::Config;
+use crate::secret_store::SecretStore;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use tracing::info;
+
+/// Field names that look like they hold credentials
+pub(crate) const SECRET_FIELD_NAMES: &[&str] =
+    &["api_key", "apikey", "token", "secret", "password"];
+
+/// A credential written in plain text in the config file
+#[derive(Debug, Clone)]
+pub struct InlineSecret {
+    /// Dotted path of the field, e.g. `backend.api_key`; also its keyring key
+    pub path: String,
+    pub value: String,
+}
+
+impl ConfigManager {
+    /// Find credentials pasted directly into the config file
+    pub fn find_inline_secrets(&self) -> Result<Vec<InlineSecret>> {
+        if !self.config_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let value = self.read_raw()?;
+        let mut secrets = Vec::new();
+        collect_inline_secrets(&value, "", &mut secrets);
+        Ok(secrets)
+    }
+
+    /// Move `secrets` into the keyring and remove them from the config file
+    ///
+    /// The original file is backed up first; the backup path is returned.
+    pub async fn migrate_inline_secrets(
+        &self,
+        keyring: &dyn SecretStore,
+        secrets: &[InlineSecret],
+    ) -> Result<PathBuf> {
+        let backup_path = self.backup_path("pre-keyring");
+        fs::copy(&self.config_path, &backup_path).context("Failed to back up config file")?;
+        info!("Backed up configuration to: {:?}", backup_path);
+
+        let mut value = self.read_raw()?;
+        for secret in secrets {
+            keyring.store(None, &secret.path, &secret.value).await?;
+            clear_path(&mut value, &secret.path);
+        }
+
+        let mut config: Config =
+            serde_json::from_value(value).context("Failed to parse cleaned config")?;
+        for secret in secrets {
+            if !config.keyring.stored_secrets.contains(&secret.path) {
+                config.keyring.stored_secrets.push(secret.path.clone());
+            }
+        }
+        self.save(&config)?;
+
+        info!("Migrated {} secrets into the keyring", secrets.len());
+        Ok(backup_path)
+    }
+}
+
+fn collect_inline_secrets(value: &Value, path: &str, found: &mut Vec<InlineSecret>) {
+    let child_path = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                match child {
+                    Value::String(secret)
+                        if !secret.is_empty()
+                            && SECRET_FIELD_NAMES.contains(&key.to_lowercase().as_str()) =>
+                    {
+                        found.push(InlineSecret {
+                            path: child_path(key),
+                            value: secret.clone(),
+                        });
+                    }
+                    _ => collect_inline_secrets(child, &child_path(key), found),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                collect_inline_secrets(child, &child_path(&index.to_string()), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Null out the field at a dotted path (secret fields are optional)
+fn clear_path(value: &mut Value, path: &str) {
+    let target = path.split('.').try_fold(value, |node, segment| match node {
+        Value::Object(map) => map.get_mut(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get_mut(i)),
+        _ => None,
+    });
+    if let Some(target) = target {
+        *target = Value::Null;
+    }
+}
@@ -0,0 +1,137 @@
+//! The commented starting point `--generate-config` writes
+
+use super::file::{file_contents, ConfigFormat};
+use crate::config::{Config, Provider, RoutingRule};
+use anyhow::Result;
+use std::collections::{BTreeMap, HashSet};
+
+const HEADER: &str = "\
+# VibeProxy configuration
+#
+# Every field is optional; anything left out takes the default shown here.
+# The example provider and routing rule are only a starting point: replace
+# them with the providers your backend serves, or remove them.
+
+";
+
+/// Comments for sections and fields, in dotted form (`backend.port`)
+///
+/// Fields without one are written uncommented; the README documents them all.
+const FIELD_DOCS: &[(&str, &str)] = &[
+    ("schema_version", "Format of this file, managed by the app"),
+    ("backend", "The Bifrost backend the app monitors and controls"),
+    ("backend.url", "Base URL, or unix:///path/to/backend.sock for a Unix socket"),
+    ("backend.port", "Backend port"),
+    ("backend.api_key", "API key sent to the backend; Settings can move it to the keyring"),
+    ("backend.timeout_secs", "Request timeout in seconds"),
+    ("keyring", "Where secrets are kept when not in this file"),
+    ("keyring.backend", "`auto`, `secret_service` or `file` (an encrypted vault)"),
+    ("providers", "An upstream AI provider routed through the backend; repeat for each"),
+    ("providers.id", "Provider identifier as known to the backend"),
+    ("providers.models", "Models routed to this provider; leave out to allow all"),
+    ("providers.enabled", "Set to false to take the provider out of the backend's routing"),
+    (
+        "providers.base_url_override",
+        "Send this provider's traffic somewhere else, e.g. a local mock",
+    ),
+    ("routing_rules", "Pin model aliases to a provider; the backend takes the first match"),
+    ("routing_rules.pattern", "Model alias with * wildcards"),
+    ("routing_rules.provider", "One of the providers above"),
+    ("routing_rules.model", "Model name to use at that provider (optional)"),
+    ("health", "Background health checks of the backend"),
+    ("health.polling_enabled", "Set to false to make no background requests at all"),
+    ("health.interval_secs", "Seconds between health checks"),
+    ("health.failure_threshold", "Consecutive failures before the backend counts as down"),
+    ("health.down_interval_secs", "Seconds between probes while the backend is down"),
+    ("ui", "Main window and tray"),
+    ("ui.tray_only", "Start with only the tray icon, no window"),
+    ("ui.theme", "`system`, `light` or `dark`"),
+    ("client", "How the app connects to the backend"),
+    ("client.max_concurrent_requests", "Backend requests the app has in flight at once"),
+    ("client.request_timeout_ms", "Deadline for each health check"),
+    ("client.extra_headers", "Headers sent with every backend request"),
+    ("failover", "Other backends to switch to when this one is down"),
+    ("failover.endpoints", "Extra http://host:port backends, in priority order"),
+    ("status_socket", "JSON status over a Unix socket, for scripts and status bars"),
+    ("metrics", "Prometheus metrics endpoint"),
+    ("logging", "Log files and their retention"),
+    ("history", "Latency samples and events kept across restarts"),
+    ("backups", "Copies of this file kept before each save"),
+    ("backend_process", "Starting the backend binary when nothing answers"),
+    ("backend_process.binary_path", "Bifrost binary; a bare name is looked up on PATH"),
+    ("backend_process.args", "Arguments for the binary; {port} is replaced with backend.port"),
+    ("restart_policy", "Restarting a started backend that crashed"),
+    ("retry", "Retrying status checks while the backend can't be reached"),
+    ("profiles", "Named backend settings to switch between, e.g. [profiles.work]"),
+];
+
+/// Defaults plus an example provider and routing rule
+pub(super) fn example_config() -> Config {
+    let mut config = Config::default();
+    let mut provider = Provider::new("openai");
+    provider.models = vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()];
+    config.providers.push(provider);
+    config.routing_rules.push(RoutingRule {
+        pattern: "gpt-4*".to_string(),
+        provider: "openai".to_string(),
+        model: None,
+        weights: BTreeMap::new(),
+    });
+    config
+}
+
+/// `config` as TOML with a comment above each documented section and field
+///
+/// Built from the serialized config, so it always matches the schema; each
+/// comment appears once, above the first `[[providers]]` for instance.
+pub(super) fn commented_toml(config: &Config) -> Result<String> {
+    let content = file_contents(config, ConfigFormat::Toml)?;
+    let mut commented = String::from(HEADER);
+    let mut table = String::new();
+    let mut documented = HashSet::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        let header = trimmed
+            .strip_prefix("[[")
+            .and_then(|rest| rest.strip_suffix("]]"))
+            .or_else(|| trimmed.strip_prefix('[')?.strip_suffix(']'));
+        let path = match (header, trimmed.split_once(" = ")) {
+            (Some(header), _) => {
+                table = header.to_string();
+                Some(table.clone())
+            }
+            (None, Some((key, _))) if table.is_empty() => Some(key.to_string()),
+            (None, Some((key, _))) => Some(format!("{}.{}", table, key)),
+            (None, None) => None,
+        };
+
+        let doc = path.and_then(|path| FIELD_DOCS.iter().find(|(field, _)| *field == path));
+        if let Some((field, doc)) = doc {
+            if documented.insert(*field) {
+                commented.push_str(&format!("# {}\n", doc));
+            }
+        }
+        commented.push_str(line);
+        commented.push('\n');
+    }
+    Ok(commented)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_manager::file::parse_migrated;
+
+    #[test]
+    fn test_template_parses_back_to_the_example() {
+        let template = commented_toml(&example_config()).unwrap();
+        assert!(template.contains("# Provider identifier as known to the backend\n"));
+        assert_eq!(template.matches("# Model alias with * wildcards").count(), 1);
+
+        let (config, _) = parse_migrated(&template, ConfigFormat::Toml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.providers[0].models, vec!["gpt-4o", "gpt-4o-mini"]);
+        assert_eq!(config.routing_rules, example_config().routing_rules);
+    }
+}
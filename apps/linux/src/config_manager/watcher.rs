@@ -0,0 +1,88 @@
+//! Reloading the config when the file changes on disk
+
+use super::ConfigManager;
+use crate::config::Config;
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+impl ConfigManager {
+    /// Reload the config whenever the file changes on disk
+    ///
+    /// The receiver starts with the current config and gets each new version
+    /// that parses and validates. A broken edit is logged and skipped, so the
+    /// last good config stays current until the file is fixed. Watching stops
+    /// when the returned [`ConfigWatcher`] is dropped.
+    pub fn watch(self: &Arc<Self>) -> Result<ConfigWatcher> {
+        let (sender, receiver) = watch::channel(self.load()?);
+
+        // Watch the directory: editors and our own atomic saves replace the file
+        let dir = self
+            .config_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        let file_name = self.config_path.file_name().map(|name| name.to_os_string());
+
+        let manager = self.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Config watch error: {}", e);
+                    return;
+                }
+            };
+            let ours = event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == file_name.as_deref());
+            if !ours || !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                return;
+            }
+
+            match manager.load() {
+                Ok(config) => {
+                    sender.send_if_modified(|current| {
+                        let changed = serde_json::to_value(&*current).ok()
+                            != serde_json::to_value(&config).ok();
+                        if changed {
+                            info!("Config file changed, reloaded");
+                            *current = config;
+                        }
+                        changed
+                    });
+                }
+                Err(e) => warn!(
+                    "Ignoring invalid config change, keeping the previous config: {:#}",
+                    e
+                ),
+            }
+        })
+        .context("Failed to create config file watcher")?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {:?}", dir))?;
+
+        Ok(ConfigWatcher {
+            _watcher: watcher,
+            receiver,
+        })
+    }
+}
+
+/// Live config reloads from [`ConfigManager::watch`]; watching stops when dropped
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+    receiver: watch::Receiver<Config>,
+}
+
+impl ConfigWatcher {
+    pub fn subscribe(&self) -> watch::Receiver<Config> {
+        self.receiver.clone()
+    }
+}
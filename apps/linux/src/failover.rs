@@ -0,0 +1,91 @@
+//! Endpoint selection across the primary backend and its failover endpoints
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Latency samples kept per endpoint
+const LATENCY_SAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailoverStrategy {
+    /// First healthy endpoint in configured order
+    #[default]
+    Priority,
+    /// Healthy endpoint with the lowest recent average latency
+    LowestLatency,
+}
+
+#[derive(Debug, Default)]
+pub struct EndpointSelector {
+    strategy: FailoverStrategy,
+    latencies: HashMap<String, VecDeque<u64>>,
+}
+
+impl EndpointSelector {
+    pub fn new(strategy: FailoverStrategy) -> Self {
+        Self {
+            strategy,
+            latencies: HashMap::new(),
+        }
+    }
+
+    pub fn set_strategy(&mut self, strategy: FailoverStrategy) {
+        self.strategy = strategy;
+    }
+
+    /// Record a health-check result; `None` means the endpoint was unhealthy
+    pub fn record(&mut self, endpoint: &str, latency_ms: Option<u64>) {
+        match latency_ms {
+            Some(latency) => {
+                let samples = self.latencies.entry(endpoint.to_string()).or_default();
+                if samples.len() == LATENCY_SAMPLES {
+                    samples.pop_front();
+                }
+                samples.push_back(latency);
+            }
+            None => {
+                self.latencies.remove(endpoint);
+            }
+        }
+    }
+
+    pub fn average_latency(&self, endpoint: &str) -> Option<u64> {
+        let samples = self.latencies.get(endpoint).filter(|s| !s.is_empty())?;
+        Some(samples.iter().sum::<u64>() / samples.len() as u64)
+    }
+
+    /// Pick an endpoint among `endpoints` (in priority order) that has recent healthy samples
+    pub fn select<'a>(&self, endpoints: &'a [String]) -> Option<&'a String> {
+        let mut healthy = endpoints
+            .iter()
+            .filter_map(|e| self.average_latency(e).map(|latency| (e, latency)));
+
+        match self.strategy {
+            FailoverStrategy::Priority => healthy.next().map(|(e, _)| e),
+            // min_by_key keeps the first of equal latencies, so ties go by priority
+            FailoverStrategy::LowestLatency => healthy.min_by_key(|(_, latency)| *latency).map(|(e, _)| e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategies() {
+        let endpoints = vec!["http://a:1".to_string(), "http://b:1".to_string()];
+        let mut selector = EndpointSelector::new(FailoverStrategy::Priority);
+        selector.record("http://a:1", Some(300));
+        selector.record("http://b:1", Some(40));
+
+        assert_eq!(selector.select(&endpoints), Some(&endpoints[0]));
+
+        selector.set_strategy(FailoverStrategy::LowestLatency);
+        assert_eq!(selector.select(&endpoints), Some(&endpoints[1]));
+
+        selector.record("http://b:1", None);
+        assert_eq!(selector.select(&endpoints), Some(&endpoints[0]));
+    }
+}
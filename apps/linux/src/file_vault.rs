@@ -0,0 +1,293 @@
+//! Encrypted file vault for secrets
+//!
+//! Used where no secret service is running (headless machines, minimal window
+//! managers). All secrets are kept in one JSON map encrypted with
+//! ChaCha20-Poly1305 under a key derived from a passphrase with Argon2id. The
+//! salt and a fresh nonce per write are stored next to the ciphertext.
+//!
+//! [`seal`] and [`unseal`] use the same format for one-off encrypted data,
+//! such as secrets exported for another machine.
+
+use crate::keyring::DEFAULT_NAMESPACE;
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::info;
+use zeroize::Zeroizing;
+
+/// Environment variable holding the vault passphrase
+pub const PASSPHRASE_ENV: &str = "VIBEPROXY_VAULT_PASSPHRASE";
+
+const VAULT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// On-disk layout; binary fields are base64
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+pub struct FileVault {
+    path: PathBuf,
+    salt: Vec<u8>,
+    cipher: ChaCha20Poly1305,
+    /// Namespace -> key -> secret
+    secrets: Mutex<Secrets>,
+}
+
+type Secrets = BTreeMap<String, BTreeMap<String, String>>;
+
+impl FileVault {
+    /// `~/.local/share/vibeproxy/secrets.vault`
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("com", "vibeproxy", "VibeProxy")
+            .map(|dirs| dirs.data_dir().join("secrets.vault"))
+    }
+
+    /// Open the vault at `path` with the passphrase from [`PASSPHRASE_ENV`]
+    pub fn from_env(path: PathBuf) -> Result<Self> {
+        let passphrase = std::env::var(PASSPHRASE_ENV)
+            .with_context(|| format!("Set {} to use the encrypted vault", PASSPHRASE_ENV))?;
+        Self::open(path, &passphrase)
+    }
+
+    /// Open the vault at `path`; a missing file is created on the first store
+    pub fn open(path: PathBuf, passphrase: &str) -> Result<Self> {
+        if passphrase.is_empty() {
+            bail!("Vault passphrase is empty");
+        }
+
+        if !path.exists() {
+            let salt = new_salt();
+            let cipher = derive_cipher(passphrase, &salt)?;
+            info!("Using new secrets vault at {:?}", path);
+            return Ok(Self {
+                path,
+                salt,
+                cipher,
+                secrets: Mutex::default(),
+            });
+        }
+
+        let content = fs::read(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        let (salt, cipher, plaintext) =
+            decrypt(passphrase, &content).with_context(|| format!("Failed to open {:?}", path))?;
+        let secrets =
+            serde_json::from_slice(&plaintext).context("Vault contents are not valid")?;
+
+        info!("Opened secrets vault at {:?}", path);
+        Ok(Self {
+            path,
+            salt,
+            cipher,
+            secrets: Mutex::new(secrets),
+        })
+    }
+
+    pub fn store(&self, namespace: Option<&str>, key: &str, value: &str) -> Result<()> {
+        let namespace = namespace.unwrap_or(DEFAULT_NAMESPACE);
+        let mut secrets = self.secrets.lock().unwrap();
+        let mut updated = secrets.clone();
+        updated
+            .entry(namespace.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+
+        // Only keep in memory what made it to the file
+        self.write(&updated)?;
+        *secrets = updated;
+        info!("Stored secret in vault: {}/{}", namespace, key);
+        Ok(())
+    }
+
+    pub fn retrieve(&self, namespace: Option<&str>, key: &str) -> Option<String> {
+        let namespace = namespace.unwrap_or(DEFAULT_NAMESPACE);
+        self.secrets.lock().unwrap().get(namespace)?.get(key).cloned()
+    }
+
+    pub fn delete(&self, namespace: Option<&str>, key: &str) -> Result<()> {
+        let namespace = namespace.unwrap_or(DEFAULT_NAMESPACE);
+        let mut secrets = self.secrets.lock().unwrap();
+        let mut updated = secrets.clone();
+        let Some(keys) = updated.get_mut(namespace) else {
+            return Ok(());
+        };
+        if keys.remove(key).is_none() {
+            return Ok(());
+        }
+        if keys.is_empty() {
+            updated.remove(namespace);
+        }
+
+        self.write(&updated)?;
+        *secrets = updated;
+        info!("Deleted secret from vault: {}/{}", namespace, key);
+        Ok(())
+    }
+
+    /// All stored keys as `(namespace, key)` pairs
+    pub fn list_keys(&self) -> Vec<(String, String)> {
+        self.secrets
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|(namespace, keys)| {
+                keys.keys().map(|key| (namespace.clone(), key.clone()))
+            })
+            .collect()
+    }
+
+    /// Encrypt `secrets` with a fresh nonce and replace the file, readable by the owner only
+    fn write(&self, secrets: &Secrets) -> Result<()> {
+        let plaintext =
+            Zeroizing::new(serde_json::to_vec(secrets).context("Failed to serialize secrets")?);
+        let content = encrypt(&self.cipher, &self.salt, &plaintext)?;
+        write_private(&self.path, &content)
+    }
+}
+
+/// Encrypt `plaintext` under `passphrase` with a fresh salt
+pub fn seal(passphrase: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    if passphrase.is_empty() {
+        bail!("Passphrase is empty");
+    }
+    let salt = new_salt();
+    encrypt(&derive_cipher(passphrase, &salt)?, &salt, plaintext)
+}
+
+/// Decrypt data from [`seal`]; a wrong passphrase is an error, never garbage
+pub fn unseal(passphrase: &str, sealed: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    decrypt(passphrase, sealed).map(|(_, _, plaintext)| plaintext)
+}
+
+fn encrypt(cipher: &ChaCha20Poly1305, salt: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow!("Failed to encrypt secrets"))?;
+
+    let file = VaultFile {
+        version: VAULT_VERSION,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    serde_json::to_vec(&file).context("Failed to serialize encrypted secrets")
+}
+
+/// The salt, the derived cipher and the plaintext of `content`
+fn decrypt(
+    passphrase: &str,
+    content: &[u8],
+) -> Result<(Vec<u8>, ChaCha20Poly1305, Zeroizing<Vec<u8>>)> {
+    let file: VaultFile =
+        serde_json::from_slice(content).context("Not VibeProxy encrypted secrets")?;
+    if file.version != VAULT_VERSION {
+        bail!("Unsupported encrypted secrets version {}", file.version);
+    }
+
+    let salt = decode(&file.salt)?;
+    let nonce = decode(&file.nonce)?;
+    if nonce.len() != NONCE_LEN {
+        bail!("Encrypted secrets have an invalid nonce");
+    }
+    let cipher = derive_cipher(passphrase, &salt)?;
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), decode(&file.ciphertext)?.as_slice())
+        .map_err(|_| anyhow!("Wrong passphrase, or the encrypted secrets are corrupted"))?;
+    Ok((salt, cipher, Zeroizing::new(plaintext)))
+}
+
+fn new_salt() -> Vec<u8> {
+    let mut salt = vec![0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+fn derive_cipher(passphrase: &str, salt: &[u8]) -> Result<ChaCha20Poly1305> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive vault key: {}", e))?;
+    Ok(ChaCha20Poly1305::new(&key.into()))
+}
+
+fn decode(field: &str) -> Result<Vec<u8>> {
+    BASE64.decode(field).context("Encrypted secrets are corrupted")
+}
+
+/// Write `content` to a mode 0600 temporary file and rename it over `path`
+fn write_private(path: &Path, content: &[u8]) -> Result<()> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+
+    let result = (|| {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp_path)
+            .context("Failed to create temporary vault file")?;
+        file.write_all(content).context("Failed to write vault")?;
+        file.sync_all().context("Failed to flush vault")?;
+        fs::rename(&tmp_path, path).context("Failed to replace vault")
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_round_trip_and_wrong_passphrase() {
+        let path = std::env::temp_dir().join(format!("vibeproxy-vault-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let vault = FileVault::open(path.clone(), "correct horse").unwrap();
+        vault.store(None, "openai", "sk-one").unwrap();
+        vault.store(Some("work"), "openai", "sk-work").unwrap();
+        vault.store(None, "anthropic", "sk-two").unwrap();
+        vault.delete(None, "anthropic").unwrap();
+
+        let reopened = FileVault::open(path.clone(), "correct horse").unwrap();
+        assert_eq!(reopened.retrieve(None, "openai").as_deref(), Some("sk-one"));
+        assert_eq!(reopened.retrieve(Some("work"), "openai").as_deref(), Some("sk-work"));
+        assert_eq!(
+            reopened.list_keys(),
+            [
+                ("default".to_string(), "openai".to_string()),
+                ("work".to_string(), "openai".to_string())
+            ]
+        );
+        assert!(!fs::read_to_string(&path).unwrap().contains("sk-one"));
+
+        assert!(FileVault::open(path.clone(), "wrong").is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,111 @@
+//! `--headless`: supervise the backend without a display
+//!
+//! Never touches GTK. Starts the backend, then keeps the status poller,
+//! status socket, metrics endpoint and config reload running on the Tokio
+//! runtime until SIGINT or SIGTERM, when the usual shutdown hooks stop
+//! everything. Uses the same config file and keyring as the desktop app.
+
+use crate::app::{VibeProxyApp, BACKEND_STOP_TIMEOUT};
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+use tracing::{info, warn};
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::server_manager::{ServerManager, ServerStatus};
+use vibeproxy_linux::shutdown::ShutdownHooks;
+
+pub fn run(config_manager: ConfigManager) -> Result<()> {
+    let runtime = Runtime::new().context("Failed to create Tokio runtime")?;
+    let handle = runtime.handle().clone();
+    runtime.block_on(supervise(Arc::new(config_manager), handle))
+}
+
+async fn supervise(config_manager: Arc<ConfigManager>, runtime: Handle) -> Result<()> {
+    let config = config_manager.load()?;
+    let server_manager = Arc::new(ServerManager::new(config_manager.clone(), runtime.clone())?);
+
+    // Installed before anything starts, so a signal during startup isn't fatal
+    let mut interrupt = signal(SignalKind::interrupt()).context("Failed to handle SIGINT")?;
+    let mut terminate = signal(SignalKind::terminate()).context("Failed to handle SIGTERM")?;
+
+    if server_manager.is_running().await {
+        info!("Backend already running, nothing to start");
+    } else {
+        server_manager
+            .start()
+            .await
+            .context("Failed to start the backend")?;
+    }
+
+    // Registered first so it runs last, after everything that talks to the backend
+    let shutdown = ShutdownHooks::new();
+    shutdown.register("backend", BACKEND_STOP_TIMEOUT, {
+        let server_manager = server_manager.clone();
+        // Also when a start is still under way, whose backend would be orphaned
+        move || async move { server_manager.stop_for_exit().await }
+    });
+
+    let statuses = if config.health.polling_enabled {
+        server_manager.watch_status(Duration::from_secs(config.health.interval_secs))
+    } else {
+        server_manager.status_updates()
+    };
+    VibeProxyApp::start_metrics(&config, &server_manager, statuses.clone(), &runtime, &shutdown);
+    VibeProxyApp::start_status_socket(&config, statuses.clone(), &runtime, &shutdown);
+    runtime.spawn(log_status_changes(statuses));
+
+    // Kept alive until shutdown; dropping it stops watching the file
+    let _config_watcher = match config_manager.watch() {
+        Ok(watcher) => {
+            let mut configs = watcher.subscribe();
+            let server_manager = server_manager.clone();
+            runtime.spawn(async move {
+                let mut previous = configs.borrow_and_update().clone();
+                while configs.changed().await.is_ok() {
+                    let config = configs.borrow_and_update().clone();
+                    server_manager.config_reloaded(&previous, &config).await;
+                    previous = config;
+                }
+            });
+            Some(watcher)
+        }
+        Err(e) => {
+            warn!("Config changes need a restart to take effect: {:#}", e);
+            None
+        }
+    };
+
+    info!("Running headless, stop with SIGINT or SIGTERM");
+    let received = tokio::select! {
+        _ = interrupt.recv() => "SIGINT",
+        _ = terminate.recv() => "SIGTERM",
+    };
+    info!("Received {}, shutting down", received);
+    shutdown.run().await;
+    Ok(())
+}
+
+/// Log when the backend goes down or comes back, in place of the tray and notifications
+async fn log_status_changes(mut statuses: watch::Receiver<Option<ServerStatus>>) {
+    let mut reported: Option<bool> = None;
+    while statuses.changed().await.is_ok() {
+        let Some(status) = statuses.borrow_and_update().clone() else {
+            continue;
+        };
+        if reported == Some(status.running) {
+            continue;
+        }
+        if status.running {
+            info!("Backend is up, responding in {} ms", status.latency_ms);
+        } else {
+            warn!(
+                "Backend is down: {}",
+                status.message.as_deref().unwrap_or("no response")
+            );
+        }
+        reported = Some(status.running);
+    }
+}
@@ -0,0 +1,196 @@
+//! Health-check scheduling
+//!
+//! Polls at the normal interval while the backend answers. After enough
+//! consecutive failures the backend is treated as down and probed sparsely
+//! until it recovers; only those transitions are logged.
+
+use crate::backend_api::BackendDetails;
+use crate::config::HealthSettings;
+use std::time::Duration;
+use tracing::{info, warn};
+
+pub struct HealthSchedule {
+    interval: Duration,
+    down_interval: Duration,
+    failure_threshold: u32,
+    consecutive_failures: u32,
+}
+
+impl HealthSchedule {
+    pub fn new(settings: &HealthSettings) -> Self {
+        Self::with_interval(Duration::from_secs(settings.interval_secs), settings)
+    }
+
+    /// Like [`new`](Self::new) but polling every `interval` while the backend is up
+    pub fn with_interval(interval: Duration, settings: &HealthSettings) -> Self {
+        Self {
+            interval,
+            down_interval: Duration::from_secs(settings.down_interval_secs),
+            failure_threshold: settings.failure_threshold.max(1),
+            consecutive_failures: 0,
+        }
+    }
+
+    /// Record the outcome of a health check
+    ///
+    /// Returns `true` if this result moved the backend between up and down.
+    pub fn record(&mut self, healthy: bool) -> bool {
+        let was_down = self.is_down();
+
+        if healthy {
+            self.consecutive_failures = 0;
+        } else {
+            self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        }
+
+        let is_down = self.is_down();
+        if is_down && !was_down {
+            warn!(
+                "Backend down after {} consecutive failed health checks, probing every {:?}",
+                self.consecutive_failures, self.down_interval
+            );
+        } else if was_down && !is_down {
+            info!("Backend is reachable again, resuming normal health checks");
+        }
+
+        is_down != was_down
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.consecutive_failures >= self.failure_threshold
+    }
+
+    /// Delay before the next health check
+    pub fn next_delay(&self) -> Duration {
+        if self.is_down() {
+            self.down_interval
+        } else {
+            self.interval
+        }
+    }
+}
+
+/// Health classification shown for each provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthLevel {
+    Healthy,
+    Degraded,
+    Critical,
+    Unknown,
+}
+
+impl HealthLevel {
+    pub const ALL: [HealthLevel; 4] = [
+        HealthLevel::Healthy,
+        HealthLevel::Degraded,
+        HealthLevel::Critical,
+        HealthLevel::Unknown,
+    ];
+
+    /// Classify a probe result; `None` means no result yet
+    pub fn classify(healthy: Option<bool>, latency_ms: u64, settings: &HealthSettings) -> Self {
+        match healthy {
+            None => HealthLevel::Unknown,
+            Some(false) => HealthLevel::Critical,
+            Some(true) if latency_ms >= settings.critical_latency_ms => HealthLevel::Critical,
+            Some(true) if latency_ms >= settings.degraded_latency_ms => HealthLevel::Degraded,
+            Some(true) => HealthLevel::Healthy,
+        }
+    }
+
+    /// Classify provider `id` by the backend's last report, `Unknown` if it isn't in one
+    pub fn of_provider(
+        details: Option<&BackendDetails>,
+        id: &str,
+        settings: &HealthSettings,
+    ) -> Self {
+        match details.and_then(|details| details.providers.iter().find(|p| p.id == id)) {
+            Some(provider) => Self::classify(
+                Some(provider.reachable),
+                provider.latency_ms.unwrap_or(0),
+                settings,
+            ),
+            None => HealthLevel::Unknown,
+        }
+    }
+
+    /// Shape drawn next to the label, so the level never relies on color alone
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            HealthLevel::Healthy => "●",
+            HealthLevel::Degraded => "▲",
+            HealthLevel::Critical => "■",
+            HealthLevel::Unknown => "○",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HealthLevel::Healthy => "Healthy",
+            HealthLevel::Degraded => "Degraded",
+            HealthLevel::Critical => "Critical",
+            HealthLevel::Unknown => "Unknown",
+        }
+    }
+
+    /// libadwaita style class used to color the indicator
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            HealthLevel::Healthy => "success",
+            HealthLevel::Degraded => "warning",
+            HealthLevel::Critical => "error",
+            HealthLevel::Unknown => "dim-label",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapses_after_threshold_and_recovers() {
+        let settings = HealthSettings {
+            interval_secs: 5,
+            failure_threshold: 3,
+            down_interval_secs: 60,
+            ..Default::default()
+        };
+        let mut schedule = HealthSchedule::new(&settings);
+
+        assert!(!schedule.record(false));
+        assert!(!schedule.record(false));
+        assert_eq!(schedule.next_delay(), Duration::from_secs(5));
+
+        assert!(schedule.record(false));
+        assert!(schedule.is_down());
+        assert_eq!(schedule.next_delay(), Duration::from_secs(60));
+
+        // Further failures are not transitions
+        assert!(!schedule.record(false));
+
+        assert!(schedule.record(true));
+        assert_eq!(schedule.next_delay(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_provider_level_from_backend_details() {
+        let settings = HealthSettings::default();
+        let details: BackendDetails = serde_json::from_str(
+            r#"{"providers": [
+                {"id": "openai", "reachable": true, "latency_ms": 120},
+                {"id": "anthropic", "reachable": false}
+            ]}"#,
+        )
+        .unwrap();
+
+        let level = |id| HealthLevel::of_provider(Some(&details), id, &settings);
+        assert_eq!(level("openai"), HealthLevel::Healthy);
+        assert_eq!(level("anthropic"), HealthLevel::Critical);
+        assert_eq!(level("groq"), HealthLevel::Unknown);
+        assert_eq!(
+            HealthLevel::of_provider(None, "openai", &settings),
+            HealthLevel::Unknown
+        );
+    }
+}
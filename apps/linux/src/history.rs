@@ -0,0 +1,207 @@
+//! Latency samples and lifecycle events kept across restarts
+//!
+//! Stored as JSON lines in the data directory: appended as they happen,
+//! pruned to the retention window and compacted on load.
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HistoryRecord {
+    /// A health check; `latency_ms` is `None` when the backend was unreachable
+    Sample {
+        at: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        endpoint: Option<String>,
+        latency_ms: Option<u64>,
+    },
+    Event {
+        at: u64,
+        event: LifecycleEvent,
+    },
+}
+
+impl HistoryRecord {
+    /// Seconds since the Unix epoch
+    pub fn at(&self) -> u64 {
+        match self {
+            Self::Sample { at, .. } | Self::Event { at, .. } => *at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleEvent {
+    Started,
+    Stopped,
+    /// Health checks started succeeding
+    Up,
+    /// Health checks started failing
+    Down,
+}
+
+pub struct HealthHistory {
+    records: VecDeque<HistoryRecord>,
+    retention: Duration,
+    file: File,
+}
+
+impl HealthHistory {
+    /// `~/.local/share/vibeproxy/history.jsonl`
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("com", "vibeproxy", "VibeProxy")
+            .map(|dirs| dirs.data_dir().join("history.jsonl"))
+    }
+
+    /// Read `path`, drop records older than `retention` and rewrite what's left
+    pub fn load(path: &Path, retention: Duration) -> Result<Self> {
+        let cutoff = now().saturating_sub(retention.as_secs());
+        let mut records = VecDeque::new();
+
+        if path.exists() {
+            let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+            let mut skipped = 0;
+            for line in BufReader::new(file).lines() {
+                match serde_json::from_str::<HistoryRecord>(&line?) {
+                    Ok(record) if record.at() >= cutoff => records.push_back(record),
+                    Ok(_) => {}
+                    // A torn last line after a crash shouldn't lose the rest
+                    Err(_) => skipped += 1,
+                }
+            }
+            if skipped > 0 {
+                warn!("Skipped {} unreadable history records", skipped);
+            }
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        }
+        let mut compacted = String::new();
+        for record in &records {
+            compacted.push_str(&serde_json::to_string(record)?);
+            compacted.push('\n');
+        }
+        fs::write(path, compacted).with_context(|| format!("Failed to write {:?}", path))?;
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {:?}", path))?;
+
+        Ok(Self {
+            records,
+            retention,
+            file,
+        })
+    }
+
+    /// Record a health check, adding an up/down event when reachability changed
+    pub fn record_sample(
+        &mut self,
+        endpoint: Option<String>,
+        latency_ms: Option<u64>,
+    ) -> Result<()> {
+        let was_up = self.records.iter().rev().find_map(|r| match r {
+            HistoryRecord::Sample { latency_ms, .. } => Some(latency_ms.is_some()),
+            HistoryRecord::Event { .. } => None,
+        });
+        let is_up = latency_ms.is_some();
+        if was_up.is_some_and(|was_up| was_up != is_up) {
+            self.record_event(if is_up {
+                LifecycleEvent::Up
+            } else {
+                LifecycleEvent::Down
+            })?;
+        }
+
+        self.append(HistoryRecord::Sample {
+            at: now(),
+            endpoint,
+            latency_ms,
+        })
+    }
+
+    pub fn record_event(&mut self, event: LifecycleEvent) -> Result<()> {
+        self.append(HistoryRecord::Event { at: now(), event })
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &HistoryRecord> {
+        self.records.iter()
+    }
+
+    /// Share of retained health checks that reached the backend
+    pub fn uptime(&self) -> Option<f64> {
+        let (up, total) = self
+            .records
+            .iter()
+            .filter_map(|r| match r {
+                HistoryRecord::Sample { latency_ms, .. } => Some(latency_ms.is_some()),
+                HistoryRecord::Event { .. } => None,
+            })
+            .fold((0, 0), |(up, total), is_up| (up + is_up as u32, total + 1));
+        (total > 0).then(|| up as f64 / total as f64)
+    }
+
+    fn append(&mut self, record: HistoryRecord) -> Result<()> {
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .context("Failed to append to history")?;
+
+        let cutoff = record.at().saturating_sub(self.retention.as_secs());
+        while self.records.front().is_some_and(|r| r.at() < cutoff) {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+        Ok(())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_survives_reload_and_tracks_transitions() {
+        let path =
+            std::env::temp_dir().join(format!("vibeproxy-history-{}.jsonl", std::process::id()));
+        let retention = Duration::from_secs(3600);
+
+        let mut history = HealthHistory::load(&path, retention).unwrap();
+        history.record_event(LifecycleEvent::Started).unwrap();
+        history.record_sample(None, Some(20)).unwrap();
+        history.record_sample(None, None).unwrap();
+        drop(history);
+
+        let history = HealthHistory::load(&path, retention).unwrap();
+        let _ = fs::remove_file(&path);
+
+        let events: Vec<_> = history
+            .records()
+            .filter_map(|r| match r {
+                HistoryRecord::Event { event, .. } => Some(*event),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(events, [LifecycleEvent::Started, LifecycleEvent::Down]);
+        assert_eq!(history.uptime(), Some(0.5));
+    }
+}
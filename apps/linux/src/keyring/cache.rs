@@ -0,0 +1,95 @@
+//! In-memory cache of recently retrieved secrets
+
+use super::qualified_name;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+use zeroize::Zeroizing;
+
+/// Recently retrieved secrets, kept for `ttl`
+///
+/// Values are zeroed in memory when they expire, are replaced or the cache is dropped.
+pub(super) struct SecretCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(String, String), (Instant, Zeroizing<String>)>>,
+}
+
+impl SecretCache {
+    pub(super) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// The cached value while fresh, otherwise the result of `fetch`
+    ///
+    /// Missing secrets are not cached, so a key stored elsewhere shows up on the next call.
+    pub(super) async fn get_or_fetch(
+        &self,
+        namespace: &str,
+        key: &str,
+        fetch: impl Future<Output = Result<Option<String>>>,
+    ) -> Result<Option<String>> {
+        let id = (namespace.to_string(), key.to_string());
+        {
+            let mut entries = self.entries.lock().unwrap();
+            match entries.get(&id) {
+                Some((at, value)) if at.elapsed() < self.ttl => {
+                    debug!("Secret served from cache: {}", qualified_name(namespace, key));
+                    return Ok(Some(value.to_string()));
+                }
+                Some(_) => {
+                    entries.remove(&id);
+                }
+                None => {}
+            }
+        }
+
+        let value = fetch.await?;
+        if let Some(value) = &value {
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(id, (Instant::now(), Zeroizing::new(value.clone())));
+        }
+        Ok(value)
+    }
+
+    pub(super) fn invalidate(&self, namespace: &str, key: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .remove(&(namespace.to_string(), key.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cache_skips_lookup_within_ttl() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = SecretCache::new(Duration::from_secs(60));
+        let lookups = AtomicUsize::new(0);
+        let lookup = || async {
+            lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(Some("secret".to_string()))
+        };
+
+        for _ in 0..2 {
+            let value = cache.get_or_fetch("default", "key", lookup()).await.unwrap();
+            assert_eq!(value.as_deref(), Some("secret"));
+        }
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+
+        cache.invalidate("default", "key");
+        cache.get_or_fetch("default", "key", lookup()).await.unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,178 @@
+//! Keyring integration using secret-service (libsecret)
+
+mod cache;
+mod secrets;
+mod session;
+
+use anyhow::{Context, Result};
+use cache::SecretCache;
+use secret_service::{Collection, EncryptionType, Item, SecretService};
+use session::check_session;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{info, warn};
+
+const SERVICE_NAME: &str = "vibeproxy";
+const COLLECTION_NAME: &str = "default";
+
+/// Namespace of keys stored without one, including those from before namespaces
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+#[derive(Debug, Error)]
+pub enum KeyringError {
+    /// The unlock prompt (or the secret service itself) did not answer in time
+    #[error("keyring prompt timed out — try again")]
+    Timeout,
+    /// The session bus belongs to a different user than this process
+    #[error("refusing to use another user's keyring: {0}")]
+    WrongSession(String),
+    /// The collection is still locked after the unlock prompt, e.g. it was dismissed
+    #[error("keyring is locked — unlock it and try again")]
+    Locked,
+}
+
+impl KeyringError {
+    /// Whether `error` is, or was caused by, a locked keyring
+    pub fn is_locked(error: &anyhow::Error) -> bool {
+        error.chain().any(|cause| {
+            matches!(cause.downcast_ref::<KeyringError>(), Some(KeyringError::Locked))
+        })
+    }
+}
+
+pub struct Keyring {
+    service: SecretService<'static>,
+    timeout: Duration,
+    cache: Option<SecretCache>,
+}
+
+impl Keyring {
+    /// Connect to the secret service, giving up on any single call or unlock
+    /// prompt after `timeout`
+    ///
+    /// With `cache_ttl`, retrieved secrets are kept in memory that long so
+    /// repeated lookups skip D-Bus; `None` always asks the secret service.
+    pub async fn new(timeout: Duration, cache_ttl: Option<Duration>) -> Result<Self> {
+        info!("Initializing keyring");
+
+        // secret-service lives on the per-user session bus; make sure it's ours
+        check_session()?;
+
+        // Connect to secret service
+        let service = with_timeout(timeout, SecretService::connect(EncryptionType::Dh))
+            .await?
+            .context("Failed to connect to secret service")?;
+
+        let keyring = Self {
+            service,
+            timeout,
+            cache: cache_ttl.map(SecretCache::new),
+        };
+
+        // Unlock up front so the prompt appears at startup
+        keyring.collection().await?;
+
+        info!("Keyring initialized successfully");
+
+        Ok(keyring)
+    }
+
+    /// Make sure the keyring is unlocked, prompting to unlock it if it isn't
+    ///
+    /// The collection can be locked again at any time (screen lock, idle
+    /// timeout), so every operation checks; this does the same up front.
+    /// Fails with [`KeyringError::Locked`] if it stays locked.
+    pub async fn ensure_unlocked(&self) -> Result<()> {
+        self.collection().await.map(drop)
+    }
+
+    /// Get the default collection, unlocking it if needed
+    async fn collection(&self) -> Result<Collection<'_>> {
+        let collection = self
+            .guard(self.service.get_default_collection())
+            .await?
+            .context("Failed to get default collection")?;
+
+        if self.is_locked(&collection).await? {
+            info!("Keyring is locked, asking to unlock it");
+            self.guard(collection.unlock())
+                .await?
+                .context("Failed to unlock keyring collection")?;
+            // A dismissed prompt returns without unlocking
+            if self.is_locked(&collection).await? {
+                warn!("Keyring is still locked");
+                return Err(KeyringError::Locked.into());
+            }
+        }
+
+        Ok(collection)
+    }
+
+    async fn is_locked(&self, collection: &Collection<'_>) -> Result<bool> {
+        Ok(self.guard(collection.is_locked()).await?.unwrap_or(false))
+    }
+
+    async fn guard<F: Future>(&self, fut: F) -> Result<F::Output, KeyringError> {
+        with_timeout(self.timeout, fut).await
+    }
+
+    /// Keep only the items that belong to `namespace`
+    ///
+    /// Items stored before namespaces existed have no namespace attribute and
+    /// belong to the default one.
+    async fn in_namespace<'i>(
+        &self,
+        items: Vec<Item<'i>>,
+        namespace: &str,
+    ) -> Result<Vec<Item<'i>>> {
+        let mut matching = Vec::with_capacity(items.len());
+        for item in items {
+            if let Ok(attrs) = self.guard(item.get_attributes()).await? {
+                if item_namespace(&attrs) == namespace {
+                    matching.push(item);
+                }
+            }
+        }
+        Ok(matching)
+    }
+
+    fn invalidate(&self, namespace: &str, key: &str) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(namespace, key);
+        }
+    }
+}
+
+/// `key`, prefixed with its namespace unless that is the default one
+pub fn qualified_name(namespace: &str, key: &str) -> String {
+    if namespace == DEFAULT_NAMESPACE {
+        key.to_string()
+    } else {
+        format!("{}/{}", namespace, key)
+    }
+}
+
+/// Attributes identifying `key`; the default namespace gets none, matching items stored before namespaces
+fn search_attributes<'a>(namespace: &'a str, key: &'a str) -> HashMap<&'a str, &'a str> {
+    let mut attributes = HashMap::from([("service", SERVICE_NAME), ("key", key)]);
+    if namespace != DEFAULT_NAMESPACE {
+        attributes.insert("namespace", namespace);
+    }
+    attributes
+}
+
+fn item_namespace(attributes: &HashMap<String, String>) -> &str {
+    attributes
+        .get("namespace")
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_NAMESPACE)
+}
+
+async fn with_timeout<F: Future>(timeout: Duration, fut: F) -> Result<F::Output, KeyringError> {
+    tokio::time::timeout(timeout, fut).await.map_err(|_| {
+        warn!("Keyring operation timed out after {:?}", timeout);
+        KeyringError::Timeout
+    })
+}
@@ -0,0 +1,247 @@
+//! Storing, retrieving and deleting secrets
+
+use super::{item_namespace, qualified_name, search_attributes, Keyring};
+use super::{DEFAULT_NAMESPACE, SERVICE_NAME};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+
+impl Keyring {
+    /// Store a secret in the keyring, in the default namespace if `namespace` is `None`
+    pub async fn store(&self, namespace: Option<&str>, key: &str, value: &str) -> Result<()> {
+        let namespace = namespace.unwrap_or(DEFAULT_NAMESPACE);
+        let name = qualified_name(namespace, key);
+        info!("Storing secret: {}", name);
+        self.invalidate(namespace, key);
+
+        let collection = self.collection().await?;
+        let label = format!("{}/{}", SERVICE_NAME, name);
+        let attributes = search_attributes(namespace, key);
+
+        // Create or update item
+        let existing = match self.guard(collection.search_items(attributes.clone())).await? {
+            Ok(items) => self.in_namespace(items, namespace).await?.pop(),
+            Err(e) => {
+                warn!("Search failed, creating new item: {}", e);
+                None
+            }
+        };
+
+        if let Some(item) = existing {
+            self.guard(item.set_secret(value.as_bytes(), "text/plain"))
+                .await?
+                .context("Failed to update secret")?;
+            info!("Updated existing secret: {}", name);
+        } else {
+            self.guard(collection.create_item(
+                &label,
+                attributes,
+                value.as_bytes(),
+                true,
+                "text/plain",
+            ))
+            .await?
+            .context("Failed to create secret")?;
+            info!("Created new secret: {}", name);
+        }
+
+        Ok(())
+    }
+
+    /// Retrieve a secret from the keyring, or the cache while it is fresh
+    pub async fn retrieve(&self, namespace: Option<&str>, key: &str) -> Result<Option<String>> {
+        let namespace = namespace.unwrap_or(DEFAULT_NAMESPACE);
+        match &self.cache {
+            Some(cache) => {
+                cache
+                    .get_or_fetch(namespace, key, self.fetch(namespace, key))
+                    .await
+            }
+            None => self.fetch(namespace, key).await,
+        }
+    }
+
+    /// Look a secret up over D-Bus
+    async fn fetch(&self, namespace: &str, key: &str) -> Result<Option<String>> {
+        let name = qualified_name(namespace, key);
+        info!("Retrieving secret: {}", name);
+
+        let collection = self.collection().await?;
+        let attributes = search_attributes(namespace, key);
+
+        match self.guard(collection.search_items(attributes)).await? {
+            Ok(items) => {
+                if let Some(item) = self.in_namespace(items, namespace).await?.pop() {
+                    let secret = self
+                        .guard(item.get_secret())
+                        .await?
+                        .context("Failed to get secret")?;
+                    let value = String::from_utf8(secret)
+                        .context("Secret is not valid UTF-8")?;
+                    info!("Retrieved secret: {}", name);
+                    Ok(Some(value))
+                } else {
+                    info!("Secret not found: {}", name);
+                    Ok(None)
+                }
+            }
+            Err(e) => {
+                error!("Failed to search for secret: {}", e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Retrieve several secrets from one namespace with a single keyring search
+    ///
+    /// Only keys that exist get an entry in the result.
+    pub async fn retrieve_many(
+        &self,
+        namespace: Option<&str>,
+        keys: &[&str],
+    ) -> Result<HashMap<String, String>> {
+        let namespace = namespace.unwrap_or(DEFAULT_NAMESPACE);
+        let collection = self.collection().await?;
+        let attributes = HashMap::from([("service", SERVICE_NAME)]);
+
+        let items = match self.guard(collection.search_items(attributes)).await? {
+            Ok(items) => items,
+            Err(e) => {
+                error!("Failed to search for secrets: {}", e);
+                return Ok(HashMap::new());
+            }
+        };
+
+        let mut secrets = HashMap::with_capacity(keys.len());
+        for item in &items {
+            let Ok(attrs) = self.guard(item.get_attributes()).await? else {
+                continue;
+            };
+            if item_namespace(&attrs) != namespace {
+                continue;
+            }
+            let Some(key) = attrs.get("key").filter(|key| keys.contains(&key.as_str())) else {
+                continue;
+            };
+            let secret = self
+                .guard(item.get_secret())
+                .await?
+                .context("Failed to get secret")?;
+            let value = String::from_utf8(secret).context("Secret is not valid UTF-8")?;
+            secrets.insert(key.clone(), value);
+        }
+
+        info!("Retrieved {} of {} secrets", secrets.len(), keys.len());
+        Ok(secrets)
+    }
+
+    /// Delete a secret from the keyring
+    pub async fn delete(&self, namespace: Option<&str>, key: &str) -> Result<()> {
+        let namespace = namespace.unwrap_or(DEFAULT_NAMESPACE);
+        let name = qualified_name(namespace, key);
+        info!("Deleting secret: {}", name);
+        self.invalidate(namespace, key);
+
+        let collection = self.collection().await?;
+        let attributes = search_attributes(namespace, key);
+
+        match self.guard(collection.search_items(attributes)).await? {
+            Ok(items) => {
+                for item in self.in_namespace(items, namespace).await? {
+                    self.guard(item.delete())
+                        .await?
+                        .context("Failed to delete secret")?;
+                }
+                info!("Deleted secret: {}", name);
+            }
+            Err(e) => {
+                warn!("Failed to search for secret to delete: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// List all stored keys as `(namespace, key)` pairs
+    pub async fn list_keys(&self) -> Result<Vec<(String, String)>> {
+        let collection = self.collection().await?;
+        let attributes = HashMap::from([("service", SERVICE_NAME)]);
+
+        match self.guard(collection.search_items(attributes)).await? {
+            Ok(items) => {
+                let mut keys = Vec::with_capacity(items.len());
+                for item in &items {
+                    if let Ok(attrs) = self.guard(item.get_attributes()).await? {
+                        if let Some(key) = attrs.get("key") {
+                            keys.push((item_namespace(&attrs).to_string(), key.clone()));
+                        }
+                    }
+                }
+                Ok(keys)
+            }
+            Err(e) => {
+                error!("Failed to list keys: {}", e);
+                Ok(vec![])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_keyring_operations() {
+        let keyring = Keyring::new(Duration::from_secs(30), None)
+            .await
+            .expect("Failed to create keyring");
+
+        // Test store and retrieve
+        keyring
+            .store(None, "test_key", "test_value")
+            .await
+            .expect("Failed to store secret");
+        let value = keyring
+            .retrieve(None, "test_key")
+            .await
+            .expect("Failed to retrieve secret");
+        assert_eq!(value, Some("test_value".to_string()));
+
+        // The same key in another namespace is a separate secret
+        keyring
+            .store(Some("work"), "test_key", "work_value")
+            .await
+            .expect("Failed to store namespaced secret");
+        let value = keyring
+            .retrieve(None, "test_key")
+            .await
+            .expect("Failed to retrieve secret");
+        assert_eq!(value, Some("test_value".to_string()));
+        let keys = keyring.list_keys().await.expect("Failed to list keys");
+        assert!(keys.contains(&("work".to_string(), "test_key".to_string())));
+        keyring
+            .delete(Some("work"), "test_key")
+            .await
+            .expect("Failed to delete namespaced secret");
+
+        let values = keyring
+            .retrieve_many(None, &["test_key", "missing_key"])
+            .await
+            .expect("Failed to retrieve secrets");
+        assert_eq!(values.len(), 1);
+        assert_eq!(values["test_key"], "test_value");
+
+        // Test delete
+        keyring
+            .delete(None, "test_key")
+            .await
+            .expect("Failed to delete secret");
+        let value = keyring
+            .retrieve(None, "test_key")
+            .await
+            .expect("Failed to retrieve secret");
+        assert_eq!(value, None);
+    }
+}
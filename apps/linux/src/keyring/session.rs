@@ -0,0 +1,45 @@
+//! Checks that the keyring we'd reach belongs to the current user
+
+use super::KeyringError;
+use std::os::unix::fs::MetadataExt;
+use tracing::warn;
+
+/// Make sure the session bus we'd talk to belongs to the current user
+pub(super) fn check_session() -> Result<(), KeyringError> {
+    let uid = std::fs::metadata("/proc/self")
+        .map(|m| m.uid())
+        .map_err(|e| KeyringError::WrongSession(format!("cannot determine current user: {}", e)))?;
+
+    if let Ok(sudo_user) = std::env::var("SUDO_USER") {
+        if uid == 0 {
+            return Err(KeyringError::WrongSession(format!(
+                "running as root via sudo; run VibeProxy as '{}' instead",
+                sudo_user
+            )));
+        }
+    }
+
+    let address = std::env::var("DBUS_SESSION_BUS_ADDRESS").unwrap_or_default();
+    let bus_path = address
+        .split(',')
+        .find_map(|part| part.strip_prefix("unix:path="));
+    if let Some(path) = bus_path {
+        match std::fs::metadata(path) {
+            Ok(meta) if meta.uid() != uid => {
+                return Err(KeyringError::WrongSession(format!(
+                    "session bus {} is owned by uid {}, but VibeProxy runs as uid {}; \
+                     log in as that user or start a session for this one",
+                    path,
+                    meta.uid(),
+                    uid
+                )));
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Cannot inspect session bus {}: {}", path, e),
+        }
+    } else if address.is_empty() {
+        warn!("DBUS_SESSION_BUS_ADDRESS is not set; the keyring may be unreachable");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! VibeProxy management library
+//!
+//! The non-GUI half of the Linux app: configuration, backend lifecycle and
+//! secret storage. Nothing here depends on GTK, so other tools can embed it
+//! with `default-features = false`:
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use vibeproxy_linux::{ConfigManager, ServerManager};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let config_manager = Arc::new(ConfigManager::new()?);
+//! let mut config = config_manager.load()?;
+//! config.core.backend.port = 9000;
+//! config_manager.save(&config)?;
+//!
+//! let server = ServerManager::new(config_manager, tokio::runtime::Handle::current())?;
+//! server.start().await?;
+//! println!("{:?}", server.status().await?);
+//! server.stop().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod backend_api;
+pub mod backend_connection;
+pub mod backend_logs;
+pub mod autostart;
+pub mod branding;
+pub mod completion_stream;
+pub mod config;
+pub mod config_manager;
+pub mod diagnostics;
+pub mod failover;
+pub mod file_vault;
+pub mod health;
+pub mod history;
+pub mod keyring;
+pub mod logging;
+pub mod metrics;
+pub mod process_usage;
+pub mod provider_import;
+pub mod recent_checks;
+pub mod run_state;
+pub mod sd_notify;
+pub mod secret_store;
+pub mod server_manager;
+pub mod shutdown;
+pub mod sleep_monitor;
+pub mod status_socket;
+
+pub use branding::Branding;
+pub use config::Config;
+pub use config_manager::ConfigManager;
+pub use keyring::{Keyring, KeyringError};
+pub use secret_store::SecretStore;
+pub use server_manager::{ApplyOutcome, ServerManager, ServerStatus};
@@ -0,0 +1,144 @@
+//! Backend log window
+
+use adw::prelude::*;
+use adw::HeaderBar;
+use gtk::{glib, Application, Button, ScrolledWindow, SearchEntry, TextBuffer, TextView};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use vibeproxy_linux::backend_logs::MAX_LINES;
+use vibeproxy_linux::server_manager::ServerManager;
+
+/// Names the window so a second request presents it instead of opening another
+const WINDOW_NAME: &str = "backend-logs";
+/// Right gravity, so it stays at the end as lines are appended
+const END_MARK: &str = "end";
+
+/// Show the output of the backend VibeProxy started, following new lines
+pub fn show_logs(app: &Application, server_manager: Arc<ServerManager>) {
+    if let Some(window) = app
+        .windows()
+        .into_iter()
+        .find(|window| window.widget_name() == WINDOW_NAME)
+    {
+        window.present();
+        return;
+    }
+
+    let window = gtk::Window::builder()
+        .application(app)
+        .title("Backend Logs")
+        .default_width(800)
+        .default_height(500)
+        .name(WINDOW_NAME)
+        .build();
+
+    let filter = SearchEntry::builder()
+        .placeholder_text("Filter lines")
+        .build();
+    let clear_button = Button::builder()
+        .label("Clear")
+        .tooltip_text("Forget the captured lines")
+        .build();
+    let header = HeaderBar::new();
+    header.pack_start(&filter);
+    header.pack_end(&clear_button);
+    window.set_titlebar(Some(&header));
+
+    let view = TextView::builder()
+        .editable(false)
+        .cursor_visible(false)
+        .monospace(true)
+        .wrap_mode(gtk::WrapMode::WordChar)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    let buffer = view.buffer();
+    buffer.create_mark(Some(END_MARK), &buffer.end_iter(), false);
+    window.set_child(Some(
+        &ScrolledWindow::builder()
+            .child(&view)
+            .vexpand(true)
+            .build(),
+    ));
+
+    let render = glib::clone!(
+        @weak view,
+        @weak buffer,
+        @weak filter,
+        @strong server_manager
+        => move || {
+            let pattern = filter.text().to_lowercase();
+            let lines: Vec<String> = server_manager
+                .recent_logs()
+                .into_iter()
+                .filter(|line| matches(line, &pattern))
+                .collect();
+            buffer.set_text(&lines.join("\n"));
+            scroll_to_end(&view);
+        }
+    );
+    render();
+    filter.connect_search_changed(glib::clone!(@strong render => move |_| render()));
+
+    clear_button.connect_clicked(glib::clone!(
+        @weak buffer,
+        @strong server_manager
+        => move |_| {
+            server_manager.clear_logs();
+            buffer.set_text("");
+        }
+    ));
+
+    // Weak so the subscription goes away with the window
+    let widgets = (view.downgrade(), filter.downgrade());
+    let mut updates = server_manager.log_updates();
+    glib::spawn_future_local(async move {
+        loop {
+            let result = updates.recv().await;
+            let (Some(view), Some(filter)) = (widgets.0.upgrade(), widgets.1.upgrade()) else {
+                break;
+            };
+            match result {
+                Ok(line) => {
+                    if matches(&line, &filter.text().to_lowercase()) {
+                        append(&view.buffer(), &line);
+                        scroll_to_end(&view);
+                    }
+                }
+                // Fell behind; the backend buffer has everything that was kept
+                Err(RecvError::Lagged(_)) => render(),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    window.present();
+}
+
+fn scroll_to_end(view: &TextView) {
+    if let Some(mark) = view.buffer().mark(END_MARK) {
+        view.scroll_to_mark(&mark, 0.0, false, 0.0, 1.0);
+    }
+}
+
+/// Case-insensitive substring match; `pattern` is already lowercase
+fn matches(line: &str, pattern: &str) -> bool {
+    pattern.is_empty() || line.to_lowercase().contains(pattern)
+}
+
+/// Add `line` at the end, dropping the oldest beyond what the backend buffer keeps
+fn append(buffer: &TextBuffer, line: &str) {
+    let mut end = buffer.end_iter();
+    if buffer.char_count() > 0 {
+        buffer.insert(&mut end, "\n");
+    }
+    buffer.insert(&mut end, line);
+
+    let excess = buffer.line_count() - MAX_LINES as i32;
+    if excess > 0 {
+        let mut start = buffer.start_iter();
+        if let Some(mut cut) = buffer.iter_at_line(excess) {
+            buffer.delete(&mut start, &mut cut);
+        }
+    }
+}
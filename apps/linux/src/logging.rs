@@ -0,0 +1,285 @@
+//! Rotating log files and their retention
+//!
+//! Logs roll over daily into the data directory. `tracing-appender` caps the
+//! number of files; a background cleanup also enforces the total size and age
+//! limits from [`LogSettings`].
+//!
+//! Every writer is wrapped in [`Redacted`], which masks API keys and bearer
+//! tokens in a formatted line before it reaches the console or a file.
+
+use crate::config::LogSettings;
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+
+const FILE_PREFIX: &str = "vibeproxy";
+const FILE_SUFFIX: &str = "log";
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Shown instead of a secret
+const MASK: &str = "***";
+/// Secrets start after one of these (matched case-insensitively); `sk-` is
+/// the OpenAI-style key prefix most providers copied
+const SECRET_PREFIXES: &[&str] = &["sk-", "bearer "];
+/// Anything shorter after a prefix is ordinary text, like "sk-1" or "bearer of"
+const MIN_SECRET_LEN: usize = 8;
+
+/// `~/.local/share/vibeproxy/logs`
+pub fn log_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "vibeproxy", "VibeProxy").map(|dirs| dirs.data_dir().join("logs"))
+}
+
+/// Daily rolling appender keeping at most `settings.max_files` files
+pub fn file_appender(dir: &Path, settings: &LogSettings) -> Result<RollingFileAppender> {
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(FILE_PREFIX)
+        .filename_suffix(FILE_SUFFIX)
+        .max_log_files(settings.max_files.max(1))
+        .build(dir)
+        .with_context(|| format!("Failed to open log directory {:?}", dir))
+}
+
+/// Wraps a log writer so each formatted line is passed through [`redact`]
+///
+/// `fmt` layers take a fresh writer per event and write the whole line to
+/// it, so buffering until the writer is flushed or dropped sees a secret in
+/// one piece.
+#[derive(Debug, Clone)]
+pub struct Redacted<M>(pub M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for Redacted<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            inner: self.0.make_writer(),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+pub struct RedactingWriter<W: Write> {
+    inner: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> RedactingWriter<W> {
+    fn write_buffered(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&self.buffer);
+        self.inner.write_all(redact(&text).as_bytes())?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buffered()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for RedactingWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.write_buffered();
+    }
+}
+
+/// `text` with anything that looks like an API key or bearer token masked
+///
+/// `sk-abc…` becomes `sk-***` and `Bearer abc…` becomes `Bearer ***`, so the
+/// kind of credential is still visible.
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let lower = text.to_ascii_lowercase();
+    let mut redacted = String::new();
+    let mut copied = 0;
+    let mut pos = 0;
+
+    while pos < text.len() {
+        let prefix = SECRET_PREFIXES.iter().find(|prefix| {
+            lower[pos..].starts_with(*prefix) && !ends_word(&text[..pos])
+        });
+        let Some(prefix) = prefix else {
+            pos += text[pos..].chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+
+        let start = pos + prefix.len();
+        let len = text[start..]
+            .find(|c: char| !is_token_char(c))
+            .unwrap_or(text.len() - start);
+        if len >= MIN_SECRET_LEN {
+            redacted.push_str(&text[copied..start]);
+            redacted.push_str(MASK);
+            copied = start + len;
+        }
+        pos = start + len;
+    }
+
+    if copied == 0 {
+        return Cow::Borrowed(text);
+    }
+    redacted.push_str(&text[copied..]);
+    Cow::Owned(redacted)
+}
+
+/// Whether `text` ends inside a word, e.g. "task-" before "sk-"
+fn ends_word(text: &str) -> bool {
+    text.chars().next_back().is_some_and(|c| c.is_alphanumeric())
+}
+
+/// Characters of keys and tokens, including base64 and JWT punctuation
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~' | '+' | '/' | '=')
+}
+
+/// Prune now and then hourly on a background thread
+pub fn spawn_cleanup(dir: PathBuf, settings: LogSettings) {
+    std::thread::Builder::new()
+        .name("log-cleanup".to_string())
+        .spawn(move || loop {
+            match prune(&dir, &settings) {
+                Ok(0) => {}
+                Ok(removed) => info!("Removed {} old log files", removed),
+                Err(e) => warn!("Log cleanup failed: {:#}", e),
+            }
+            std::thread::sleep(CLEANUP_INTERVAL);
+        })
+        .expect("Failed to spawn log cleanup thread");
+}
+
+/// Delete log files older than `max_age_days`, then the oldest ones until the
+/// total fits in `max_total_mb`; the newest file is always kept
+pub fn prune(dir: &Path, settings: &LogSettings) -> Result<usize> {
+    let mut files = log_files(dir)?;
+    // Newest first
+    files.sort_by(|a, b| b.modified.cmp(&a.modified));
+
+    // Saturating, so an unvalidated limit keeps everything instead of wrapping
+    let max_age = Duration::from_secs(settings.max_age_days.saturating_mul(24 * 60 * 60));
+    let max_total = settings.max_total_mb.saturating_mul(1024 * 1024);
+    let now = SystemTime::now();
+
+    let mut total = 0;
+    let mut removed = 0;
+    for (index, file) in files.iter().enumerate() {
+        let too_old = now
+            .duration_since(file.modified)
+            .is_ok_and(|age| age > max_age);
+        let over_budget = total + file.size > max_total;
+
+        if index > 0 && (too_old || over_budget) {
+            fs::remove_file(&file.path)
+                .with_context(|| format!("Failed to remove {:?}", file.path))?;
+            removed += 1;
+        } else {
+            total += file.size;
+        }
+    }
+    Ok(removed)
+}
+
+/// Disk space taken by log files
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogUsage {
+    pub files: usize,
+    pub total_bytes: u64,
+}
+
+pub fn usage(dir: &Path) -> Result<LogUsage> {
+    let files = log_files(dir)?;
+    Ok(LogUsage {
+        files: files.len(),
+        total_bytes: files.iter().map(|f| f.size).sum(),
+    })
+}
+
+struct LogFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn log_files(dir: &Path) -> Result<Vec<LogFile>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", dir)),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if !name.starts_with(FILE_PREFIX) || !name.ends_with(FILE_SUFFIX) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_file() {
+            files.push(LogFile {
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_log_lines_mask_secrets() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer(Redacted(move || writer.clone()))
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            info!("Using key sk-proj-4f9Xa21bQz for task-sk-lookup");
+            info!("Authorization: Bearer eyJhbGciOi.J9.sig; bearer of news");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Using key sk-*** for task-sk-lookup"));
+        assert!(output.contains("Authorization: Bearer ***; bearer of news"));
+        assert!(!output.contains("4f9Xa21bQz"));
+        assert!(!output.contains("eyJhbGciOi"));
+    }
+}
@@ -1,36 +1,275 @@
 //! VibeProxy Linux Application
 //!
 //! GTK4-based desktop application for managing Bifrost-enhanced AI routing.
+//! `--headless` runs the same backend supervision without a display.
 
 mod app;
-mod config_manager;
-mod keyring;
-mod server_manager;
+mod headless;
+mod log_viewer;
+mod self_test;
+mod settings;
 mod system_tray;
 mod ui;
 
-use anyhow::Result;
-use gtk::prelude::*;
-use gtk::{gio, glib};
-use tracing_subscriber;
-
-fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "vibeproxy=info".into()),
-        )
-        .init();
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use tokio::runtime::Runtime;
+use tracing::error;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
+use vibeproxy_linux::config_manager::SCHEMA_VERSION;
+use vibeproxy_linux::logging::Redacted;
+use vibeproxy_linux::secret_store;
+use vibeproxy_linux::{logging, ConfigManager};
+
+/// The app, or a one-shot command, failed
+const EXIT_FAILURE: u8 = 1;
+/// The config file is unreadable or invalid, or has nowhere safe to live
+const EXIT_CONFIG: u8 = 3;
+/// GTK could not be initialized, usually because there is no display
+const EXIT_NO_DISPLAY: u8 = 4;
+
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  Success
+  1  The app or a command failed
+  2  Invalid arguments
+  3  The config file could not be loaded (try --safe-mode)
+  4  No display to show the app on";
+
+/// VibeProxy: manage Bifrost-enhanced AI routing from the desktop
+#[derive(Parser)]
+#[command(name = "vibeproxy", version, after_help = EXIT_CODES_HELP)]
+struct Cli {
+    /// Use this config file instead of the standard location
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Stay in the system tray instead of showing the main window
+    #[arg(long)]
+    start_minimized: bool,
+
+    /// Start the backend once the app is up
+    #[arg(long)]
+    autostart_server: bool,
+
+    /// Don't start the backend just because it was running when the app last quit
+    #[arg(long, conflicts_with = "autostart_server")]
+    no_autostart: bool,
+
+    /// Start with default settings, ignoring the config file and keyring
+    #[arg(long, conflicts_with = "autostart_server")]
+    safe_mode: bool,
+
+    /// Supervise the backend without a window or tray until SIGINT/SIGTERM
+    #[arg(long, conflicts_with_all = ["start_minimized", "safe_mode"])]
+    headless: bool,
+
+    /// Write a default config file, to PATH if given, and exit
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    generate_config: Option<Option<PathBuf>>,
+
+    /// Overwrite an existing file with --generate-config
+    #[arg(long, requires = "generate_config")]
+    force: bool,
+
+    /// Upgrade the config file to the current schema and exit
+    #[arg(long)]
+    migrate: bool,
+
+    /// Show what --migrate would change without writing it
+    #[arg(long, requires = "migrate")]
+    dry_run: bool,
+
+    /// Store provider API keys found in the environment (OPENAI_API_KEY, ...) and exit
+    #[arg(long, conflicts_with = "safe_mode")]
+    import_env_keys: bool,
+
+    /// Replace keys that are already stored with --import-env-keys
+    #[arg(long, requires = "import_env_keys")]
+    overwrite: bool,
+
+    /// Exercise the backend lifecycle for CI/packaging
+    #[arg(long, hide = true)]
+    self_test: bool,
+}
+
+impl Cli {
+    fn config_manager(&self) -> Result<ConfigManager> {
+        let config_manager = match &self.config {
+            Some(path) => ConfigManager::with_path(path.clone()),
+            None => ConfigManager::new()?,
+        };
+        Ok(if self.safe_mode {
+            config_manager.into_safe_mode()
+        } else {
+            config_manager
+        })
+    }
+}
+
+fn main() -> ExitCode {
+    // Invalid arguments exit with 2, --help and --version with 0
+    let cli = Cli::parse();
+
+    // Initialize logging; the guard flushes the log file on exit
+    let _log_guard = init_logging(&cli);
+
+    // Handle one-shot CLI commands before touching GTK
+    let command = if let Some(path) = &cli.generate_config {
+        Some(generate_config(&cli, path.as_deref()))
+    } else if cli.migrate {
+        Some(migrate(&cli))
+    } else if cli.import_env_keys {
+        Some(import_env_keys(&cli))
+    } else if cli.self_test {
+        Some(self_test::run())
+    } else {
+        None
+    };
+    if let Some(result) = command {
+        return match result {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                ExitCode::from(EXIT_FAILURE)
+            }
+        };
+    }
+
+    // Fail before any window appears so scripts and service managers can tell
+    let config_manager = match cli.config_manager() {
+        Ok(config_manager) => config_manager,
+        Err(e) => {
+            error!("No usable config location: {:#}", e);
+            eprintln!("Error: {:#}", e);
+            return ExitCode::from(EXIT_CONFIG);
+        }
+    };
+    if let Err(e) = config_manager.load() {
+        error!("Failed to load configuration: {:#}", e);
+        eprintln!("Error: {:#}\nRun with --safe-mode to fix the settings.", e);
+        return ExitCode::from(EXIT_CONFIG);
+    }
+
+    if cli.headless {
+        return match headless::run(config_manager) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("Headless mode failed: {:#}", e);
+                eprintln!("Error: {:#}", e);
+                ExitCode::from(EXIT_FAILURE)
+            }
+        };
+    }
 
     // Initialize GTK
-    gtk::init()?;
+    if let Err(e) = gtk::init() {
+        eprintln!("Error: failed to initialize GTK: {}", e);
+        return ExitCode::from(EXIT_NO_DISPLAY);
+    }
 
     // Create application
-    let app = app::VibeProxyApp::new();
-    
+    let options = app::LaunchOptions {
+        start_minimized: cli.start_minimized,
+        autostart_server: cli.autostart_server,
+        resume_server: !cli.no_autostart,
+    };
+    let app = app::VibeProxyApp::new(config_manager, options);
+
     // Run application
-    app.run();
+    match app.run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+fn generate_config(cli: &Cli, path: Option<&Path>) -> Result<()> {
+    let written = cli.config_manager()?.generate_default(path, cli.force)?;
+    println!("Wrote default configuration to {}", written.display());
+    Ok(())
+}
+
+fn migrate(cli: &Cli) -> Result<()> {
+    let config_manager = cli.config_manager()?;
+    if cli.dry_run {
+        let preview = config_manager.preview_migration()?;
+        if preview.is_noop() {
+            println!("Config is up to date (schema version {})", SCHEMA_VERSION);
+        } else {
+            print!("{}", preview.diff());
+        }
+    } else {
+        match config_manager.migrate()? {
+            Some(backup) => println!("Config migrated, original saved to {}", backup.display()),
+            None => println!("Config is up to date (schema version {})", SCHEMA_VERSION),
+        }
+    }
+    Ok(())
+}
+
+fn import_env_keys(cli: &Cli) -> Result<()> {
+    let config = cli.config_manager()?.load()?;
+    let sources = secret_store::env_key_sources(&config);
+    let runtime = Runtime::new().context("Failed to create Tokio runtime")?;
+    let summary = runtime.block_on(async {
+        let store = secret_store::open(&config.keyring).await?;
+        let lookup = |var: &str| std::env::var(var).ok();
+        secret_store::import_env_keys(store.as_ref(), &sources, lookup, cli.overwrite).await
+    })?;
 
+    if summary.imported.is_empty() && summary.skipped.is_empty() {
+        let vars: Vec<&str> = sources.iter().map(|(var, _)| var.as_str()).collect();
+        println!("No API keys found in the environment (checked {})", vars.join(", "));
+    }
+    for name in &summary.imported {
+        println!("Imported {}", name);
+    }
+    for name in &summary.skipped {
+        println!("Skipped {}: already stored, use --overwrite to replace it", name);
+    }
     Ok(())
 }
+
+/// Log to stderr and, unless disabled in the config, to rotating files
+fn init_logging(cli: &Cli) -> Option<WorkerGuard> {
+    let settings = cli
+        .config_manager()
+        .and_then(|config_manager| config_manager.load())
+        .map(|config| config.logging)
+        .unwrap_or_default();
+
+    let mut guard = None;
+    let file_layer = settings
+        .file_enabled
+        .then(logging::log_dir)
+        .flatten()
+        .and_then(|dir| match logging::file_appender(&dir, &settings) {
+            Ok(appender) => {
+                logging::spawn_cleanup(dir, settings.clone());
+                let (writer, worker_guard) = tracing_appender::non_blocking(appender);
+                guard = Some(worker_guard);
+                Some(fmt::layer().with_ansi(false).with_writer(Redacted(writer)))
+            }
+            Err(e) => {
+                eprintln!("File logging disabled: {:#}", e);
+                None
+            }
+        });
+
+    tracing_subscriber::registry()
+        .with(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| "vibeproxy=info".into()),
+        )
+        .with(fmt::layer().with_writer(Redacted(std::io::stdout)))
+        .with(file_layer)
+        .init();
+
+    guard
+}
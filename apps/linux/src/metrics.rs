@@ -0,0 +1,248 @@
+//! Prometheus metrics over HTTP
+//!
+//! An optional plain-HTTP endpoint answering `GET /metrics` in the
+//! Prometheus text format, so the desktop agent can be scraped next to the
+//! backend. The gauges follow the same status updates as the window and tray.
+//! With `metrics.require_token`, requests without the keyring's
+//! [`METRICS_TOKEN`] as a bearer token get 401.
+
+use crate::config::MetricsSettings;
+use crate::secret_store::METRICS_TOKEN;
+use crate::server_manager::{ServerManager, ServerStatus};
+use anyhow::{bail, Context, Result};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+/// Longest request line and headers read; scrapes send far less
+const MAX_HEAD_BYTES: u64 = 8 * 1024;
+/// Time a client has to send its request before the connection is dropped
+const HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Bind `settings.bind_address` and answer scrapes until the task is dropped
+///
+/// Doesn't start at all when a token is required but can't be read.
+pub async fn serve(
+    server_manager: Arc<ServerManager>,
+    statuses: watch::Receiver<Option<ServerStatus>>,
+    settings: MetricsSettings,
+) -> Result<()> {
+    let token: Option<Arc<str>> = if settings.require_token {
+        match server_manager.stored_secret(METRICS_TOKEN).await? {
+            Some(token) if !token.is_empty() => Some(token.into()),
+            _ => bail!(
+                "metrics.require_token is set but there is no '{}' in the keyring",
+                METRICS_TOKEN
+            ),
+        }
+    } else {
+        None
+    };
+
+    let address = settings.bind_address;
+    let listener = TcpListener::bind(address)
+        .await
+        .with_context(|| format!("Failed to bind metrics endpoint {}", address))?;
+    info!(
+        "Metrics endpoint listening on http://{}/metrics{}",
+        address,
+        if token.is_some() { ", token required" } else { "" }
+    );
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .context("Metrics endpoint accept failed")?;
+        let server_manager = server_manager.clone();
+        let statuses = statuses.clone();
+        let token = token.clone();
+        tokio::spawn(async move {
+            if let Err(e) = respond(stream, &server_manager, &statuses, token.as_deref()).await {
+                warn!("Metrics client: {}", e);
+            }
+        });
+    }
+}
+
+async fn respond(
+    mut stream: TcpStream,
+    server_manager: &ServerManager,
+    statuses: &watch::Receiver<Option<ServerStatus>>,
+    token: Option<&str>,
+) -> Result<()> {
+    // Bounded in size and time, so a client can't hold memory or a task forever
+    let head = read_head(BufReader::new((&mut stream).take(MAX_HEAD_BYTES)));
+    let (request_line, authorization) = tokio::time::timeout(HEAD_TIMEOUT, head)
+        .await
+        .with_context(|| format!("No request within {:?}", HEAD_TIMEOUT))??;
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let response = if !authorized(authorization.as_deref(), token) {
+        "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Bearer\r\nContent-Length: 0\r\n\
+         Connection: close\r\n\r\n"
+            .to_string()
+    } else if path == "/metrics" {
+        let body = render(
+            statuses.borrow().as_ref(),
+            server_manager.restart_count(),
+            server_manager.failed_check_count(),
+        );
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            CONTENT_TYPE,
+            body.len(),
+            body
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// The request line and `Authorization` header; scrapes have no body
+async fn read_head<R: AsyncBufReadExt + Unpin>(mut reader: R) -> Result<(String, Option<String>)> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut authorization = None;
+    let mut line = String::new();
+    while reader.read_line(&mut line).await? > 0 && line != "\r\n" {
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            }
+        }
+        line.clear();
+    }
+    Ok((request_line, authorization))
+}
+
+/// Whether `authorization` is `Bearer <token>`; anything goes without a token
+///
+/// Compared without stopping at the first difference, so response times
+/// don't give the token away.
+fn authorized(authorization: Option<&str>, token: Option<&str>) -> bool {
+    let Some(token) = token else {
+        return true;
+    };
+    let Some((scheme, given)) = authorization.and_then(|value| value.split_once(' ')) else {
+        return false;
+    };
+    let given = given.trim();
+    scheme.eq_ignore_ascii_case("bearer")
+        && given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// The metrics page for the latest `status` and the manager's counters
+///
+/// Latency is left out until a check has reached the backend.
+pub fn render(status: Option<&ServerStatus>, restarts: u64, failed_checks: u64) -> String {
+    let running = status.is_some_and(|status| status.running);
+    let ready = status.is_some_and(|status| status.ready);
+
+    let mut page = String::new();
+    metric(
+        &mut page,
+        "vibeproxy_backend_up",
+        "gauge",
+        "Backend answers health checks",
+        running as u64,
+    );
+    metric(
+        &mut page,
+        "vibeproxy_backend_ready",
+        "gauge",
+        "Backend has finished starting up",
+        ready as u64,
+    );
+    if let Some(status) = status.filter(|status| status.running) {
+        metric(
+            &mut page,
+            "vibeproxy_backend_latency_ms",
+            "gauge",
+            "Latency of the last health check in milliseconds",
+            status.latency_ms,
+        );
+    }
+    metric(
+        &mut page,
+        "vibeproxy_backend_restarts_total",
+        "counter",
+        "Crash restarts of the spawned backend",
+        restarts,
+    );
+    metric(
+        &mut page,
+        "vibeproxy_health_check_failures_total",
+        "counter",
+        "Health checks that found the backend down",
+        failed_checks,
+    );
+    page
+}
+
+fn metric(page: &mut String, name: &str, kind: &str, help: &str, value: u64) {
+    let _ = writeln!(page, "# HELP {} {}", name, help);
+    let _ = writeln!(page, "# TYPE {} {}", name, kind);
+    let _ = writeln!(page, "{} {}", name, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus_text() {
+        let down = render(None, 0, 0);
+        assert!(down.contains("vibeproxy_backend_up 0\n"));
+        assert!(!down.contains("vibeproxy_backend_latency_ms"));
+
+        let status = ServerStatus {
+            running: true,
+            ready: true,
+            latency_ms: 42,
+            message: None,
+            endpoint: None,
+            details: None,
+        };
+        let up = render(Some(&status), 2, 5);
+        assert!(up.contains("# TYPE vibeproxy_backend_up gauge\nvibeproxy_backend_up 1\n"));
+        assert!(up.contains("vibeproxy_backend_latency_ms 42\n"));
+        assert!(up.contains("# TYPE vibeproxy_backend_restarts_total counter\n"));
+        assert!(up.contains("vibeproxy_backend_restarts_total 2\n"));
+        assert!(up.contains("vibeproxy_health_check_failures_total 5\n"));
+
+        assert!(authorized(None, None));
+        assert!(authorized(Some("bearer s3cret"), Some("s3cret")));
+        assert!(!authorized(None, Some("s3cret")));
+        assert!(!authorized(Some("Bearer s3cre"), Some("s3cret")));
+        assert!(!authorized(Some("Basic s3cret"), Some("s3cret")));
+    }
+
+    #[tokio::test]
+    async fn test_read_head_stops_at_the_limit() {
+        let request = b"GET /metrics HTTP/1.1\r\nAuthorization: Bearer s3cret\r\n\r\n";
+        let (request_line, authorization) = read_head(&request[..]).await.unwrap();
+        assert_eq!(request_line, "GET /metrics HTTP/1.1\r\n");
+        assert_eq!(authorization.as_deref(), Some("Bearer s3cret"));
+
+        // An endless header line ends at the limit instead of growing
+        let endless = tokio::io::repeat(b'a').take(MAX_HEAD_BYTES);
+        let (request_line, _) = read_head(BufReader::new(endless)).await.unwrap();
+        assert_eq!(request_line.len() as u64, MAX_HEAD_BYTES);
+    }
+}
@@ -0,0 +1,107 @@
+//! CPU and memory use of the backend process
+//!
+//! Read from `/proc/<pid>/stat` and `/proc/<pid>/statm`. CPU is measured
+//! between two samples as a share of one core, so a busy multithreaded
+//! backend can go past 100%.
+
+use anyhow::{Context, Result};
+use nix::unistd::{sysconf, SysconfVar};
+use std::fs;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceUsage {
+    pub pid: u32,
+    /// `None` for the first sample of a process
+    pub cpu_percent: Option<f64>,
+    /// Resident memory
+    pub rss_bytes: u64,
+}
+
+/// Remembers the previous sample to turn CPU time into a percentage
+#[derive(Debug, Default)]
+pub struct UsageSampler {
+    previous: Option<CpuSample>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CpuSample {
+    pid: u32,
+    ticks: u64,
+    at: Instant,
+}
+
+impl UsageSampler {
+    pub fn sample(&mut self, pid: u32) -> Result<ResourceUsage> {
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid))
+            .with_context(|| format!("Failed to read the stats of process {}", pid))?;
+        let statm = fs::read_to_string(format!("/proc/{}/statm", pid))
+            .with_context(|| format!("Failed to read the memory use of process {}", pid))?;
+        let ticks = cpu_ticks(&stat).context("Unexpected /proc/<pid>/stat format")?;
+        let rss_pages = rss_pages(&statm).context("Unexpected /proc/<pid>/statm format")?;
+
+        let now = CpuSample {
+            pid,
+            ticks,
+            at: Instant::now(),
+        };
+        let cpu_percent = match self.previous.replace(now) {
+            // A restarted backend has a new pid and starts counting again
+            Some(previous) if previous.pid == pid => {
+                let elapsed = now.at.duration_since(previous.at).as_secs_f64();
+                let used = ticks.saturating_sub(previous.ticks) as f64 / clock_ticks();
+                (elapsed > 0.0).then(|| used / elapsed * 100.0)
+            }
+            _ => None,
+        };
+
+        Ok(ResourceUsage {
+            pid,
+            cpu_percent,
+            rss_bytes: rss_pages * page_size(),
+        })
+    }
+}
+
+/// User plus system time in clock ticks
+///
+/// The command name in parentheses may contain spaces, so fields are counted
+/// from the last `)`: `utime` and `stime` are the 14th and 15th overall.
+fn cpu_ticks(stat: &str) -> Option<u64> {
+    let fields: Vec<&str> = stat.get(stat.rfind(')')? + 1..)?.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// Resident set size in pages, the second field
+fn rss_pages(statm: &str) -> Option<u64> {
+    statm.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn clock_ticks() -> f64 {
+    sysconf(SysconfVar::CLK_TCK).ok().flatten().unwrap_or(100) as f64
+}
+
+fn page_size() -> u64 {
+    sysconf(SysconfVar::PAGE_SIZE).ok().flatten().unwrap_or(4096) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_fields() {
+        let stat = "4242 (bifrost (v2) x) S 1 4242 4242 0 -1 4194560 \
+                    1234 0 0 0 150 25 0 0 20 0 8 0 98765 1048576 2048";
+        assert_eq!(cpu_ticks(stat), Some(175));
+        assert_eq!(rss_pages("262144 2048 512 100 0 4000 0\n"), Some(2048));
+
+        let mut sampler = UsageSampler::default();
+        let usage = sampler.sample(std::process::id()).unwrap();
+        assert_eq!(usage.cpu_percent, None);
+        assert!(usage.rss_bytes > 0);
+        assert!(sampler.sample(std::process::id()).unwrap().cpu_percent.is_some());
+    }
+}
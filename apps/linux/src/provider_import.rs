@@ -0,0 +1,262 @@
+//! Importing a provider setup shared as a JSON file
+//!
+//! The file names a backend profile and the providers behind it, keys
+//! included; every part is optional:
+//!
+//! ```json
+//! {
+//!   "profile": "acme",
+//!   "backend": { "url": "https://bifrost.acme.example", "port": 443, "api_key": "..." },
+//!   "providers": [
+//!     { "id": "openai", "api_key": "sk-...", "base_url": "https://...", "models": ["gpt-4o"] }
+//!   ]
+//! }
+//! ```
+//!
+//! Keys go to the secret store, never the config file: provider keys under
+//! the provider id like the settings window saves them, the backend key under
+//! `profiles.<name>.api_key`. Nothing is written until the merged config has
+//! validated.
+
+use crate::config::{check_backend_url, profile_key_secret, Config, Provider};
+use crate::config_manager::ConfigManager;
+use crate::secret_store;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use tracing::info;
+use zeroize::Zeroizing;
+
+#[derive(Deserialize)]
+struct ImportFile {
+    profile: Option<String>,
+    backend: Option<ImportBackend>,
+    #[serde(default)]
+    providers: Vec<ImportProvider>,
+}
+
+#[derive(Deserialize)]
+struct ImportBackend {
+    url: String,
+    port: Option<u16>,
+    api_key: Option<String>,
+    timeout_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ImportProvider {
+    id: String,
+    api_key: Option<String>,
+    base_url: Option<String>,
+    #[serde(default)]
+    models: Vec<String>,
+}
+
+/// A parsed import file, checked against the config before anything is written
+pub struct ProviderImport {
+    profile: Option<(String, ImportBackend)>,
+    providers: Vec<ImportProvider>,
+}
+
+impl ProviderImport {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        Self::parse(&content).with_context(|| format!("{:?} isn't a provider import file", path))
+    }
+
+    pub fn parse(json: &str) -> Result<Self> {
+        let file: ImportFile = serde_json::from_str(json).context("Invalid JSON")?;
+        let profile = match (file.profile, file.backend) {
+            (Some(name), Some(backend)) if !name.trim().is_empty() => Some((name, backend)),
+            (_, Some(_)) => bail!("\"backend\" needs a \"profile\" name to be saved under"),
+            (_, None) => None,
+        };
+        if profile.is_none() && file.providers.is_empty() {
+            bail!("Nothing to import: no \"backend\" and no \"providers\"");
+        }
+        if let Some(provider) = file.providers.iter().find(|p| p.id.trim().is_empty()) {
+            bail!("A provider has no id (models: {:?})", provider.models);
+        }
+        Ok(Self {
+            profile,
+            providers: file.providers,
+        })
+    }
+
+    /// One line per change `apply` would make to `config`, for a preview
+    pub fn preview(&self, config: &Config) -> Vec<ImportChange> {
+        let mut changes = Vec::new();
+        if let Some((name, backend)) = &self.profile {
+            changes.push(ImportChange {
+                subject: format!("Profile '{}'", name),
+                replaces: config.profiles.contains_key(name),
+                detail: format!(
+                    "{}:{}",
+                    backend.url.trim_end_matches('/'),
+                    backend.port.unwrap_or(config.core.backend.port)
+                ),
+                stores_key: backend.api_key.is_some(),
+            });
+        }
+        for provider in &self.providers {
+            let mut detail = match provider.models.len() {
+                0 => "all models".to_string(),
+                1 => "1 model".to_string(),
+                n => format!("{} models", n),
+            };
+            if let Some(base_url) = &provider.base_url {
+                detail.push_str(&format!(", via {}", base_url));
+            }
+            changes.push(ImportChange {
+                subject: format!("Provider '{}'", provider.id),
+                replaces: config.providers.iter().any(|p| p.id == provider.id),
+                detail,
+                stores_key: provider.api_key.is_some(),
+            });
+        }
+        changes
+    }
+
+    /// Merge the profile and providers into `config`, without their keys
+    ///
+    /// A provider with the same id is replaced, keeping whether it was
+    /// enabled. The result is validated.
+    pub fn apply(&self, config: &mut Config) -> Result<()> {
+        if let Some((name, imported)) = &self.profile {
+            let url = imported.url.trim_end_matches('/');
+            check_backend_url(url).with_context(|| format!("Backend of profile '{}'", name))?;
+            let mut backend = config.core.backend.clone();
+            backend.url = url.to_string();
+            backend.port = imported.port.unwrap_or(backend.port);
+            backend.timeout_secs = imported.timeout_secs.unwrap_or(backend.timeout_secs);
+            backend.api_key = None;
+            config.add_profile(name, backend)?;
+            let secret = profile_key_secret(name);
+            if imported.api_key.is_some() && !config.keyring.stored_secrets.contains(&secret) {
+                config.keyring.stored_secrets.push(secret);
+            }
+        }
+
+        for imported in &self.providers {
+            let mut provider = Provider::new(imported.id.trim());
+            provider.base_url_override = imported.base_url.clone();
+            provider.models = imported.models.clone();
+            match config.providers.iter_mut().find(|p| p.id == provider.id) {
+                Some(existing) => {
+                    provider.enabled = existing.enabled;
+                    *existing = provider;
+                }
+                None => config.providers.push(provider),
+            }
+        }
+
+        config.validate()?;
+        Ok(())
+    }
+
+    /// `(keyring key, value)` for every key in the file
+    fn secrets(&self) -> Vec<(String, Zeroizing<String>)> {
+        let profile = self.profile.iter().filter_map(|(name, backend)| {
+            let key = backend.api_key.clone()?;
+            Some((profile_key_secret(name), Zeroizing::new(key)))
+        });
+        let providers = self.providers.iter().filter_map(|provider| {
+            let key = provider.api_key.clone()?;
+            Some((provider.id.trim().to_string(), Zeroizing::new(key)))
+        });
+        profile.chain(providers).collect()
+    }
+
+    /// Validate against the current config, store the keys, then save the config
+    ///
+    /// The secret store is only opened when the file has keys in it.
+    pub async fn commit(&self, config_manager: &ConfigManager) -> Result<()> {
+        let mut config = config_manager.load()?;
+        self.apply(&mut config)?;
+
+        let secrets = self.secrets();
+        if !secrets.is_empty() {
+            let store = secret_store::open(&config.keyring).await?;
+            for (key, value) in &secrets {
+                store.store(None, key, value).await?;
+            }
+        }
+        config_manager.save(&config)?;
+        info!(
+            "Imported {} providers and {} keys",
+            self.providers.len(),
+            secrets.len()
+        );
+        Ok(())
+    }
+}
+
+/// One entry of an import preview
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportChange {
+    pub subject: String,
+    /// There is one of the same name already
+    pub replaces: bool,
+    pub detail: String,
+    pub stores_key: bool,
+}
+
+impl fmt::Display for ImportChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.subject, self.detail)?;
+        if self.replaces {
+            write!(f, ", replaces the existing one")?;
+        }
+        if self.stores_key {
+            write!(f, ", key stored in the keyring")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_keeps_keys_out_of_the_config() {
+        let import = ProviderImport::parse(
+            r#"{
+                "profile": "acme",
+                "backend": {
+                    "url": "https://bifrost.acme.example/",
+                    "port": 443,
+                    "api_key": "sk-b"
+                },
+                "providers": [
+                    { "id": "openai", "api_key": "sk-o", "models": ["gpt-4o"] },
+                    { "id": "local", "base_url": "http://127.0.0.1:11434" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config.providers.push(Provider::new("openai"));
+        config.providers[0].enabled = false;
+        let preview = import.preview(&config);
+        assert!(preview[1].replaces && preview[1].stores_key);
+        assert!(!preview[2].replaces && !preview[2].stores_key);
+
+        import.apply(&mut config).unwrap();
+        assert_eq!(config.profiles["acme"].backend.url, "https://bifrost.acme.example");
+        assert_eq!(config.profiles["acme"].backend.api_key, None);
+        assert_eq!(config.keyring.stored_secrets, ["profiles.acme.api_key"]);
+        assert!(!config.providers[0].enabled);
+        assert_eq!(config.providers[0].models, ["gpt-4o"]);
+        let keys: Vec<String> = import.secrets().into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["profiles.acme.api_key", "openai"]);
+
+        assert!(ProviderImport::parse(r#"{ "backend": { "url": "http://x" } }"#).is_err());
+        let bad_url = r#"{ "providers": [{ "id": "x", "base_url": "ftp://x" }] }"#;
+        let import = ProviderImport::parse(bad_url).unwrap();
+        assert!(import.apply(&mut Config::default()).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! The last health checks, in memory only
+//!
+//! A fixed-size ring of results behind the reliability strip in the window.
+//! Unlike [`HealthHistory`](crate::history::HealthHistory) nothing is written
+//! to disk, so it starts empty with every launch.
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckResult {
+    pub at: SystemTime,
+    /// `None` when the backend wasn't reachable
+    pub latency_ms: Option<u64>,
+}
+
+impl CheckResult {
+    pub fn is_up(&self) -> bool {
+        self.latency_ms.is_some()
+    }
+}
+
+/// How many checks in a window reached the backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uptime {
+    pub up: usize,
+    pub total: usize,
+}
+
+impl Uptime {
+    pub fn percent(&self) -> f64 {
+        self.up as f64 / self.total as f64 * 100.0
+    }
+}
+
+#[derive(Debug)]
+pub struct RecentChecks {
+    results: VecDeque<CheckResult>,
+    capacity: usize,
+}
+
+impl RecentChecks {
+    /// Keeps the last `capacity` results; 0 keeps none
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            results: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn record(&mut self, at: SystemTime, latency_ms: Option<u64>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.results.len() == self.capacity {
+            self.results.pop_front();
+        }
+        self.results.push_back(CheckResult { at, latency_ms });
+    }
+
+    /// Oldest first
+    pub fn results(&self) -> Vec<CheckResult> {
+        self.results.iter().copied().collect()
+    }
+
+    /// Checks in the `window` before `now`; `None` if there were none
+    pub fn uptime(&self, window: Duration, now: SystemTime) -> Option<Uptime> {
+        let since = now.checked_sub(window).unwrap_or(SystemTime::UNIX_EPOCH);
+        let (up, total) = self
+            .results
+            .iter()
+            .filter(|result| result.at >= since)
+            .fold((0, 0), |(up, total), result| (up + result.is_up() as usize, total + 1));
+        (total > 0).then_some(Uptime { up, total })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_drops_oldest_and_uptime_covers_window() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let mut checks = RecentChecks::new(3);
+        assert_eq!(checks.uptime(Duration::from_secs(60), start), None);
+
+        for (secs, latency_ms) in [(0, Some(10)), (30, None), (60, Some(12)), (90, Some(11))] {
+            checks.record(start + Duration::from_secs(secs), latency_ms);
+        }
+        let kept: Vec<_> = checks.results().iter().map(|r| r.latency_ms).collect();
+        assert_eq!(kept, [None, Some(12), Some(11)]);
+
+        let now = start + Duration::from_secs(90);
+        let all = checks.uptime(Duration::from_secs(3600), now).unwrap();
+        assert_eq!(all, Uptime { up: 2, total: 3 });
+        let last_minute = checks.uptime(Duration::from_secs(45), now).unwrap();
+        assert_eq!(last_minute.percent(), 100.0);
+
+        let mut disabled = RecentChecks::new(0);
+        disabled.record(now, Some(1));
+        assert!(disabled.results().is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+//! Whether the backend should be running at the next launch
+//!
+//! Kept in the data directory rather than the config file: it changes with
+//! every Start/Stop, which shouldn't rotate config backups or trigger a
+//! reload. Stopping the backend because the app quits leaves it untouched.
+
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunState {
+    /// The backend was last started, not stopped, on purpose
+    pub backend_running: bool,
+    /// It then kept crashing until the restart policy gave up
+    pub crash_loop: bool,
+}
+
+impl RunState {
+    /// `~/.local/share/vibeproxy/run-state.json`
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("com", "vibeproxy", "VibeProxy")
+            .map(|dirs| dirs.data_dir().join("run-state.json"))
+    }
+
+    /// The saved state; nothing saved or an unreadable file means stopped
+    pub fn load(path: &Path) -> Self {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read {:?}: {}", path, e);
+                return Self::default();
+            }
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable run state in {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// Start the backend at launch: it was running, and not crashing over and over
+    pub fn should_resume(&self) -> bool {
+        self.backend_running && !self.crash_loop
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_loop_is_not_resumed() {
+        let path = std::env::temp_dir().join(format!("vibeproxy-run-state-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert!(!RunState::load(&path).should_resume());
+
+        let running = RunState {
+            backend_running: true,
+            crash_loop: false,
+        };
+        running.save(&path).unwrap();
+        assert!(RunState::load(&path).should_resume());
+
+        RunState {
+            crash_loop: true,
+            ..running
+        }
+        .save(&path)
+        .unwrap();
+        assert!(!RunState::load(&path).should_resume());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+//! Minimal systemd `sd_notify` support
+//!
+//! Lets a `Type=notify` user unit wait until the backend is ready. Does
+//! nothing when not started by systemd.
+
+use std::os::unix::net::UnixDatagram;
+use tracing::{info, warn};
+
+/// Send `READY=1` to `$NOTIFY_SOCKET`, if set
+pub fn ready() {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+
+    match send(&path.to_string_lossy(), "READY=1") {
+        Ok(()) => info!("Notified systemd that the backend is ready"),
+        Err(e) => warn!("Failed to notify systemd: {}", e),
+    }
+}
+
+fn send(path: &str, message: &str) -> std::io::Result<()> {
+    let socket = UnixDatagram::unbound()?;
+    // A leading '@' names a socket in the abstract namespace
+    if let Some(name) = path.strip_prefix('@') {
+        use std::os::linux::net::SocketAddrExt;
+        let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+        socket.send_to_addr(message.as_bytes(), &addr)?;
+    } else {
+        socket.send_to(message.as_bytes(), path)?;
+    }
+    Ok(())
+}
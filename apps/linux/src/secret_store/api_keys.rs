@@ -0,0 +1,281 @@
+//! Provider API keys: handing them to the backend, importing them, finding unused ones
+
+use super::{ImportSummary, SecretStore, METRICS_TOKEN};
+use crate::config::Config;
+use crate::keyring::{qualified_name, KeyringError, DEFAULT_NAMESPACE};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+/// API keys of the providers the active profile uses, from its namespace
+///
+/// See [`Config::active_provider_ids`]; without an active profile these are
+/// the enabled providers' keys from the default namespace.
+pub async fn active_provider_keys(
+    store: &dyn SecretStore,
+    config: &Config,
+) -> Result<HashMap<String, String>> {
+    provider_keys(store, config.active_profile.as_deref(), &config.active_provider_ids()).await
+}
+
+/// API keys of `provider_ids`, keyed by the environment variable the backend reads
+///
+/// Each provider's key is looked up under its id in `namespace` (a profile
+/// name), then in the default namespace for keys saved without one, so a
+/// key stored where it is looked for first costs one lookup. Providers
+/// without a key are left out. A failed lookup is logged and skipped so one
+/// unreadable item doesn't keep the backend from starting, but a locked
+/// keyring is returned as an error since no key could be read.
+pub async fn provider_keys(
+    store: &dyn SecretStore,
+    namespace: Option<&str>,
+    provider_ids: &[String],
+) -> Result<HashMap<String, String>> {
+    let namespaces: Vec<Option<&str>> = match namespace {
+        Some(namespace) if namespace != DEFAULT_NAMESPACE => vec![Some(namespace), None],
+        _ => vec![None],
+    };
+
+    let mut keys = HashMap::new();
+    for id in provider_ids {
+        for namespace in &namespaces {
+            match store.retrieve(*namespace, id).await {
+                Ok(Some(value)) => {
+                    keys.insert(provider_key_env(id), value);
+                    break;
+                }
+                Ok(None) => {}
+                Err(e) if KeyringError::is_locked(&e) => return Err(e),
+                Err(e) => {
+                    warn!("Failed to read the API key of provider '{}': {:#}", id, e);
+                    break;
+                }
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// `OPENAI_API_KEY` for `openai`, the variable the backend looks a provider's key up in
+pub fn provider_key_env(provider_id: &str) -> String {
+    let name: String = provider_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("{}_API_KEY", name)
+}
+
+/// Stored `(namespace, key)` pairs nothing in the config uses any more
+///
+/// A key is live if it is named after one of `provider_ids`, in any
+/// namespace, is one of the `stored_secrets` moved out of the config file, or
+/// is the [`METRICS_TOKEN`]. Everything else, typically the key of a removed
+/// provider, is an orphan.
+pub fn orphaned_keys(
+    provider_ids: &[String],
+    stored_secrets: &[String],
+    stored: &[(String, String)],
+) -> Vec<(String, String)> {
+    let providers: HashSet<&str> = provider_ids.iter().map(String::as_str).collect();
+    stored
+        .iter()
+        .filter(|(namespace, key)| {
+            let app_secret = namespace == DEFAULT_NAMESPACE
+                && (stored_secrets.contains(key) || key == METRICS_TOKEN);
+            !providers.contains(key.as_str()) && !app_secret
+        })
+        .cloned()
+        .collect()
+}
+
+/// `(environment variable, provider id)` pairs an API key import looks at
+///
+/// `keyring.import_env_vars` when set, otherwise `<PROVIDER>_API_KEY` for
+/// every configured provider. A listed variable belongs to the provider whose
+/// key it would be passed as, or else to its lowercased name without `_API_KEY`.
+pub fn env_key_sources(config: &Config) -> Vec<(String, String)> {
+    if config.keyring.import_env_vars.is_empty() {
+        return config
+            .providers
+            .iter()
+            .map(|provider| (provider_key_env(&provider.id), provider.id.clone()))
+            .collect();
+    }
+
+    config
+        .keyring
+        .import_env_vars
+        .iter()
+        .map(|var| {
+            let provider = config
+                .providers
+                .iter()
+                .find(|provider| provider_key_env(&provider.id) == *var)
+                .map(|provider| provider.id.clone())
+                .unwrap_or_else(|| {
+                    var.strip_suffix("_API_KEY").unwrap_or(var).to_ascii_lowercase()
+                });
+            (var.clone(), provider)
+        })
+        .collect()
+}
+
+/// Store the API keys found in the environment under their provider ids
+///
+/// `lookup` reads a variable, normally [`std::env::var`]; unset and empty
+/// ones are ignored. Providers that already have a key are skipped unless
+/// `overwrite` is set.
+pub async fn import_env_keys(
+    store: &dyn SecretStore,
+    sources: &[(String, String)],
+    lookup: impl Fn(&str) -> Option<String>,
+    overwrite: bool,
+) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    for (var, provider) in sources {
+        let value = match lookup(var.as_str()) {
+            Some(value) if !value.is_empty() => Zeroizing::new(value),
+            _ => continue,
+        };
+        let name = qualified_name(DEFAULT_NAMESPACE, provider);
+        if !overwrite && store.retrieve(None, provider).await?.is_some() {
+            summary.skipped.push(name);
+            continue;
+        }
+        store.store(None, provider, &value).await?;
+        summary.imported.push(name);
+    }
+
+    info!(
+        "Imported {} API keys from the environment, skipped {} existing",
+        summary.imported.len(),
+        summary.skipped.len()
+    );
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Provider;
+    use crate::file_vault::FileVault;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts lookups, for checking which secrets are asked for
+    ///
+    /// Has every key, all in `namespace` (`None` for the default one).
+    #[derive(Default)]
+    struct CountingStore {
+        retrieved: AtomicUsize,
+        namespace: Option<String>,
+    }
+
+    #[async_trait]
+    impl SecretStore for CountingStore {
+        async fn store(&self, _: Option<&str>, _: &str, _: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn retrieve(&self, namespace: Option<&str>, key: &str) -> Result<Option<String>> {
+            self.retrieved.fetch_add(1, Ordering::SeqCst);
+            Ok((namespace == self.namespace.as_deref()).then(|| format!("sk-{}", key)))
+        }
+
+        async fn delete(&self, _: Option<&str>, _: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn list_keys(&self) -> Result<Vec<(String, String)>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_keys_only_asks_for_listed_providers() {
+        let store = CountingStore::default();
+        let keys = provider_keys(&store, None, &["open-router".to_string()]).await.unwrap();
+        assert_eq!(store.retrieved.load(Ordering::SeqCst), 1);
+        assert_eq!(keys["OPEN_ROUTER_API_KEY"], "sk-open-router");
+
+        provider_keys(&store, None, &[]).await.unwrap();
+        assert_eq!(store.retrieved.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_profile_with_one_provider_reads_one_key() {
+        let mut config = Config::default();
+        config.providers = ["openai", "anthropic", "groq"].map(Provider::new).to_vec();
+        config.add_profile("work", config.core.backend.clone()).unwrap();
+        config.profiles.get_mut("work").unwrap().providers = vec!["openai".to_string()];
+        config.select_profile("work").unwrap();
+
+        let store = CountingStore {
+            namespace: Some("work".to_string()),
+            ..Default::default()
+        };
+        let keys = active_provider_keys(&store, &config).await.unwrap();
+        assert_eq!(store.retrieved.load(Ordering::SeqCst), 1);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys["OPENAI_API_KEY"], "sk-openai");
+
+        // Keys saved before profiles were used are still found
+        let store = CountingStore::default();
+        let keys = active_provider_keys(&store, &config).await.unwrap();
+        assert_eq!(store.retrieved.load(Ordering::SeqCst), 2);
+        assert_eq!(keys["OPENAI_API_KEY"], "sk-openai");
+    }
+
+    #[tokio::test]
+    async fn test_import_env_keys_skips_existing() {
+        let path = std::env::temp_dir()
+            .join(format!("vibeproxy-env-import-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let vault = FileVault::open(path.clone(), "env").unwrap();
+        vault.store(None, "anthropic", "sk-stored").unwrap();
+
+        let mut config = Config::default();
+        config.keyring.import_env_vars = vec![
+            "OPENAI_API_KEY".to_string(),
+            "ANTHROPIC_API_KEY".to_string(),
+            "GROQ_API_KEY".to_string(),
+        ];
+        let sources = env_key_sources(&config);
+        let env = HashMap::from([
+            ("OPENAI_API_KEY", "sk-openai"),
+            ("ANTHROPIC_API_KEY", "sk-env"),
+            ("GROQ_API_KEY", ""),
+        ]);
+        let lookup = |var: &str| env.get(var).map(|value| value.to_string());
+
+        let summary = import_env_keys(&vault, &sources, lookup, false).await.unwrap();
+        assert_eq!(summary.imported, ["openai"]);
+        assert_eq!(summary.skipped, ["anthropic"]);
+        assert_eq!(vault.retrieve(None, "anthropic").as_deref(), Some("sk-stored"));
+        assert_eq!(vault.retrieve(None, "groq"), None);
+
+        import_env_keys(&vault, &sources, lookup, true).await.unwrap();
+        assert_eq!(vault.retrieve(None, "anthropic").as_deref(), Some("sk-env"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_orphaned_keys_are_unused_ones() {
+        let stored = [
+            (DEFAULT_NAMESPACE.to_string(), "openai".to_string()),
+            ("work".to_string(), "openai".to_string()),
+            (DEFAULT_NAMESPACE.to_string(), "backend.api_key".to_string()),
+            (DEFAULT_NAMESPACE.to_string(), "mistral".to_string()),
+            ("work".to_string(), "backend.api_key".to_string()),
+            (DEFAULT_NAMESPACE.to_string(), METRICS_TOKEN.to_string()),
+        ];
+        let orphans = orphaned_keys(
+            &["openai".to_string()],
+            &["backend.api_key".to_string()],
+            &stored,
+        );
+        assert_eq!(orphans, [stored[3].clone(), stored[4].clone()]);
+    }
+}
@@ -0,0 +1,242 @@
+//! Where secrets live: the system keyring or the encrypted file vault
+
+mod api_keys;
+
+pub use api_keys::{
+    active_provider_keys, env_key_sources, import_env_keys, orphaned_keys, provider_key_env,
+    provider_keys,
+};
+
+use crate::config::{KeyringSettings, SecretBackend};
+use crate::file_vault::{self, FileVault};
+use crate::keyring::{qualified_name, Keyring, KeyringError};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+/// Keyring key of the token the metrics endpoint asks for with `metrics.require_token`
+pub const METRICS_TOKEN: &str = "metrics.token";
+
+#[async_trait]
+pub trait SecretStore: Send + Sync {
+    /// Store or replace the secret under `key`; `None` is the default namespace
+    async fn store(&self, namespace: Option<&str>, key: &str, value: &str) -> Result<()>;
+
+    async fn retrieve(&self, namespace: Option<&str>, key: &str) -> Result<Option<String>>;
+
+    /// Retrieve the `keys` that exist, in one lookup where the store allows it
+    async fn retrieve_many(
+        &self,
+        namespace: Option<&str>,
+        keys: &[&str],
+    ) -> Result<HashMap<String, String>> {
+        let mut secrets = HashMap::new();
+        for key in keys {
+            if let Some(value) = self.retrieve(namespace, key).await? {
+                secrets.insert(key.to_string(), value);
+            }
+        }
+        Ok(secrets)
+    }
+
+    /// Remove `key`; a missing key is not an error
+    async fn delete(&self, namespace: Option<&str>, key: &str) -> Result<()>;
+
+    /// All stored keys as `(namespace, key)` pairs
+    async fn list_keys(&self) -> Result<Vec<(String, String)>>;
+
+    /// Every stored secret, encrypted under `passphrase` for moving to another machine
+    async fn export_encrypted(&self, passphrase: &str) -> Result<Vec<u8>> {
+        let mut by_namespace: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (namespace, key) in self.list_keys().await? {
+            by_namespace.entry(namespace).or_default().push(key);
+        }
+
+        let mut bundle = Vec::new();
+        for (namespace, keys) in &by_namespace {
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            for (key, value) in self.retrieve_many(Some(namespace), &keys).await? {
+                bundle.push(ExportedSecret {
+                    namespace: namespace.clone(),
+                    key,
+                    value,
+                });
+            }
+        }
+
+        let plaintext =
+            Zeroizing::new(serde_json::to_vec(&bundle).context("Failed to serialize secrets")?);
+        let sealed = file_vault::seal(passphrase, &plaintext)?;
+        info!("Exported {} secrets", bundle.len());
+        Ok(sealed)
+    }
+
+    /// Restore secrets written by [`export_encrypted`](Self::export_encrypted)
+    ///
+    /// Keys that already exist are replaced with `overwrite` and skipped otherwise.
+    async fn import_encrypted(
+        &self,
+        bundle: &[u8],
+        passphrase: &str,
+        overwrite: bool,
+    ) -> Result<ImportSummary> {
+        let plaintext = file_vault::unseal(passphrase, bundle)?;
+        let secrets: Vec<ExportedSecret> =
+            serde_json::from_slice(&plaintext).context("Invalid secrets bundle")?;
+        let existing: HashSet<(String, String)> = self.list_keys().await?.into_iter().collect();
+
+        let mut summary = ImportSummary::default();
+        for secret in secrets {
+            let name = qualified_name(&secret.namespace, &secret.key);
+            let id = (secret.namespace, secret.key);
+            if !overwrite && existing.contains(&id) {
+                summary.skipped.push(name);
+                continue;
+            }
+            self.store(Some(&id.0), &id.1, &secret.value).await?;
+            summary.imported.push(name);
+        }
+
+        info!(
+            "Imported {} secrets, skipped {} existing",
+            summary.imported.len(),
+            summary.skipped.len()
+        );
+        Ok(summary)
+    }
+}
+
+/// Result of [`SecretStore::import_encrypted`], as namespace-qualified key names
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: Vec<String>,
+    /// Already present and left unchanged
+    pub skipped: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedSecret {
+    namespace: String,
+    key: String,
+    value: String,
+}
+
+#[async_trait]
+impl SecretStore for Keyring {
+    async fn store(&self, namespace: Option<&str>, key: &str, value: &str) -> Result<()> {
+        Keyring::store(self, namespace, key, value).await
+    }
+
+    async fn retrieve(&self, namespace: Option<&str>, key: &str) -> Result<Option<String>> {
+        Keyring::retrieve(self, namespace, key).await
+    }
+
+    async fn retrieve_many(
+        &self,
+        namespace: Option<&str>,
+        keys: &[&str],
+    ) -> Result<HashMap<String, String>> {
+        Keyring::retrieve_many(self, namespace, keys).await
+    }
+
+    async fn delete(&self, namespace: Option<&str>, key: &str) -> Result<()> {
+        Keyring::delete(self, namespace, key).await
+    }
+
+    async fn list_keys(&self) -> Result<Vec<(String, String)>> {
+        Keyring::list_keys(self).await
+    }
+}
+
+#[async_trait]
+impl SecretStore for FileVault {
+    async fn store(&self, namespace: Option<&str>, key: &str, value: &str) -> Result<()> {
+        FileVault::store(self, namespace, key, value)
+    }
+
+    async fn retrieve(&self, namespace: Option<&str>, key: &str) -> Result<Option<String>> {
+        Ok(FileVault::retrieve(self, namespace, key))
+    }
+
+    async fn delete(&self, namespace: Option<&str>, key: &str) -> Result<()> {
+        FileVault::delete(self, namespace, key)
+    }
+
+    async fn list_keys(&self) -> Result<Vec<(String, String)>> {
+        Ok(FileVault::list_keys(self))
+    }
+}
+
+/// Open the secret store selected by `keyring.backend`
+///
+/// `auto` prefers the secret service and falls back to the vault only when
+/// none can be reached; a timed-out unlock prompt or a refused session is
+/// reported rather than silently switching stores.
+pub async fn open(settings: &KeyringSettings) -> Result<Arc<dyn SecretStore>> {
+    match settings.backend {
+        SecretBackend::SecretService => Ok(Arc::new(
+            Keyring::new(settings.timeout(), settings.cache_ttl()).await?,
+        )),
+        SecretBackend::File => Ok(Arc::new(open_vault(settings)?)),
+        SecretBackend::Auto => match Keyring::new(settings.timeout(), settings.cache_ttl()).await {
+            Ok(keyring) => Ok(Arc::new(keyring)),
+            Err(e) if e.downcast_ref::<KeyringError>().is_some() => Err(e),
+            Err(e) => {
+                warn!("Secret service unavailable, using the encrypted vault: {:#}", e);
+                let vault = open_vault(settings)
+                    .context("No secret service is running and the vault can't be opened")?;
+                Ok(Arc::new(vault))
+            }
+        },
+    }
+}
+
+fn open_vault(settings: &KeyringSettings) -> Result<FileVault> {
+    let path = settings
+        .vault_path
+        .clone()
+        .or_else(FileVault::default_path)
+        .context("Could not determine the vault location")?;
+    FileVault::from_env(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let dir = std::env::temp_dir();
+        let pid = std::process::id();
+        let source_path = dir.join(format!("vibeproxy-export-src-{}", pid));
+        let target_path = dir.join(format!("vibeproxy-export-dst-{}", pid));
+        let _ = std::fs::remove_file(&source_path);
+        let _ = std::fs::remove_file(&target_path);
+
+        let source = FileVault::open(source_path.clone(), "source").unwrap();
+        source.store(None, "openai", "sk-one").unwrap();
+        source.store(Some("work"), "openai", "sk-work").unwrap();
+        let bundle = SecretStore::export_encrypted(&source, "transfer").await.unwrap();
+
+        let target = FileVault::open(target_path.clone(), "target").unwrap();
+        target.store(None, "openai", "sk-local").unwrap();
+        assert!(target.import_encrypted(&bundle, "wrong", false).await.is_err());
+
+        let summary = target.import_encrypted(&bundle, "transfer", false).await.unwrap();
+        assert_eq!(summary.imported, ["work/openai"]);
+        assert_eq!(summary.skipped, ["openai"]);
+        assert_eq!(target.retrieve(None, "openai").as_deref(), Some("sk-local"));
+        assert_eq!(target.retrieve(Some("work"), "openai").as_deref(), Some("sk-work"));
+
+        let summary = target.import_encrypted(&bundle, "transfer", true).await.unwrap();
+        assert_eq!(summary.imported.len(), 2);
+        assert_eq!(target.retrieve(None, "openai").as_deref(), Some("sk-one"));
+
+        std::fs::remove_file(&source_path).unwrap();
+        std::fs::remove_file(&target_path).unwrap();
+    }
+}
@@ -0,0 +1,108 @@
+//! `--self-test`: exercise the backend lifecycle against a fake backend
+//!
+//! Meant for CI and packagers. Spawns `vibeproxy-fake-backend` on a free
+//! port, drives `ServerManager` through start → health → stop against it and
+//! prints pass/fail with timings.
+
+use anyhow::{bail, Context, Result};
+use std::future::Future;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::runtime::Runtime;
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::server_manager::ServerManager;
+
+const FAKE_BACKEND: &str = "vibeproxy-fake-backend";
+const HEALTH_WAIT: Duration = Duration::from_secs(10);
+
+pub fn run() -> Result<()> {
+    let runtime = Runtime::new().context("Failed to create Tokio runtime")?;
+    let work_dir = std::env::temp_dir().join(format!("vibeproxy-self-test-{}", std::process::id()));
+    std::fs::create_dir_all(&work_dir).context("Failed to create self-test directory")?;
+
+    let result = runtime.block_on(exercise(&work_dir, runtime.handle().clone()));
+    let _ = std::fs::remove_dir_all(&work_dir);
+
+    match &result {
+        Ok(()) => println!("self-test: PASS"),
+        Err(e) => println!("self-test: FAIL: {:#}", e),
+    }
+    result
+}
+
+async fn exercise(work_dir: &std::path::Path, runtime: tokio::runtime::Handle) -> Result<()> {
+    let port = free_port()?;
+
+    let mut config = Config::default();
+    config.core.backend.url = "http://127.0.0.1".to_string();
+    config.core.backend.port = port;
+    // Starting and stopping here says nothing about the user's backend
+    config.backend_process.resume_on_launch = false;
+
+    let config_manager = Arc::new(ConfigManager::with_path(work_dir.join("config.json")));
+    config_manager.save(&config)?;
+    let server_manager = ServerManager::new(config_manager, runtime)?;
+
+    let mut backend = step("spawn fake backend", async {
+        Command::new(fake_backend_path()?)
+            .args(["--port", &port.to_string()])
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to spawn fake backend")
+    })
+    .await?;
+
+    let outcome = async {
+        step("health wait", async {
+            let deadline = Instant::now() + HEALTH_WAIT;
+            loop {
+                if server_manager.status().await.map(|s| s.running).unwrap_or(false) {
+                    return Ok(());
+                }
+                if Instant::now() >= deadline {
+                    bail!("backend not healthy after {:?}", HEALTH_WAIT);
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await?;
+
+        step("start", server_manager.start()).await?;
+        step("stop", server_manager.stop()).await
+    }
+    .await;
+
+    let _ = backend.kill().await;
+    outcome
+}
+
+async fn step<T>(name: &str, fut: impl Future<Output = Result<T>>) -> Result<T> {
+    let started = Instant::now();
+    let result = fut.await;
+    let status = if result.is_ok() { "ok" } else { "FAILED" };
+    println!("  {:<20} {:>6} ms  {}", name, started.elapsed().as_millis(), status);
+    result.with_context(|| format!("step '{}' failed", name))
+}
+
+/// The fake backend ships next to the main binary; `VIBEPROXY_FAKE_BACKEND` overrides
+fn fake_backend_path() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os("VIBEPROXY_FAKE_BACKEND") {
+        return Ok(PathBuf::from(path));
+    }
+
+    let exe = std::env::current_exe().context("Failed to locate current executable")?;
+    let path = exe.with_file_name(FAKE_BACKEND);
+    if !path.exists() {
+        bail!("{} not found next to {:?}", FAKE_BACKEND, exe);
+    }
+    Ok(path)
+}
+
+fn free_port() -> Result<u16> {
+    let listener = TcpListener::bind("127.0.0.1:0").context("Failed to find a free port")?;
+    Ok(listener.local_addr()?.port())
+}
@@ -0,0 +1,159 @@
+//! Secrets from the keyring and reconnecting with new credentials
+
+use super::ServerManager;
+use crate::config::{profile_key_secret, Config};
+use crate::keyring::KeyringError;
+use crate::secret_store::{self, provider_key_env, SecretStore};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tracing::{debug, info, warn};
+
+/// Keyring key (and config path) of the backend API key once moved out of the config file
+const BACKEND_API_KEY: &str = "backend.api_key";
+
+impl ServerManager {
+    /// Load the config with secrets that live in the keyring filled back in
+    ///
+    /// The keyring is only opened if the config says a secret was moved there.
+    pub async fn resolved_config(&self) -> Result<Config> {
+        let mut config = self.config_manager.load()?;
+
+        // The active profile's own key first, then the one moved out of the config file
+        let key_in_keyring = config
+            .active_profile
+            .as_deref()
+            .map(profile_key_secret)
+            .into_iter()
+            .chain([BACKEND_API_KEY.to_string()])
+            .find(|key| config.keyring.stored_secrets.contains(key));
+        if let (None, Some(key)) = (&config.core.backend.api_key, key_in_keyring) {
+            if let Some(keyring) = self.keyring(&config).await? {
+                match keyring.retrieve(None, &key).await {
+                    Ok(api_key) => config.core.backend.api_key = api_key,
+                    // Carrying on without the key would only fail as an auth error
+                    Err(e) if KeyringError::is_locked(&e) => return Err(e),
+                    Err(e) => warn!("Failed to read backend API key from keyring: {}", e),
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// API keys of the providers the active profile uses, for the backend we spawn
+    ///
+    /// Only those providers are looked up, in the profile's namespace, and the
+    /// keyring isn't opened at all without any, so unused keys never trigger
+    /// an unlock prompt.
+    pub(super) async fn provider_keys(&self, config: &Config) -> Result<HashMap<String, String>> {
+        if config.active_provider_ids().is_empty() {
+            return Ok(HashMap::new());
+        }
+        match self.keyring(config).await? {
+            Some(keyring) => secret_store::active_provider_keys(keyring.as_ref(), config).await,
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    /// API key of one provider, for calling it directly instead of through the backend
+    pub(super) async fn provider_key(
+        &self,
+        config: &Config,
+        provider_id: &str,
+    ) -> Result<Option<String>> {
+        let Some(keyring) = self.keyring(config).await? else {
+            return Ok(None);
+        };
+        let namespace = config.active_profile.as_deref();
+        let mut keys =
+            secret_store::provider_keys(keyring.as_ref(), namespace, &[provider_id.to_string()])
+                .await?;
+        Ok(keys.remove(&provider_key_env(provider_id)))
+    }
+
+    /// The secret store, connected on first use
+    ///
+    /// An unavailable store is remembered as `None` until credentials are
+    /// reloaded; a locked keyring is returned as an error and not remembered,
+    /// so unlocking it is picked up on the next use.
+    async fn keyring(&self, config: &Config) -> Result<Option<Arc<dyn SecretStore>>> {
+        if self.config_manager.is_safe_mode() {
+            return Ok(None);
+        }
+
+        let keyring = self.keyring.lock().unwrap().clone();
+        let store = keyring
+            .get_or_try_init(|| async {
+                match secret_store::open(&config.keyring).await {
+                    Ok(store) => Ok(Some(store)),
+                    Err(e) if KeyringError::is_locked(&e) => Err(e),
+                    Err(e) => {
+                        warn!("Keyring unavailable: {:#}", e);
+                        Ok(None)
+                    }
+                }
+            })
+            .await?;
+        Ok(store.clone())
+    }
+
+    /// A secret from the default namespace of the keyring, such as the metrics token
+    ///
+    /// `None` if it isn't stored, or there's no keyring (including in safe mode).
+    pub async fn stored_secret(&self, key: &str) -> Result<Option<String>> {
+        let config = self.config_manager.load()?;
+        match self.keyring(&config).await? {
+            Some(keyring) => keyring.retrieve(None, key).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Pick up rotated certificates and keyring secrets without a restart
+    ///
+    /// Drops the pooled backend clients (and their TLS sessions) and the
+    /// keyring connection; both are recreated on next use from the files and
+    /// secrets as they are now.
+    pub fn reload_credentials(&self) {
+        self.clients.lock().unwrap().clear();
+        *self.keyring.lock().unwrap() = Arc::new(OnceCell::new());
+        info!("Credentials will be reloaded on the next backend request");
+    }
+
+    /// React to the config file being edited while the app runs
+    ///
+    /// New backend, proxy or TLS settings [`reconnect`](Self::reconnect)
+    /// with them, then the status is refreshed.
+    pub async fn config_reloaded(&self, previous: &Config, config: &Config) {
+        let connection_changed = config.client.connection_changed(&previous.client);
+        if connection_changed {
+            self.reload_credentials();
+        }
+        if connection_changed
+            || format!("{:?}", config.core.backend) != format!("{:?}", previous.core.backend)
+        {
+            info!("Backend settings changed, reconnecting");
+            if let Err(e) = self.reconnect().await {
+                warn!("Failed to reconnect: {:#}", e);
+            }
+        }
+        // Another backend may serve other models
+        self.models.send_replace(None);
+        self.refresh_status().await;
+    }
+
+    /// Connect to the backend the config names now, replacing pooled clients
+    ///
+    /// Builds (and warms up) the one client health checks reuse, so a new
+    /// host, port or API key takes effect without recreating the manager and
+    /// the next poll doesn't pay for the TLS handshake. Called when the
+    /// config file changes, which includes switching profiles.
+    pub async fn reconnect(&self) -> Result<()> {
+        self.clients.lock().unwrap().clear();
+        let config = self.resolved_config().await?;
+        self.client(&config, &config.core.backend).await;
+        debug!("Connected to {}", config.backend_endpoint());
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+//! Starting and stopping the backend
+
+use super::requests::{is_unavailable, with_deadline};
+use super::supervisor::{save_run_state, Supervisor};
+use super::transition::{begin_transition, Transition};
+use super::ServerManager;
+use crate::config::{parse_http_url, Config};
+use crate::history::LifecycleEvent;
+use crate::process_usage::ResourceUsage;
+use crate::run_state::RunState;
+use anyhow::{bail, Context, Result};
+use nix::errno::Errno;
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, TcpListener};
+use std::time::Duration;
+use tokio::process::Child;
+use tokio::sync::{broadcast, watch};
+use tracing::{debug, error, info, warn};
+use url::Host;
+use vibeproxy_core::BackendConfig;
+
+impl ServerManager {
+    /// Start the backend, or adopt one that is already running, and remember
+    /// to do so again at the next launch
+    ///
+    /// Fails with [`TransitionError`](super::TransitionError) while a start or stop
+    /// is still under way.
+    pub async fn start(&self) -> Result<()> {
+        let _transition = begin_transition(&self.transition, Transition::Starting)?;
+        self.start_backend().await?;
+        self.remember_run_state(RunState {
+            backend_running: true,
+            crash_loop: false,
+        });
+        Ok(())
+    }
+
+    async fn start_backend(&self) -> Result<()> {
+        if *self.is_running.borrow() {
+            warn!("Server is already running");
+            return Ok(());
+        }
+
+        if self.config_manager.is_safe_mode() {
+            bail!("Starting the backend is disabled in safe mode");
+        }
+
+        info!("Starting server");
+
+        // Load configuration
+        let config = self.resolved_config().await?;
+
+        let client = self.client(&config, &config.core.backend).await;
+
+        // Check if server is already running
+        let timeout = config.client.request_timeout();
+        match self.limited(with_deadline(timeout, client.health_check())).await {
+            Ok(status) => {
+                if let Some(auth) = status.auth {
+                    return Err(anyhow::Error::new(auth)
+                        .context("A backend is running but didn't accept VibeProxy's API key"));
+                }
+                if status.healthy {
+                    info!("Backend server is already running");
+                    self.is_running.send_replace(true);
+                    return Ok(());
+                }
+            }
+            Err(e) if is_unavailable(&e) => {
+                info!("Backend server is not available, starting...");
+                let mut backend = config.core.backend.clone();
+                let client = if self.claim_port(&config, &mut backend)? {
+                    self.client(&config, &backend).await
+                } else {
+                    client
+                };
+                let mut settings = config.backend_process.clone();
+                settings.args = settings.args_for_port(backend.port);
+                let provider_keys = self.provider_keys(&config).await?;
+
+                let supervisor = Supervisor {
+                    child: self.child.clone(),
+                    logs: self.backend_logs.clone(),
+                    is_running: self.is_running.clone(),
+                    request_limit: self.request_limit.clone(),
+                    client,
+                    request_timeout: timeout,
+                    settings,
+                    provider_keys,
+                    policy: config.restart_policy.clone(),
+                    restarts: self.restarts.clone(),
+                    run_state: self.run_state.clone(),
+                };
+                supervisor.launch().await?;
+                let task = self.runtime.spawn(supervisor.run());
+                *self.supervisor.lock().unwrap() = Some(task);
+            }
+            Err(e) => {
+                error!("Failed to check server health: {:#}", e);
+                return Err(e);
+            }
+        }
+
+        self.is_running.send_replace(true);
+        self.record_history(|history| history.record_event(LifecycleEvent::Started));
+        info!("Server started successfully");
+
+        Ok(())
+    }
+
+    /// Make sure the backend we're about to spawn can listen on its port
+    ///
+    /// Something holding the port without answering health checks is
+    /// usually a stale backend. With `backend_process.auto_port` a free port
+    /// is picked and saved instead; returns whether `backend` was moved.
+    fn claim_port(&self, config: &Config, backend: &mut BackendConfig) -> Result<bool> {
+        let Some(ip) = local_address(backend) else {
+            // Not ours to spawn on; the launch reports whatever goes wrong
+            return Ok(false);
+        };
+        if !port_in_use(ip, backend.port) {
+            return Ok(false);
+        }
+        if !config.backend_process.auto_port {
+            bail!(
+                "Port {} is already in use by another process that isn't answering as a \
+                 backend. Stop the other instance, or set backend_process.auto_port to \
+                 use a free port.",
+                backend.port
+            );
+        }
+
+        let port = TcpListener::bind((ip, 0))
+            .and_then(|listener| listener.local_addr())
+            .context("Failed to find a free port for the backend")?
+            .port();
+        warn!("Port {} is in use, starting the backend on port {}", backend.port, port);
+        backend.port = port;
+
+        // Saved from the file, not `config`, so resolved secrets stay in the keyring
+        let mut saved = self.config_manager.load()?;
+        saved.core.backend.port = port;
+        self.config_manager.save(&saved)?;
+        Ok(true)
+    }
+
+    /// Stop the backend and leave it stopped at the next launch
+    ///
+    /// Fails with [`TransitionError`](super::TransitionError) while a start or stop
+    /// is still under way.
+    pub async fn stop(&self) -> Result<()> {
+        let _transition = begin_transition(&self.transition, Transition::Stopping)?;
+        self.stop_backend().await?;
+        self.remember_run_state(RunState::default());
+        Ok(())
+    }
+
+    /// Stop the backend because the app is quitting; the next launch resumes it
+    ///
+    /// Waits for a start or stop under way rather than failing, so a backend
+    /// still being spawned isn't left behind. Does nothing if, after that,
+    /// the backend isn't running; call it on every exit.
+    pub async fn stop_for_exit(&self) -> Result<()> {
+        let mut transitions = self.transition.subscribe();
+        let _ = transitions.wait_for(Option::is_none).await;
+        let _transition = begin_transition(&self.transition, Transition::Stopping)?;
+        if !*self.is_running.borrow() {
+            return Ok(());
+        }
+        self.stop_backend().await
+    }
+
+    async fn stop_backend(&self) -> Result<()> {
+        if !*self.is_running.borrow() {
+            warn!("Server is not running");
+            return Ok(());
+        }
+
+        info!("Stopping server");
+
+        // A crashed backend may be waiting out the restart delay with no process
+        // to stop; cancel the restart and make sure no launch is left half done
+        let supervisor = self.supervisor.lock().unwrap().take();
+        let spawned = supervisor.is_some();
+        if let Some(supervisor) = supervisor {
+            supervisor.abort();
+            let _ = supervisor.await;
+        }
+
+        let child = self.child.lock().unwrap().take();
+        match child {
+            Some(mut child) => {
+                let grace = self
+                    .config_manager
+                    .load()
+                    .map(|config| config.backend_process)
+                    .unwrap_or_default()
+                    .stop_grace();
+                terminate(&mut child, grace).await?;
+            }
+            None if spawned => info!("Cancelled the pending restart of the backend"),
+            None => info!("Backend was started externally, leaving it running"),
+        }
+
+        self.is_running.send_replace(false);
+        self.record_history(|history| history.record_event(LifecycleEvent::Stopped));
+        info!("Server stopped successfully");
+
+        Ok(())
+    }
+
+    /// CPU and memory use of the backend process we spawned
+    ///
+    /// `None` for a backend started elsewhere, which has no process here to
+    /// look at. CPU use is since the previous call, so it is `None` the first
+    /// time; call it on each status update.
+    pub fn resource_usage(&self) -> Option<ResourceUsage> {
+        let pid = self.child.lock().unwrap().as_ref()?.id()?;
+        match self.usage_sampler.lock().unwrap().sample(pid) {
+            Ok(usage) => Some(usage),
+            Err(e) => {
+                debug!("Backend resource usage unavailable: {:#}", e);
+                None
+            }
+        }
+    }
+
+    /// Whether the backend was running when the app last quit, and should be again
+    ///
+    /// Always `false` with `backend_process.resume_on_launch` off, or after
+    /// the restart policy gave up on a crashing backend.
+    pub fn should_resume(&self) -> bool {
+        self.run_state
+            .as_deref()
+            .is_some_and(|path| RunState::load(path).should_resume())
+    }
+
+    fn remember_run_state(&self, state: RunState) {
+        save_run_state(self.run_state.as_deref(), state);
+    }
+
+    /// Latest output lines of the backend process we spawned, oldest first
+    pub fn recent_logs(&self) -> Vec<String> {
+        self.backend_logs.lines()
+    }
+
+    /// Backend output lines as they are captured
+    pub fn log_updates(&self) -> broadcast::Receiver<String> {
+        self.backend_logs.subscribe()
+    }
+
+    pub fn clear_logs(&self) {
+        self.backend_logs.clear();
+    }
+
+    pub async fn is_running(&self) -> bool {
+        *self.is_running.borrow()
+    }
+
+    /// Whether the backend is started, for views that enable controls from it
+    ///
+    /// Changes as soon as a start or stop completes or the supervisor gives
+    /// up, without waiting for a health check, so every subscriber sees the
+    /// same value.
+    pub fn running_updates(&self) -> watch::Receiver<bool> {
+        self.is_running.subscribe()
+    }
+
+    /// The start or stop under way, `None` once it has finished or failed
+    ///
+    /// Views disable their Start and Stop controls while it is set; a
+    /// conflicting request would only fail with
+    /// [`TransitionError`](super::TransitionError).
+    pub fn transition_updates(&self) -> watch::Receiver<Option<Transition>> {
+        self.transition.subscribe()
+    }
+}
+
+/// Loopback address the backend would listen on, if `backend` is on this machine
+fn local_address(backend: &BackendConfig) -> Option<IpAddr> {
+    match parse_http_url(&backend.url).ok()?.host()? {
+        Host::Domain(domain) if domain.eq_ignore_ascii_case("localhost") => {
+            Some(Ipv4Addr::LOCALHOST.into())
+        }
+        Host::Ipv4(ip) if ip.is_loopback() || ip.is_unspecified() => Some(ip.into()),
+        Host::Ipv6(ip) if ip.is_loopback() || ip.is_unspecified() => Some(ip.into()),
+        _ => None,
+    }
+}
+
+/// Whether another socket is bound to `port` on `ip`
+fn port_in_use(ip: IpAddr, port: u16) -> bool {
+    matches!(TcpListener::bind((ip, port)), Err(e) if e.kind() == io::ErrorKind::AddrInUse)
+}
+
+/// SIGTERM, then SIGKILL if the process is still around after `grace`
+async fn terminate(child: &mut Child, grace: Duration) -> Result<()> {
+    let Some(pid) = child.id() else {
+        // Already reaped
+        return Ok(());
+    };
+
+    match signal::kill(Pid::from_raw(pid as i32), Signal::SIGTERM) {
+        Ok(()) | Err(Errno::ESRCH) => {}
+        Err(e) => return Err(e).context("Failed to send SIGTERM to backend"),
+    }
+
+    match tokio::time::timeout(grace, child.wait()).await {
+        Ok(exit) => {
+            let exit = exit.context("Failed to wait for backend to exit")?;
+            info!("Backend exited ({})", exit);
+            Ok(())
+        }
+        Err(_) => {
+            warn!("Backend still running {:?} after SIGTERM, killing it", grace);
+            child.kill().await.context("Failed to kill backend process")
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Server management (start/stop/status)
+
+mod credentials;
+mod lifecycle;
+mod providers;
+mod requests;
+mod status;
+mod supervisor;
+mod transition;
+
+pub use requests::TimeoutError;
+pub use status::ServerStatus;
+pub use transition::{Transition, TransitionError};
+
+use crate::backend_api::DiscoveredModel;
+use crate::backend_connection::BackendConnection;
+use crate::backend_logs::BackendLogs;
+use crate::config::Config;
+use crate::config_manager::ConfigManager;
+use crate::failover::EndpointSelector;
+use crate::history::{HealthHistory, HistoryRecord};
+use crate::process_usage::UsageSampler;
+use crate::recent_checks::RecentChecks;
+use crate::run_state::RunState;
+use crate::secret_store::SecretStore;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::process::Child;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+use tokio::sync::{watch, Notify, OnceCell, Semaphore};
+use tracing::{info, warn};
+
+pub struct ServerManager {
+    config_manager: Arc<ConfigManager>,
+    runtime: Handle,
+    /// Clients kept across health checks so their connection pools stay warm
+    clients: Mutex<HashMap<String, Arc<BackendConnection>>>,
+    /// Whether we started (or adopted) the backend; see [`running_updates`](Self::running_updates)
+    is_running: Arc<watch::Sender<bool>>,
+    /// Backend process we spawned; `None` when using an externally started backend
+    /// or while a crashed one waits to be restarted
+    child: Arc<Mutex<Option<Child>>>,
+    /// Watches the backend we spawned; `None` when using an externally started one
+    supervisor: Mutex<Option<JoinHandle<()>>>,
+    /// Recent stdout/stderr of the spawned backend, kept across restarts
+    backend_logs: Arc<BackendLogs>,
+    request_limit: Arc<Semaphore>,
+    endpoint_selector: Mutex<EndpointSelector>,
+    /// Replaced on credential reload so the next use reconnects
+    keyring: Mutex<Arc<OnceCell<Option<Arc<dyn SecretStore>>>>>,
+    ready_notified: AtomicBool,
+    refresh_in_flight: AtomicBool,
+    /// Shared with status watchers, which also use it to notice the manager was dropped
+    refresh_requested: Arc<Notify>,
+    /// Latest status, see [`watch_status`](Self::watch_status)
+    status_updates: watch::Sender<Option<ServerStatus>>,
+    watching: AtomicBool,
+    /// Whether the system is suspended, see [`set_suspended`](Self::set_suspended)
+    suspended: watch::Sender<bool>,
+    /// `None` when history is disabled or its file couldn't be opened
+    history: Option<Mutex<HealthHistory>>,
+    /// Last model listing, see [`models`](Self::models); `None` until listed
+    models: watch::Sender<Option<Option<Vec<DiscoveredModel>>>>,
+    /// CPU time of the spawned backend at the last [`resource_usage`](Self::resource_usage)
+    usage_sampler: Mutex<UsageSampler>,
+    /// Where the intended run state is kept, `None` with `resume_on_launch` off
+    run_state: Option<PathBuf>,
+    /// Crash restarts of the spawned backend since the app started
+    restarts: Arc<AtomicU64>,
+    /// Status checks that found the backend down since the app started
+    failed_checks: AtomicU64,
+    /// The last health checks, see [`recent_checks`](Self::recent_checks)
+    recent_checks: Mutex<RecentChecks>,
+    /// Start or stop under way, see [`transition_updates`](Self::transition_updates)
+    transition: watch::Sender<Option<Transition>>,
+}
+
+impl ServerManager {
+    pub fn new(config_manager: Arc<ConfigManager>, runtime: Handle) -> Result<Self> {
+        let config = config_manager.load().unwrap_or_default();
+        let max_requests = config.client.max_concurrent_requests.max(1);
+
+        let retention = config.history.retention();
+        let history = config
+            .history
+            .enabled
+            .then(HealthHistory::default_path)
+            .flatten()
+            .and_then(|path| match HealthHistory::load(&path, retention) {
+                Ok(history) => Some(history),
+                Err(e) => {
+                    warn!("Health history disabled: {:#}", e);
+                    None
+                }
+            });
+
+        // Pick up where the last run left off instead of starting with no latency data
+        let mut endpoint_selector = EndpointSelector::default();
+        for record in history.iter().flat_map(HealthHistory::records) {
+            if let HistoryRecord::Sample {
+                endpoint: Some(endpoint),
+                latency_ms,
+                ..
+            } = record
+            {
+                endpoint_selector.record(endpoint, *latency_ms);
+            }
+        }
+
+        Ok(Self {
+            config_manager,
+            runtime,
+            clients: Mutex::new(HashMap::new()),
+            is_running: Arc::new(watch::channel(false).0),
+            child: Arc::new(Mutex::new(None)),
+            supervisor: Mutex::new(None),
+            backend_logs: Arc::new(BackendLogs::default()),
+            request_limit: Arc::new(Semaphore::new(max_requests)),
+            endpoint_selector: Mutex::new(endpoint_selector),
+            keyring: Mutex::new(Arc::new(OnceCell::new())),
+            ready_notified: AtomicBool::new(false),
+            refresh_in_flight: AtomicBool::new(false),
+            refresh_requested: Arc::new(Notify::new()),
+            status_updates: watch::channel(None).0,
+            watching: AtomicBool::new(false),
+            suspended: watch::channel(false).0,
+            history: history.map(Mutex::new),
+            models: watch::channel(None).0,
+            usage_sampler: Mutex::default(),
+            run_state: config
+                .backend_process
+                .resume_on_launch
+                .then(RunState::default_path)
+                .flatten(),
+            restarts: Arc::new(AtomicU64::new(0)),
+            failed_checks: AtomicU64::new(0),
+            recent_checks: Mutex::new(RecentChecks::new(config.health.recent_checks)),
+            transition: watch::channel(None).0,
+        })
+    }
+
+    /// Retained latency samples and lifecycle events, oldest first
+    pub fn history(&self) -> Vec<HistoryRecord> {
+        self.history
+            .as_ref()
+            .map(|history| history.lock().unwrap().records().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn record_history(&self, record: impl FnOnce(&mut HealthHistory) -> Result<()>) {
+        if let Some(history) = &self.history {
+            if let Err(e) = record(&mut history.lock().unwrap()) {
+                warn!("Failed to record health history: {:#}", e);
+            }
+        }
+    }
+
+    /// Switch to the backend profile `name`, reconnecting to it
+    ///
+    /// Like [`apply_config`](Self::apply_config), hands back the previous
+    /// config if the new backend doesn't answer.
+    pub async fn select_profile(&self, name: &str) -> Result<ApplyOutcome> {
+        let mut config = self.config_manager.load()?;
+        config.select_profile(name)?;
+        info!("Switching to backend profile '{}'", name);
+
+        // Don't keep talking to the old backend on pooled connections
+        self.clients.lock().unwrap().clear();
+        self.models.send_replace(None);
+        self.apply_config(config).await
+    }
+
+    /// Save `config` and check the backend still answers with it
+    ///
+    /// If it doesn't within the configured timeout, the previous config is
+    /// handed back so the caller can offer to revert via `ConfigManager::save`.
+    pub async fn apply_config(&self, mut config: Config) -> Result<ApplyOutcome> {
+        let previous = self.config_manager.load()?;
+        let timeout = Duration::from_secs(config.health.apply_timeout_secs);
+
+        let endpoint = config.backend_endpoint();
+        config.recent_endpoints.record(&endpoint);
+
+        if config.client.connection_changed(&previous.client) {
+            self.reload_credentials();
+        }
+
+        self.config_manager.save(&config)?;
+        info!("Applied new configuration, verifying backend");
+
+        let error = match tokio::time::timeout(timeout, self.status()).await {
+            Ok(Ok(status)) if status.running => return Ok(ApplyOutcome::Applied),
+            Ok(Ok(status)) => status
+                .message
+                .unwrap_or_else(|| "Backend is not healthy".to_string()),
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!("Backend did not respond within {:?}", timeout),
+        };
+
+        warn!("Backend unreachable after applying config: {}", error);
+        Ok(ApplyOutcome::Unreachable { previous, error })
+    }
+}
+
+impl Drop for ServerManager {
+    /// Don't leave an orphaned backend behind if the app goes down without `stop()`
+    fn drop(&mut self) {
+        // Status watchers only hold a weak reference; wake them so they exit
+        self.refresh_requested.notify_waiters();
+
+        // Cleared first so the supervisor doesn't take this for a crash and restart it
+        self.is_running.send_replace(false);
+        let supervisor = match self.supervisor.get_mut() {
+            Ok(supervisor) => supervisor.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        if let Some(supervisor) = supervisor {
+            supervisor.abort();
+        }
+        let child = match self.child.lock() {
+            Ok(mut child) => child.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        if let Some(mut child) = child {
+            if let Err(e) = child.start_kill() {
+                warn!("Failed to kill backend process: {}", e);
+            }
+        }
+    }
+}
+
+/// Result of [`ServerManager::apply_config`]
+#[derive(Debug, Clone)]
+pub enum ApplyOutcome {
+    Applied,
+    Unreachable { previous: Config, error: String },
+}
@@ -0,0 +1,183 @@
+//! Provider routing and the models the backend serves
+
+use super::ServerManager;
+use crate::backend_api::{BackendApi, DiscoveredModel, ModelDiscovery, Routing, UsageMetrics};
+use crate::config::{Config, Provider, RoutingRule};
+use anyhow::{Context, Result};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+impl ServerManager {
+    /// Models the backend serves, or `None` if it can't list them
+    ///
+    /// The first answer is cached; `refresh` asks the backend again.
+    pub async fn models(&self, refresh: bool) -> Result<Option<Vec<DiscoveredModel>>> {
+        if !refresh {
+            if let Some(models) = self.models.borrow().clone() {
+                return Ok(models);
+            }
+        }
+
+        let config = self.resolved_config().await?;
+        let api = BackendApi::new(&config)?;
+        let models = self
+            .limited(api.list_models())
+            .await?
+            .map(|discovery| discovery.models);
+        self.models.send_replace(Some(models.clone()));
+        Ok(models)
+    }
+
+    /// Every model the backend serves, see [`BackendApi::discover_models`]
+    ///
+    /// Unlike [`models`](Self::models) this always asks the backend and
+    /// fails if it has no models endpoint.
+    pub async fn discover_models(&self) -> Result<ModelDiscovery> {
+        let config = self.resolved_config().await?;
+        let api = BackendApi::new(&config)?;
+        self.limited(api.discover_models()).await
+    }
+
+    /// Each new model listing, and `None` when a backend change clears it
+    pub fn model_updates(&self) -> watch::Receiver<Option<Option<Vec<DiscoveredModel>>>> {
+        self.models.subscribe()
+    }
+
+    /// Token usage and spend per provider, see [`BackendApi::metrics`]
+    pub async fn usage_metrics(&self) -> Result<Option<UsageMetrics>> {
+        let config = self.resolved_config().await?;
+        let api = BackendApi::new(&config)?;
+        self.limited(api.metrics()).await
+    }
+
+    /// Stream a short chat completion, see [`BackendApi::test_completion`]
+    ///
+    /// A model whose provider has a base URL override goes straight there
+    /// with the provider's own key, so a provider staged against a mock can
+    /// be tried on its own.
+    pub async fn test_completion(
+        &self,
+        model: &str,
+        prompt: &str,
+        on_text: impl FnMut(&str),
+    ) -> Result<()> {
+        let config = self.resolved_config().await?;
+        let (api, model) = match config.provider_for_model(model) {
+            Some(Provider { id, base_url_override: Some(base_url), .. }) => {
+                info!("Sending the test completion for '{}' to {}", model, base_url);
+                let api_key = self.provider_key(&config, id).await?;
+                let api = BackendApi::with_base_url(&config, base_url, api_key.as_deref())?;
+                // The provider itself doesn't know the backend's `provider/` prefix
+                let model = model.strip_prefix(&format!("{}/", id)).unwrap_or(model);
+                (api, model)
+            }
+            _ => (BackendApi::new(&config)?, model),
+        };
+        self.limited(api.test_completion(model, prompt, on_text)).await
+    }
+
+    /// Enable or disable a provider, persist it and push the new routing to the backend
+    ///
+    /// The config is saved even if the backend can't be reached; startup
+    /// reconciliation catches up later.
+    pub async fn set_provider_enabled(&self, provider_id: &str, enabled: bool) -> Result<()> {
+        self.update_provider(provider_id, |provider| provider.enabled = enabled)?;
+        info!(
+            "Provider '{}' {}",
+            provider_id,
+            if enabled { "enabled" } else { "disabled" }
+        );
+        self.push_routing().await
+    }
+
+    /// Send a provider's traffic to `base_url` (`None` to undo), persist it and push it
+    ///
+    /// Fails only if the URL is invalid or can't be saved, like
+    /// [`set_routing_rules`](Self::set_routing_rules).
+    pub async fn set_provider_base_url(
+        &self,
+        provider_id: &str,
+        base_url: Option<String>,
+    ) -> Result<()> {
+        self.update_provider(provider_id, |provider| {
+            provider.base_url_override = base_url.clone()
+        })?;
+        info!("Provider '{}' base URL override set to {:?}", provider_id, base_url);
+
+        if let Err(e) = self.push_routing().await {
+            warn!("Base URL saved but not pushed to the backend: {:#}", e);
+        }
+        Ok(())
+    }
+
+    /// Replace the routing rules, persist them and push them to the backend
+    ///
+    /// Fails only if the rules are invalid (e.g. name an unknown provider)
+    /// or can't be saved. A backend that can't be reached is logged; startup
+    /// reconciliation catches up later.
+    pub async fn set_routing_rules(&self, rules: Vec<RoutingRule>) -> Result<()> {
+        let mut config = self.config_manager.load()?;
+        config.routing_rules = rules;
+        config.validate()?;
+        self.config_manager.save(&config)?;
+        info!("Saved {} routing rules", config.routing_rules.len());
+
+        if let Err(e) = self.push_routing().await {
+            warn!("Routing rules saved but not pushed to the backend: {:#}", e);
+        }
+        Ok(())
+    }
+
+    fn update_provider(
+        &self,
+        provider_id: &str,
+        update: impl FnOnce(&mut Provider),
+    ) -> Result<()> {
+        let mut config = self.config_manager.load()?;
+        let provider = config
+            .providers
+            .iter_mut()
+            .find(|p| p.id == provider_id)
+            .with_context(|| format!("Unknown provider '{}'", provider_id))?;
+        update(provider);
+        provider.validate()?;
+        self.config_manager.save(&config)
+    }
+
+    async fn push_routing(&self) -> Result<()> {
+        let config = self.resolved_config().await?;
+        let routing = desired_routing(&config);
+        let api = BackendApi::new(&config)?;
+        self.limited(api.update_routing(&routing)).await
+    }
+
+    /// Make the backend's routing match the providers, base URLs and rules in the config
+    pub async fn reconcile_routing(&self) -> Result<()> {
+        let config = self.resolved_config().await?;
+        if config.providers.is_empty() {
+            return Ok(());
+        }
+
+        let api = BackendApi::new(&config)?;
+        let wanted = desired_routing(&config);
+        let mut current = self.limited(api.routing()).await?;
+        current.enabled_providers.sort();
+        if wanted == current {
+            return Ok(());
+        }
+
+        info!("Backend routing differs from config, updating to {:?}", wanted);
+        self.limited(api.update_routing(&wanted)).await
+    }
+}
+
+/// Routing the backend should have for `config`, with providers sorted
+fn desired_routing(config: &Config) -> Routing {
+    let mut enabled_providers = config.enabled_provider_ids();
+    enabled_providers.sort();
+    Routing {
+        enabled_providers,
+        base_urls: config.provider_base_urls(),
+        rules: config.routing_rules.clone(),
+    }
+}
@@ -0,0 +1,153 @@
+//! Backend requests: the concurrency limit, retries, deadlines and pooled clients
+
+use super::ServerManager;
+use crate::backend_connection::BackendConnection;
+use crate::config::{Config, RetryPolicy};
+use anyhow::Result;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+use vibeproxy_core::{BackendConfig, ClientError};
+
+/// Longest a connection warmup may hold up the first health check
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A backend request got no answer within `client.request_timeout_ms`
+///
+/// Attached to the request error like
+/// [`AuthError`](crate::backend_api::AuthError); the core `ClientError`
+/// can only call the backend unavailable, not hung.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("The backend didn't answer within {0:?}")]
+pub struct TimeoutError(pub Duration);
+
+impl ServerManager {
+    /// Run a backend request within the app-wide concurrency limit
+    pub(super) async fn limited<F: Future>(&self, request: F) -> F::Output {
+        let _permit = self
+            .request_limit
+            .acquire()
+            .await
+            .expect("request semaphore is never closed");
+        request.await
+    }
+
+    /// Run a backend request, retrying while the backend can't be reached
+    ///
+    /// Only an unavailable backend (refused connection, timeout) is retried;
+    /// anything else, such as a rejected API key, is returned at once. Each
+    /// attempt takes its own slot under the request limit.
+    pub(super) async fn with_retry<T, F, Fut>(
+        &self,
+        policy: &RetryPolicy,
+        mut request: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let max_attempts = policy.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            match self.limited(request()).await {
+                Ok(value) => {
+                    if attempt > 1 {
+                        info!(attempt, "Backend answered after retrying");
+                    }
+                    return Ok(value);
+                }
+                Err(e) if is_unavailable(&e) && attempt < max_attempts => {
+                    let delay = policy.delay(attempt);
+                    debug!(attempt, max_attempts, "Backend unavailable, retrying in {:?}", delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    debug!(attempt, max_attempts, "Backend request failed: {:#}", e);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Cached client for `backend`, created (and optionally warmed up) on first use
+    ///
+    /// Keyed by the whole backend config so an edited config gets a fresh client.
+    pub(super) async fn client(
+        &self,
+        config: &Config,
+        backend: &BackendConfig,
+    ) -> Arc<BackendConnection> {
+        let key = format!("{:?}", backend);
+        if let Some(client) = self.clients.lock().unwrap().get(&key) {
+            return client.clone();
+        }
+
+        let client = Arc::new(BackendConnection::new(config, backend));
+        // A backend that was just found down would only make the warmup wait out its deadline
+        let last_check_failed = matches!(
+            self.status_updates.borrow().as_ref(),
+            Some(status) if !status.running
+        );
+        if config.health.warmup_enabled && !last_check_failed {
+            self.warm_up(&client, config.client.request_timeout().min(WARMUP_TIMEOUT)).await;
+        }
+        self.clients.lock().unwrap().insert(key, client.clone());
+        client
+    }
+
+    /// Open a connection before the first measured health check
+    ///
+    /// Otherwise the first latency sample includes connect/TLS setup. A single
+    /// attempt within `timeout`, whose result is discarded either way.
+    async fn warm_up(&self, client: &BackendConnection, timeout: Duration) {
+        match self.limited(tokio::time::timeout(timeout, client.health_check())).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => debug!("Connection warmup failed, measuring cold: {}", e),
+            Err(_) => debug!("No answer to the connection warmup within {:?}", timeout),
+        }
+    }
+}
+
+/// Give up on a core client request after `timeout`
+///
+/// `BackendClient` calls have no deadline of their own, so a backend that
+/// accepts the connection but never answers would hold the caller (and its
+/// request slot) indefinitely. A timeout counts as unavailable, which status
+/// checks retry, with a [`TimeoutError`] attached to tell it apart from a
+/// refused connection.
+pub(super) async fn with_deadline<T>(
+    timeout: Duration,
+    request: impl Future<Output = Result<T, ClientError>>,
+) -> Result<T> {
+    match tokio::time::timeout(timeout, request).await {
+        Ok(result) => result.map_err(anyhow::Error::new),
+        Err(_) => {
+            warn!("Backend request timed out after {:?}, the backend may be hung", timeout);
+            Err(anyhow::Error::new(ClientError::Unavailable).context(TimeoutError(timeout)))
+        }
+    }
+}
+
+/// Whether `error` means the backend couldn't be reached or didn't answer in time
+pub(super) fn is_unavailable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        matches!(cause.downcast_ref::<ClientError>(), Some(ClientError::Unavailable))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deadline_reports_a_hung_backend() {
+        let timeout = Duration::from_millis(10);
+        let hung = std::future::pending::<Result<(), ClientError>>();
+        let error = with_deadline(timeout, hung).await.unwrap_err();
+        assert!(is_unavailable(&error));
+        assert_eq!(error.downcast_ref::<TimeoutError>(), Some(&TimeoutError(timeout)));
+    }
+}
@@ -0,0 +1,394 @@
+//! Health checks, status polling and the published status
+
+use super::requests::{is_unavailable, with_deadline, TimeoutError};
+use super::ServerManager;
+use crate::backend_api::{BackendApi, BackendDetails};
+use crate::config::{endpoint_backend, unix_socket_path, Config};
+use crate::health::HealthSchedule;
+use crate::recent_checks::{CheckResult, Uptime};
+use crate::sd_notify;
+use crate::sleep_monitor;
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::futures::Notified;
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+use vibeproxy_core::BackendConfig;
+
+/// Pause between quiet checks while the network comes back after a resume
+const RESUME_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+impl ServerManager {
+    pub async fn status(&self) -> Result<ServerStatus> {
+        let status = self.current_status().await?;
+
+        // Tell systemd once the backend can actually serve, not merely when it's up
+        if status.ready && !self.ready_notified.swap(true, std::sync::atomic::Ordering::Relaxed) {
+            sd_notify::ready();
+        }
+
+        if !status.running {
+            self.failed_checks.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+        self.record_check(&status);
+        self.publish(&status);
+
+        Ok(status)
+    }
+
+    /// The last `health.recent_checks` status checks, oldest first
+    ///
+    /// Kept in memory only, whether or not `history` is enabled.
+    pub fn recent_checks(&self) -> Vec<CheckResult> {
+        self.recent_checks.lock().unwrap().results()
+    }
+
+    /// How many of the recent checks within `window` found the backend up
+    pub fn uptime(&self, window: Duration) -> Option<Uptime> {
+        self.recent_checks
+            .lock()
+            .unwrap()
+            .uptime(window, SystemTime::now())
+    }
+
+    fn record_check(&self, status: &ServerStatus) {
+        let latency_ms = status.running.then_some(status.latency_ms);
+        self.recent_checks
+            .lock()
+            .unwrap()
+            .record(SystemTime::now(), latency_ms);
+    }
+
+    /// Times the supervisor restarted a crashed backend since the app started
+    pub fn restart_count(&self) -> u64 {
+        self.restarts.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Status checks that found the backend down since the app started
+    pub fn failed_check_count(&self) -> u64 {
+        self.failed_checks.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Out-of-band health check, e.g. after the user fixed something
+    ///
+    /// Returns `None` without checking if a refresh is already running, so
+    /// repeated requests don't stack. Pollers waiting on
+    /// [`refresh_requested`](Self::refresh_requested) restart their timer.
+    pub async fn refresh_status(&self) -> Option<Result<ServerStatus>> {
+        if self
+            .refresh_in_flight
+            .swap(true, std::sync::atomic::Ordering::AcqRel)
+        {
+            return None;
+        }
+
+        let status = self.status().await;
+        self.refresh_in_flight
+            .store(false, std::sync::atomic::Ordering::Release);
+        self.refresh_requested.notify_waiters();
+        Some(status)
+    }
+
+    /// Completes when a manual refresh has just run
+    pub fn refresh_requested(&self) -> Notified<'_> {
+        self.refresh_requested.notified()
+    }
+
+    /// Status updates shared by every view, with background polling started on first use
+    ///
+    /// Every status check is published, whoever asked for it; receivers are
+    /// only woken when the status differs from the last one. The poller
+    /// checks every `interval` while the backend is up and falls back to
+    /// `health.down_interval_secs` after `health.failure_threshold` failed
+    /// checks in a row. A manual refresh restarts the wait. Polling pauses
+    /// while the system is suspended and checks again on wake, giving the
+    /// backend `health.resume_grace_secs` before a failure is published.
+    /// Polling stops when the manager is dropped; later calls reuse the
+    /// running poller.
+    pub fn watch_status(
+        self: &Arc<Self>,
+        interval: Duration,
+    ) -> watch::Receiver<Option<ServerStatus>> {
+        let receiver = self.status_updates();
+        if self
+            .watching
+            .swap(true, std::sync::atomic::Ordering::AcqRel)
+        {
+            return receiver;
+        }
+
+        let settings = self
+            .config_manager
+            .load()
+            .map(|config| config.health)
+            .unwrap_or_default();
+        let manager = Arc::downgrade(self);
+        let wake = self.refresh_requested.clone();
+        let mut suspended = self.suspended.subscribe();
+        let grace = Duration::from_secs(settings.resume_grace_secs);
+
+        self.runtime.spawn({
+            let manager = manager.clone();
+            async move {
+                if let Err(e) = sleep_monitor::run(manager).await {
+                    debug!("Not pausing health checks on suspend: {:#}", e);
+                }
+            }
+        });
+
+        self.runtime.spawn(async move {
+            let mut schedule = HealthSchedule::with_interval(interval, &settings);
+            'poll: while let Some(running) = poll_once(&manager).await {
+                schedule.record(running);
+
+                // A manual refresh just checked, so start the wait over rather than poll again
+                loop {
+                    let woken = wake.notified();
+                    tokio::pin!(woken);
+                    woken.as_mut().enable();
+                    if manager.strong_count() == 0 {
+                        break 'poll;
+                    }
+
+                    let sleeping = tokio::select! {
+                        _ = tokio::time::sleep(schedule.next_delay()) => false,
+                        _ = woken => continue,
+                        _ = suspended.wait_for(|suspended| *suspended) => true,
+                    };
+                    if sleeping {
+                        info!("System is suspending, pausing health checks");
+                        if suspended.wait_for(|suspended| !*suspended).await.is_err() {
+                            break 'poll;
+                        }
+                        info!("System resumed, checking the backend");
+                        if !settle_after_resume(&manager, grace).await {
+                            break 'poll;
+                        }
+                    }
+                    break;
+                }
+            }
+            debug!("Status watcher stopped");
+        });
+
+        receiver
+    }
+
+    /// Pause the status poller while the system sleeps (`true`) and resume it on wake
+    ///
+    /// Called by [`sleep_monitor`]; embedders without logind can call it themselves.
+    pub fn set_suspended(&self, suspended: bool) {
+        self.suspended.send_if_modified(|current| {
+            let changed = *current != suspended;
+            *current = suspended;
+            changed
+        });
+    }
+
+    /// Status updates without starting the background poller
+    pub fn status_updates(&self) -> watch::Receiver<Option<ServerStatus>> {
+        self.status_updates.subscribe()
+    }
+
+    fn publish(&self, status: &ServerStatus) {
+        self.status_updates.send_if_modified(|current| {
+            let changed = current.as_ref() != Some(status);
+            *current = Some(status.clone());
+            changed
+        });
+    }
+
+    async fn current_status(&self) -> Result<ServerStatus> {
+        let config = self.resolved_config().await?;
+        if config.failover.endpoints.is_empty() {
+            let status = self.probe(&config, &config.core.backend).await?;
+            self.record_sample(config.backend_endpoint(), &status);
+            return Ok(status);
+        }
+
+        // Probe the primary and every failover endpoint, then let the selector pick
+        let backend = &config.core.backend;
+        let mut endpoints = vec![config.backend_endpoint()];
+        endpoints.extend(config.failover.endpoints.iter().cloned());
+
+        let mut statuses = Vec::with_capacity(endpoints.len());
+        for endpoint in &endpoints {
+            let status = match endpoint_backend(backend, endpoint) {
+                Ok(endpoint_config) => self.probe(&config, &endpoint_config).await,
+                Err(e) => Err(e),
+            }
+            .unwrap_or_else(|e| ServerStatus::unavailable(e.to_string()));
+            self.record_sample(endpoint.clone(), &status);
+            statuses.push(status);
+        }
+
+        let mut selector = self.endpoint_selector.lock().unwrap();
+        selector.set_strategy(config.failover.strategy);
+        for (endpoint, status) in endpoints.iter().zip(&statuses) {
+            selector.record(endpoint, status.running.then_some(status.latency_ms));
+        }
+
+        let chosen = selector
+            .select(&endpoints)
+            .and_then(|chosen| endpoints.iter().position(|e| e == chosen))
+            .unwrap_or(0);
+        let mut status = statuses.swap_remove(chosen);
+        status.endpoint = Some(endpoints.swap_remove(chosen));
+        Ok(status)
+    }
+
+    fn record_sample(&self, endpoint: String, status: &ServerStatus) {
+        let latency_ms = status.running.then_some(status.latency_ms);
+        self.record_history(|history| history.record_sample(Some(endpoint), latency_ms));
+    }
+
+    /// Liveness via the core health check, then readiness via the management API
+    async fn probe(&self, config: &Config, backend: &BackendConfig) -> Result<ServerStatus> {
+        let client = self.client(config, backend).await;
+        let timeout = config.client.request_timeout();
+
+        let request = || with_deadline(timeout, client.health_check());
+        let health = match self.with_retry(&config.retry, request).await {
+            Ok(health) => health,
+            // A backend that accepts connections but never answers is likely hung
+            Err(e) if is_unavailable(&e) => {
+                let message = match e.downcast_ref::<TimeoutError>() {
+                    Some(timeout) => timeout.to_string(),
+                    None => "Server unavailable".to_string(),
+                };
+                return Ok(ServerStatus::unavailable(message));
+            }
+            Err(e) => return Err(e),
+        };
+
+        let (ready, details) = if health.healthy {
+            self.diagnostics(config, backend).await
+        } else {
+            (false, None)
+        };
+        Ok(ServerStatus {
+            running: health.healthy,
+            ready,
+            latency_ms: health.latency_ms,
+            message: health.message,
+            endpoint: None,
+            details,
+        })
+    }
+
+    /// Readiness and the backend's own status report, for a live backend
+    ///
+    /// Backends without a readiness endpoint count as ready once they're
+    /// live; without a status endpoint there are no details. The management
+    /// API is TCP-only, so a backend on a Unix socket is ready once it's live.
+    async fn diagnostics(
+        &self,
+        config: &Config,
+        backend: &BackendConfig,
+    ) -> (bool, Option<BackendDetails>) {
+        if matches!(unix_socket_path(&backend.url), Ok(Some(_))) {
+            return (true, None);
+        }
+        let mut endpoint_config = config.clone();
+        endpoint_config.core.backend = backend.clone();
+
+        let api = match BackendApi::new(&endpoint_config) {
+            Ok(api) => api,
+            Err(e) => {
+                warn!("Cannot check backend readiness: {:#}", e);
+                return (false, None);
+            }
+        };
+        let (ready, details) =
+            tokio::join!(self.limited(api.readiness()), self.limited(api.details()));
+
+        let ready = match ready {
+            Ok(ready) => ready.unwrap_or(true),
+            Err(e) => {
+                debug!("Readiness check failed: {:#}", e);
+                false
+            }
+        };
+        let details = details.unwrap_or_else(|e| {
+            debug!("Backend details unavailable: {:#}", e);
+            None
+        });
+        (ready, details)
+    }
+}
+
+/// One published status check, whether the backend was up, or `None` if the manager is gone
+async fn poll_once(manager: &Weak<ServerManager>) -> Option<bool> {
+    let manager = manager.upgrade()?;
+    match manager.status().await {
+        Ok(status) => Some(status.running),
+        Err(e) => {
+            let status = ServerStatus::unavailable(e.to_string());
+            manager.record_check(&status);
+            manager.publish(&status);
+            Some(false)
+        }
+    }
+}
+
+/// Quietly re-check until the backend answers or `grace` runs out after a resume
+///
+/// Nothing is published meanwhile, so the last status from before the
+/// suspend stays up while the network reconnects. Returns `false` if the
+/// manager is gone.
+async fn settle_after_resume(manager: &Weak<ServerManager>, grace: Duration) -> bool {
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline {
+        let Some(manager) = manager.upgrade() else {
+            return false;
+        };
+        if manager
+            .current_status()
+            .await
+            .is_ok_and(|status| status.running)
+        {
+            break;
+        }
+        drop(manager);
+        tokio::time::sleep(RESUME_POLL_INTERVAL).await;
+    }
+    manager.strong_count() > 0
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerStatus {
+    /// The backend process answers health checks (liveness)
+    pub running: bool,
+    /// The backend has finished starting up and can route requests (readiness)
+    pub ready: bool,
+    pub latency_ms: u64,
+    pub message: Option<String>,
+    /// Endpoint the status refers to when failover endpoints are configured
+    pub endpoint: Option<String>,
+    /// Version, uptime and provider reachability, if the backend reports them
+    pub details: Option<BackendDetails>,
+}
+
+impl ServerStatus {
+    pub fn unavailable(message: String) -> Self {
+        Self {
+            running: false,
+            ready: false,
+            latency_ms: 0,
+            message: Some(message),
+            endpoint: None,
+            details: None,
+        }
+    }
+
+    /// Short human-readable state
+    pub fn label(&self) -> &'static str {
+        match (self.running, self.ready) {
+            (true, true) => "Ready",
+            (true, false) => "Starting up…",
+            (false, _) => "Stopped",
+        }
+    }
+}
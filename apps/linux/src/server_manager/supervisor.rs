@@ -0,0 +1,220 @@
+//! Supervision of the backend process we spawned
+
+use super::requests::{with_deadline, TimeoutError};
+use crate::backend_connection::{BackendConnection, Health};
+use crate::backend_logs::BackendLogs;
+use crate::config::{BackendProcessSettings, RestartPolicy};
+use crate::run_state::RunState;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+use tokio::sync::{watch, Semaphore};
+use tracing::{error, info, warn};
+
+/// Pause between health checks while a spawned backend starts up
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often the supervisor checks whether the spawned backend is still alive
+const SUPERVISE_INTERVAL: Duration = Duration::from_secs(1);
+/// Upper bound on the delay between restart attempts
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(300);
+/// A backend that stayed up this long gets its restart budget back
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+/// Spawns the backend and restarts it if it exits while it should be running
+pub(super) struct Supervisor {
+    pub(super) child: Arc<Mutex<Option<Child>>>,
+    pub(super) logs: Arc<BackendLogs>,
+    pub(super) is_running: Arc<watch::Sender<bool>>,
+    pub(super) request_limit: Arc<Semaphore>,
+    pub(super) client: Arc<BackendConnection>,
+    pub(super) request_timeout: Duration,
+    pub(super) settings: BackendProcessSettings,
+    /// Provider API keys, passed as environment variables
+    pub(super) provider_keys: HashMap<String, String>,
+    pub(super) policy: RestartPolicy,
+    /// Shared with the manager, see
+    /// [`ServerManager::restart_count`](super::ServerManager::restart_count)
+    pub(super) restarts: Arc<AtomicU64>,
+    /// Marked as a crash loop when restarts are given up on
+    pub(super) run_state: Option<PathBuf>,
+}
+
+impl Supervisor {
+    /// Spawn the backend and wait for it to pass a health check
+    pub(super) async fn launch(&self) -> Result<()> {
+        let binary = self.settings.binary_path.as_ref().context(
+            "Backend is not running and no backend binary is configured (backend_process.binary_path)",
+        )?;
+
+        let mut child = Command::new(binary)
+            .args(&self.settings.args)
+            .envs(&self.provider_keys)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start backend {:?}", binary))?;
+        info!("Spawned backend {:?} (pid {:?})", binary, child.id());
+
+        // The readers end on their own when the process closes its output
+        if let Some(stdout) = child.stdout.take() {
+            let logs = self.logs.clone();
+            tokio::spawn(async move { logs.capture(stdout).await });
+        }
+        if let Some(stderr) = child.stderr.take() {
+            let logs = self.logs.clone();
+            tokio::spawn(async move { logs.capture(stderr).await });
+        }
+        *self.child.lock().unwrap() = Some(child);
+
+        if let Err(e) = self.wait_until_healthy().await {
+            let child = self.child.lock().unwrap().take();
+            if let Some(mut child) = child {
+                let _ = child.kill().await;
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Health-check a freshly spawned backend until it answers or the startup timeout passes
+    async fn wait_until_healthy(&self) -> Result<()> {
+        let timeout = self.settings.startup_timeout();
+        let deadline = Instant::now() + timeout;
+        // Whether the last health check went unanswered rather than refused
+        let mut hung = None;
+        loop {
+            let health = {
+                let _permit = self
+                    .request_limit
+                    .acquire()
+                    .await
+                    .expect("request semaphore is never closed");
+                with_deadline(self.request_timeout, self.client.health_check()).await
+            };
+            match health {
+                Ok(status) if status.healthy => return Ok(()),
+                // Up, but waiting longer won't change its mind about the key
+                Ok(Health {
+                    auth: Some(auth), ..
+                }) => {
+                    return Err(anyhow::Error::new(auth)
+                        .context("The backend started but didn't accept the API key"))
+                }
+                Err(e) => hung = e.downcast_ref::<TimeoutError>().copied(),
+                _ => hung = None,
+            }
+
+            let exited = self
+                .child
+                .lock()
+                .unwrap()
+                .as_mut()
+                .and_then(|child| child.try_wait().ok().flatten());
+            if let Some(exit) = exited {
+                bail!("Backend exited during startup ({})", exit);
+            }
+
+            if Instant::now() >= deadline {
+                let message = format!("Backend did not become healthy within {:?}", timeout);
+                return Err(match hung {
+                    Some(hung) => anyhow::Error::new(hung).context(message),
+                    None => anyhow::anyhow!(message),
+                });
+            }
+            tokio::time::sleep(STARTUP_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Watch the backend until it is stopped on purpose, restarting it after crashes
+    ///
+    /// Restarts back off exponentially from `restart_policy.base_delay_ms`.
+    /// After `restart_policy.max_retries` failed restarts in a row the
+    /// backend is given up on and reported as not running.
+    pub(super) async fn run(self) {
+        let mut failures = 0;
+        let mut started_at = Instant::now();
+
+        loop {
+            tokio::time::sleep(SUPERVISE_INTERVAL).await;
+
+            let exit = {
+                let mut child = self.child.lock().unwrap();
+                let Some(running) = child.as_mut() else {
+                    // Taken by stop()
+                    return;
+                };
+                match running.try_wait() {
+                    Ok(Some(exit)) => {
+                        child.take();
+                        exit
+                    }
+                    _ => continue,
+                }
+            };
+            if !*self.is_running.borrow() {
+                return;
+            }
+
+            if started_at.elapsed() >= STABLE_UPTIME {
+                failures = 0;
+            }
+
+            loop {
+                if failures >= self.policy.max_retries {
+                    error!(
+                        "Backend exited ({}), giving up after {} restart attempts",
+                        exit, failures
+                    );
+                    self.is_running.send_replace(false);
+                    save_run_state(
+                        self.run_state.as_deref(),
+                        RunState {
+                            backend_running: true,
+                            crash_loop: true,
+                        },
+                    );
+                    return;
+                }
+
+                let delay = self.policy.delay(failures).min(MAX_RESTART_DELAY);
+                failures += 1;
+                warn!(
+                    "Backend exited unexpectedly ({}), restarting in {:?} (attempt {}/{})",
+                    exit, delay, failures, self.policy.max_retries
+                );
+                tokio::time::sleep(delay).await;
+
+                // stop() may have run while we were waiting
+                if !*self.is_running.borrow() {
+                    return;
+                }
+                match self.launch().await {
+                    Ok(()) => {
+                        info!("Backend restarted");
+                        self.restarts.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        started_at = Instant::now();
+                        break;
+                    }
+                    Err(e) => warn!("Backend restart failed: {:#}", e),
+                }
+            }
+        }
+    }
+}
+
+/// Save `state` to `path`, if run state is kept; a failure is only logged
+pub(super) fn save_run_state(path: Option<&Path>, state: RunState) {
+    if let Some(path) = path {
+        if let Err(e) = state.save(path) {
+            warn!("Failed to save the backend run state: {:#}", e);
+        }
+    }
+}
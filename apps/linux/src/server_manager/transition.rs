@@ -0,0 +1,92 @@
+//! The start or stop under way, so conflicting ones are turned down
+
+use thiserror::Error;
+use tokio::sync::watch;
+
+/// A start or stop that hasn't finished, see
+/// [`ServerManager::transition_updates`](super::ServerManager::transition_updates)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Starting,
+    Stopping,
+}
+
+impl Transition {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Starting => "Starting…",
+            Self::Stopping => "Stopping…",
+        }
+    }
+}
+
+/// A start or stop was asked for while another one was still under way
+///
+/// Returned by [`ServerManager::start`](super::ServerManager::start) and
+/// [`ServerManager::stop`](super::ServerManager::stop); find it with `downcast_ref`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum TransitionError {
+    #[error("The backend is still starting; stop it once it is up")]
+    Starting,
+    #[error("The backend is still stopping; start it again once it has stopped")]
+    Stopping,
+}
+
+/// Clears the pending transition when the start or stop ends, however it ends
+pub(super) struct TransitionGuard<'a>(&'a watch::Sender<Option<Transition>>);
+
+impl Drop for TransitionGuard<'_> {
+    fn drop(&mut self) {
+        self.0.send_replace(None);
+    }
+}
+
+/// Mark `transition` as pending, unless one already is
+pub(super) fn begin_transition(
+    pending: &watch::Sender<Option<Transition>>,
+    transition: Transition,
+) -> Result<TransitionGuard<'_>, TransitionError> {
+    let mut busy = None;
+    // Checked and set under the channel's lock, so two callers can't both claim it
+    pending.send_if_modified(|current| match current {
+        Some(other) => {
+            busy = Some(*other);
+            false
+        }
+        None => {
+            *current = Some(transition);
+            true
+        }
+    });
+    match busy {
+        Some(Transition::Starting) => Err(TransitionError::Starting),
+        Some(Transition::Stopping) => Err(TransitionError::Stopping),
+        None => Ok(TransitionGuard(pending)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflicting_transitions_are_rejected() {
+        let pending = watch::channel(None).0;
+        let updates = pending.subscribe();
+
+        let starting = begin_transition(&pending, Transition::Starting).unwrap();
+        assert_eq!(*updates.borrow(), Some(Transition::Starting));
+        let conflict = begin_transition(&pending, Transition::Stopping).err();
+        assert_eq!(conflict, Some(TransitionError::Starting));
+        assert!(begin_transition(&pending, Transition::Starting).is_err());
+        assert_eq!(*updates.borrow(), Some(Transition::Starting));
+
+        drop(starting);
+        assert_eq!(*updates.borrow(), None);
+        let stopping = begin_transition(&pending, Transition::Stopping).unwrap();
+        let conflict = begin_transition(&pending, Transition::Starting).err();
+        assert_eq!(conflict, Some(TransitionError::Stopping));
+        drop(stopping);
+        assert_eq!(*updates.borrow(), None);
+    }
+}
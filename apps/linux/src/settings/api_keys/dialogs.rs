@@ -0,0 +1,186 @@
+//! Confirmations for deleting and importing API keys
+
+use super::ApiKeys;
+use adw::prelude::*;
+use adw::{MessageDialog, ResponseAppearance};
+use gtk::{glib, CheckButton, Orientation, ScrolledWindow};
+use std::cell::Cell;
+use std::rc::Rc;
+use vibeproxy_linux::keyring::qualified_name;
+use vibeproxy_linux::secret_store::{self, ImportSummary};
+
+impl ApiKeys {
+    /// Ask once before deleting `keys`, naming a single key or counting several
+    pub(super) fn confirm_delete(self: &Rc<Self>, keys: Vec<(String, String)>) {
+        let (heading, body) = match keys.as_slice() {
+            [] => return,
+            [(namespace, name)] => (
+                "Delete API Key?".to_string(),
+                format!(
+                    "The key for '{}' will be removed from the keyring.",
+                    qualified_name(namespace, name)
+                ),
+            ),
+            keys => (
+                format!("Delete {} API Keys?", keys.len()),
+                format!(
+                    "These keys will be removed from the keyring: {}.",
+                    keys.iter()
+                        .map(|(namespace, name)| qualified_name(namespace, name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+        };
+        let dialog = MessageDialog::new(Some(&self.window), Some(&heading), Some(&body));
+        dialog.add_responses(&[("cancel", "Cancel"), ("delete", "Delete")]);
+        dialog.set_response_appearance("delete", ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as this => move |_, response| {
+                if response == "delete" {
+                    this.delete(keys.clone());
+                }
+            }),
+        );
+        dialog.present();
+    }
+
+    /// List the keys again and offer to delete those no provider uses
+    pub(super) fn find_orphans(self: &Rc<Self>) {
+        self.run_then(
+            |keyring| async move { keyring.list_keys().await },
+            |this, stored: Vec<(String, String)>| this.confirm_delete_orphans(stored),
+        );
+    }
+
+    /// One checkbox per orphaned key; only the ticked ones are deleted
+    fn confirm_delete_orphans(self: &Rc<Self>, stored: Vec<(String, String)>) {
+        let orphans =
+            secret_store::orphaned_keys(&self.providers, &self.settings.stored_secrets, &stored);
+        if orphans.is_empty() {
+            self.toast("Every stored key belongs to a configured provider");
+            return;
+        }
+
+        let dialog = MessageDialog::new(
+            Some(&self.window),
+            Some(&format!("{} Unused API Keys", orphans.len())),
+            Some("No configured provider uses these keys. Choose the ones to remove."),
+        );
+        let list = gtk::Box::new(Orientation::Vertical, 6);
+        let checks: Vec<(CheckButton, (String, String))> = orphans
+            .into_iter()
+            .map(|key| {
+                let check = CheckButton::with_label(&qualified_name(&key.0, &key.1));
+                check.set_active(true);
+                list.append(&check);
+                (check, key)
+            })
+            .collect();
+        dialog.set_extra_child(Some(
+            &ScrolledWindow::builder()
+                .child(&list)
+                .max_content_height(240)
+                .propagate_natural_height(true)
+                .build(),
+        ));
+        dialog.add_responses(&[("cancel", "Cancel"), ("delete", "Delete")]);
+        dialog.set_response_appearance("delete", ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+
+        // Counted rather than looked up, so the checks don't own each other
+        let ticked = Rc::new(Cell::new(checks.len()));
+        for (check, _) in &checks {
+            check.connect_toggled(glib::clone!(@weak dialog, @strong ticked => move |check| {
+                if check.is_active() {
+                    ticked.set(ticked.get() + 1);
+                } else {
+                    ticked.set(ticked.get() - 1);
+                }
+                dialog.set_response_enabled("delete", ticked.get() > 0);
+            }));
+        }
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as this => move |_, response| {
+                if response == "delete" {
+                    let keys = checks
+                        .iter()
+                        .filter(|(check, _)| check.is_active())
+                        .map(|(_, key)| key.clone())
+                        .collect();
+                    this.delete(keys);
+                }
+            }),
+        );
+        dialog.present();
+    }
+
+    /// Offer to store the provider keys that are set in the environment
+    pub(super) fn confirm_import_env(self: &Rc<Self>) {
+        let found: Vec<(String, String)> = self
+            .env_sources
+            .iter()
+            .filter(|(var, _)| std::env::var_os(var).is_some_and(|value| !value.is_empty()))
+            .cloned()
+            .collect();
+        if found.is_empty() {
+            self.toast("No API keys found in the environment");
+            return;
+        }
+
+        let body = format!(
+            "Each key is stored in the keyring under its provider: {}.",
+            found
+                .iter()
+                .map(|(var, provider)| format!("{} → {}", var, provider))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let dialog = MessageDialog::new(
+            Some(&self.window),
+            Some(&format!("Import {} API Keys?", found.len())),
+            Some(&body),
+        );
+        let overwrite = CheckButton::with_label("Replace keys that are already stored");
+        dialog.set_extra_child(Some(&overwrite));
+        dialog.add_responses(&[("cancel", "Cancel"), ("import", "Import")]);
+        dialog.set_response_appearance("import", ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("import"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as this, @weak overwrite => move |_, response| {
+                if response == "import" {
+                    this.import_env(found.clone(), overwrite.is_active());
+                }
+            }),
+        );
+        dialog.present();
+    }
+
+    fn import_env(self: &Rc<Self>, sources: Vec<(String, String)>, overwrite: bool) {
+        self.run_then(
+            move |keyring| async move {
+                let lookup = |var: &str| std::env::var(var).ok();
+                secret_store::import_env_keys(keyring.as_ref(), &sources, lookup, overwrite).await
+            },
+            |this, summary: ImportSummary| {
+                let mut message = match summary.imported.as_slice() {
+                    [] => "No keys imported".to_string(),
+                    names => format!("Imported {}", names.join(", ")),
+                };
+                if !summary.skipped.is_empty() {
+                    message.push_str(&format!("; kept existing {}", summary.skipped.join(", ")));
+                }
+                this.toast(&message);
+            },
+        );
+    }
+}
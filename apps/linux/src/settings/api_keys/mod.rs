@@ -0,0 +1,290 @@
+//! API keys page of the settings window
+
+mod dialogs;
+
+use adw::prelude::*;
+use adw::{
+    ActionRow, EntryRow, PasswordEntryRow, PreferencesGroup, PreferencesPage, PreferencesWindow,
+    Toast,
+};
+use anyhow::Result;
+use gtk::{glib, Align, Button, CheckButton, Orientation};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::error;
+use vibeproxy_linux::config::{Config, KeyringSettings};
+use vibeproxy_linux::keyring::{qualified_name, DEFAULT_NAMESPACE};
+use vibeproxy_linux::secret_store::{self, SecretStore};
+
+/// Provider API keys in the keyring, named after the provider id
+///
+/// Keys can be scoped to a namespace (`openai` in `work`, shown as
+/// `work/openai`). Saved values are never read back; rows only say whether a
+/// key is set.
+pub(super) fn api_keys_page(
+    window: &PreferencesWindow,
+    config: &Config,
+    runtime: &Handle,
+) -> PreferencesPage {
+    let page = PreferencesPage::builder()
+        .title("API Keys")
+        .icon_name("dialog-password-symbolic")
+        .build();
+
+    let list = PreferencesGroup::builder()
+        .title("API Keys")
+        .description("Stored in the system keyring. Saved keys are never shown again.")
+        .build();
+    page.add(&list);
+
+    let delete_selected = Button::builder()
+        .label("Delete Selected…")
+        .css_classes(&["flat", "destructive-action"])
+        .valign(Align::Center)
+        .sensitive(false)
+        .build();
+    let find_unused = Button::builder()
+        .label("Find Unused…")
+        .tooltip_text("Keys of providers that are no longer configured")
+        .css_classes(&["flat"])
+        .valign(Align::Center)
+        .build();
+    let header_buttons = gtk::Box::new(Orientation::Horizontal, 6);
+    header_buttons.append(&find_unused);
+    header_buttons.append(&delete_selected);
+    list.set_header_suffix(Some(&header_buttons));
+
+    let keys = Rc::new(ApiKeys {
+        window: window.clone(),
+        list,
+        delete_selected: delete_selected.clone(),
+        rows: RefCell::default(),
+        selected: RefCell::default(),
+        keyring: RefCell::default(),
+        settings: config.keyring.clone(),
+        providers: config.providers.iter().map(|p| p.id.clone()).collect(),
+        env_sources: secret_store::env_key_sources(config),
+        runtime: runtime.clone(),
+    });
+    delete_selected.connect_clicked(glib::clone!(@weak keys => move |_| {
+        let selected: Vec<_> = keys.selected.borrow().iter().cloned().collect();
+        keys.confirm_delete(selected);
+    }));
+    find_unused.connect_clicked(glib::clone!(@weak keys => move |_| keys.find_orphans()));
+
+    let form = PreferencesGroup::builder()
+        .title("Add or Replace a Key")
+        .build();
+    let import_button = Button::builder()
+        .label("Import from Environment…")
+        .tooltip_text("Store keys set in variables like OPENAI_API_KEY")
+        .css_classes(&["flat"])
+        .valign(Align::Center)
+        .build();
+    import_button.connect_clicked(glib::clone!(@weak keys => move |_| keys.confirm_import_env()));
+    form.set_header_suffix(Some(&import_button));
+    let name_row = EntryRow::builder().title("Provider").build();
+    let namespace_row = EntryRow::builder().title("Namespace (optional)").build();
+    let secret_row = PasswordEntryRow::builder().title("API key").build();
+    let save_button = Button::builder()
+        .label("Save Key")
+        .css_classes(&["suggested-action"])
+        .halign(Align::End)
+        .margin_top(6)
+        .build();
+    save_button.connect_clicked(glib::clone!(
+        @weak keys,
+        @weak name_row,
+        @weak namespace_row,
+        @weak secret_row
+        => move |_| {
+            let name = name_row.text().trim().to_string();
+            let namespace = namespace_row.text().trim().to_string();
+            let secret = secret_row.text().to_string();
+            if name.is_empty() || secret.is_empty() {
+                keys.toast("Enter a provider and an API key");
+                return;
+            }
+            // Don't leave the secret sitting in the widget
+            secret_row.set_text("");
+            keys.store(Some(namespace).filter(|ns| !ns.is_empty()), name, secret);
+        }
+    ));
+    form.add(&name_row);
+    form.add(&namespace_row);
+    form.add(&secret_row);
+    form.add(&save_button);
+    page.add(&form);
+
+    keys.refresh();
+    page
+}
+
+struct ApiKeys {
+    window: PreferencesWindow,
+    list: PreferencesGroup,
+    /// Deletes the keys ticked in `selected`, after one confirmation
+    delete_selected: Button,
+    rows: RefCell<Vec<ActionRow>>,
+    /// `(namespace, key)` of the ticked rows
+    selected: RefCell<BTreeSet<(String, String)>>,
+    /// Connected on first use and kept while the window is open
+    keyring: RefCell<Option<Arc<dyn SecretStore>>>,
+    settings: KeyringSettings,
+    providers: Vec<String>,
+    /// `(variable, provider)` pairs offered by "Import from Environment"
+    env_sources: Vec<(String, String)>,
+    runtime: Handle,
+}
+
+impl ApiKeys {
+    /// Run `op` against the keyring on the runtime, then list the keys again
+    fn run<F, Fut>(self: &Rc<Self>, op: F)
+    where
+        F: FnOnce(Arc<dyn SecretStore>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send,
+    {
+        self.run_then(op, |_, ()| {});
+    }
+
+    /// Like [`run`](Self::run), handing `op`'s result to `then` after the list is updated
+    fn run_then<T, F, Fut>(self: &Rc<Self>, op: F, then: impl FnOnce(&Rc<Self>, T) + 'static)
+    where
+        T: Send + 'static,
+        F: FnOnce(Arc<dyn SecretStore>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<T>> + Send,
+    {
+        let existing = self.keyring.borrow().clone();
+        let settings = self.settings.clone();
+        let request = self.runtime.spawn(async move {
+            let keyring = match existing {
+                Some(keyring) => keyring,
+                None => secret_store::open(&settings).await?,
+            };
+            let output = op(keyring.clone()).await?;
+            let keys = keyring.list_keys().await?;
+            Ok::<_, anyhow::Error>((keyring, keys, output))
+        });
+
+        glib::spawn_future_local(glib::clone!(@weak self as this => async move {
+            match request.await {
+                Ok(Ok((keyring, keys, output))) => {
+                    *this.keyring.borrow_mut() = Some(keyring);
+                    this.render(keys);
+                    then(&this, output);
+                }
+                Ok(Err(e)) => {
+                    error!("Keyring operation failed: {:#}", e);
+                    this.toast(&format!("Keyring: {:#}", e));
+                }
+                Err(e) => error!("Keyring task failed: {}", e),
+            }
+        }));
+    }
+
+    fn refresh(self: &Rc<Self>) {
+        self.run(|_| async { Ok(()) });
+    }
+
+    fn store(self: &Rc<Self>, namespace: Option<String>, name: String, secret: String) {
+        self.run(move |keyring| async move {
+            keyring.store(namespace.as_deref(), &name, &secret).await
+        });
+    }
+
+    fn delete(self: &Rc<Self>, keys: Vec<(String, String)>) {
+        self.run(move |keyring| async move {
+            for (namespace, name) in &keys {
+                keyring.delete(Some(namespace), name).await?;
+            }
+            Ok(())
+        });
+    }
+
+    /// One row per configured provider and per stored key
+    fn render(self: &Rc<Self>, stored: Vec<(String, String)>) {
+        for row in self.rows.borrow_mut().drain(..) {
+            self.list.remove(&row);
+        }
+
+        let orphans: BTreeSet<(String, String)> =
+            secret_store::orphaned_keys(&self.providers, &self.settings.stored_secrets, &stored)
+                .into_iter()
+                .collect();
+        let stored: BTreeSet<(String, String)> = stored.into_iter().collect();
+        // Keys deleted meanwhile can't stay selected
+        self.selected.borrow_mut().retain(|key| stored.contains(key));
+        self.update_delete_selected();
+        let providers = self
+            .providers
+            .iter()
+            .map(|id| (DEFAULT_NAMESPACE.to_string(), id.clone()));
+        let entries: BTreeSet<(String, String)> = providers.chain(stored.clone()).collect();
+        for (namespace, name) in entries {
+            let key = (namespace.clone(), name.clone());
+            let is_set = stored.contains(&key);
+            let subtitle = if orphans.contains(&key) {
+                "Set, not used by any provider"
+            } else if is_set {
+                "Set"
+            } else {
+                "Not set"
+            };
+            let row = ActionRow::builder()
+                .title(qualified_name(&namespace, &name))
+                .subtitle(subtitle)
+                .build();
+
+            if is_set {
+                let check = CheckButton::builder()
+                    .valign(Align::Center)
+                    .active(self.selected.borrow().contains(&key))
+                    .build();
+                check.connect_toggled(glib::clone!(
+                    @weak self as this,
+                    @strong key
+                    => move |check| {
+                        if check.is_active() {
+                            this.selected.borrow_mut().insert(key.clone());
+                        } else {
+                            this.selected.borrow_mut().remove(&key);
+                        }
+                        this.update_delete_selected();
+                    }
+                ));
+                row.add_prefix(&check);
+
+                let delete_button = Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text("Delete key")
+                    .valign(Align::Center)
+                    .css_classes(&["flat"])
+                    .build();
+                delete_button.connect_clicked(glib::clone!(
+                    @weak self as this
+                    => move |_| this.confirm_delete(vec![key.clone()])
+                ));
+                row.add_suffix(&delete_button);
+            }
+
+            self.list.add(&row);
+            self.rows.borrow_mut().push(row);
+        }
+    }
+
+    fn update_delete_selected(&self) {
+        let count = self.selected.borrow().len();
+        self.delete_selected.set_sensitive(count > 0);
+        self.delete_selected.set_label(&match count {
+            0 => "Delete Selected…".to_string(),
+            count => format!("Delete Selected ({})…", count),
+        });
+    }
+
+    fn toast(&self, message: &str) {
+        self.window.add_toast(Toast::new(message));
+    }
+}
@@ -0,0 +1,123 @@
+//! Config file schema version, with a preview of the upgrade
+
+use adw::prelude::*;
+use adw::{
+    ActionRow, MessageDialog, PreferencesGroup, PreferencesWindow, ResponseAppearance, Toast,
+};
+use gtk::{glib, Align, Button, ScrolledWindow, TextView, WrapMode};
+use std::sync::Arc;
+use tracing::{error, info};
+use vibeproxy_linux::config_manager::{ConfigManager, MigrationPreview, SCHEMA_VERSION};
+
+/// Where the config file is and whether it was written by an older version
+///
+/// An outdated file gets a button that shows the upgrade as a diff before
+/// writing it. Loading upgrades the file too; this catches one replaced or
+/// restored while the app runs.
+pub(super) fn config_file_group(
+    window: &PreferencesWindow,
+    config_manager: &Arc<ConfigManager>,
+) -> PreferencesGroup {
+    let group = PreferencesGroup::builder().title("Config File").build();
+    let row = ActionRow::builder()
+        .title(config_manager.get_config_path().display().to_string())
+        .build();
+    group.add(&row);
+
+    if !config_manager.get_config_path().exists() {
+        row.set_subtitle("Not created yet, defaults are in use");
+        return group;
+    }
+    let preview = match config_manager.preview_migration() {
+        Ok(preview) if preview.is_noop() => {
+            row.set_subtitle(&format!("Schema version {}, up to date", preview.from_version));
+            return group;
+        }
+        Ok(preview) => preview,
+        Err(e) => {
+            row.set_subtitle(&format!("Can't check the schema version: {:#}", e));
+            return group;
+        }
+    };
+
+    row.set_subtitle(&format!(
+        "Schema version {}, written by an older version of VibeProxy (current: {})",
+        preview.from_version, SCHEMA_VERSION
+    ));
+    let upgrade = Button::builder()
+        .label("Preview Upgrade…")
+        .css_classes(&["flat"])
+        .valign(Align::Center)
+        .build();
+    upgrade.connect_clicked(glib::clone!(
+        @weak window,
+        @weak row,
+        @strong config_manager
+        => move |upgrade| {
+            confirm_upgrade(&window, &config_manager, &preview, &row, upgrade)
+        }
+    ));
+    row.add_suffix(&upgrade);
+    group
+}
+
+/// Show the upgrade as a diff and write it if confirmed
+fn confirm_upgrade(
+    window: &PreferencesWindow,
+    config_manager: &Arc<ConfigManager>,
+    preview: &MigrationPreview,
+    row: &ActionRow,
+    upgrade: &Button,
+) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        Some("Upgrade Config File?"),
+        Some("These changes bring the file up to date. The original is kept next to it."),
+    );
+    let diff = TextView::builder()
+        .editable(false)
+        .cursor_visible(false)
+        .monospace(true)
+        .wrap_mode(WrapMode::None)
+        .build();
+    diff.buffer().set_text(&preview.diff());
+    dialog.set_extra_child(Some(
+        &ScrolledWindow::builder()
+            .child(&diff)
+            .min_content_width(480)
+            .min_content_height(240)
+            .build(),
+    ));
+    dialog.add_responses(&[("cancel", "Cancel"), ("upgrade", "Upgrade")]);
+    dialog.set_response_appearance("upgrade", ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    dialog.connect_response(
+        Some("upgrade"),
+        glib::clone!(
+            @weak window,
+            @weak row,
+            @weak upgrade,
+            @strong config_manager
+            => move |_, _| {
+                let message = match config_manager.migrate() {
+                    Ok(Some(backup)) => {
+                        info!("Config file upgraded from the settings window");
+                        format!("Config upgraded, original kept at {}", backup.display())
+                    }
+                    Ok(None) => "Config is already up to date".to_string(),
+                    Err(e) => {
+                        error!("Failed to upgrade config: {:#}", e);
+                        window.add_toast(Toast::new(&format!("Could not upgrade: {:#}", e)));
+                        return;
+                    }
+                };
+                row.set_subtitle(&format!("Schema version {}, up to date", SCHEMA_VERSION));
+                upgrade.set_visible(false);
+                window.add_toast(Toast::new(&message));
+            }
+        ),
+    );
+    dialog.present();
+}
@@ -0,0 +1,303 @@
+//! Settings window
+
+mod api_keys;
+mod config_file;
+mod test_completion;
+
+use crate::ui::{apply_theme, offer_rollback};
+use adw::prelude::*;
+use adw::{ActionRow, ComboRow, EntryRow, PreferencesGroup, PreferencesPage, PreferencesWindow};
+use api_keys::api_keys_page;
+use config_file::config_file_group;
+use gtk::{glib, Align, Button, Label, SpinButton, StringList, Switch};
+use std::sync::Arc;
+use test_completion::test_completion_page;
+use tokio::runtime::Handle;
+use tracing::{error, info};
+use vibeproxy_linux::autostart;
+use vibeproxy_linux::config::{check_backend_url, Config, Theme};
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::server_manager::{ApplyOutcome, ServerManager};
+
+/// Open the settings window, filled in from the config as it is now
+pub fn show_settings(
+    parent: &impl IsA<gtk::Window>,
+    config_manager: Arc<ConfigManager>,
+    server_manager: Arc<ServerManager>,
+    runtime: &Handle,
+) {
+    let window = PreferencesWindow::builder()
+        .title("Settings")
+        .transient_for(parent)
+        .modal(true)
+        .search_enabled(false)
+        .build();
+
+    let error_label = Label::builder()
+        .css_classes(&["error"])
+        .wrap(true)
+        .xalign(0.0)
+        .visible(false)
+        .build();
+
+    let config = match config_manager.load() {
+        Ok(config) => config,
+        Err(e) => {
+            show_error(
+                &error_label,
+                &format!(
+                    "Could not read the current settings, showing defaults: {:#}",
+                    e
+                ),
+            );
+            Config::default()
+        }
+    };
+
+    let page = PreferencesPage::new();
+
+    let errors = PreferencesGroup::new();
+    errors.add(&error_label);
+    page.add(&errors);
+
+    // Backend
+    let backend = PreferencesGroup::builder().title("Backend").build();
+
+    let url_row = EntryRow::builder()
+        .title("URL")
+        .text(&config.core.backend.url)
+        .build();
+    url_row.connect_changed(|row| {
+        if check_backend_url(&row.text()).is_ok() {
+            row.remove_css_class("error");
+        } else {
+            row.add_css_class("error");
+        }
+    });
+    backend.add(&url_row);
+
+    let port = spin_row(
+        &backend,
+        "Port",
+        config.core.backend.port as f64,
+        1.0,
+        65535.0,
+    );
+    let timeout = spin_row(
+        &backend,
+        "Request timeout (seconds)",
+        config.core.backend.timeout_secs as f64,
+        1.0,
+        600.0,
+    );
+    // Set in the config file; shown so it's clear they're sent, without exposing credentials
+    if !config.client.extra_headers.is_empty() {
+        let headers = ActionRow::builder()
+            .title("Extra headers (client.extra_headers)")
+            .subtitle(config.client.masked_headers().join("\n"))
+            .build();
+        backend.add(&headers);
+    }
+    page.add(&backend);
+
+    // Restart policy
+    let restarts = PreferencesGroup::builder()
+        .title("Crash Recovery")
+        .description("Restarting a backend VibeProxy started after it exits on its own")
+        .build();
+    let max_retries = spin_row(
+        &restarts,
+        "Restart attempts (0 to disable)",
+        config.restart_policy.max_retries as f64,
+        0.0,
+        100.0,
+    );
+    let base_delay = spin_row(
+        &restarts,
+        "First restart delay (ms)",
+        config.restart_policy.base_delay_ms as f64,
+        100.0,
+        600_000.0,
+    );
+    page.add(&restarts);
+
+    let appearance = PreferencesGroup::builder().title("Appearance").build();
+    let labels: Vec<&str> = Theme::ALL.iter().map(Theme::label).collect();
+    let theme_row = ComboRow::builder()
+        .title("Style")
+        .model(&StringList::new(&labels))
+        .selected(Theme::ALL.iter().position(|t| *t == config.ui.theme).unwrap_or(0) as u32)
+        .build();
+    appearance.add(&theme_row);
+    page.add(&appearance);
+
+    page.add(&startup_group(&error_label));
+    // Safe mode ignores the file, so there's nothing to upgrade
+    if !config_manager.is_safe_mode() {
+        page.add(&config_file_group(&window, &config_manager));
+    }
+
+    let save_button = Button::builder()
+        .label("Save")
+        .css_classes(&["suggested-action"])
+        .halign(Align::End)
+        .build();
+    let actions = PreferencesGroup::new();
+    actions.add(&save_button);
+    page.add(&actions);
+
+    let parent = parent.clone().upcast::<gtk::Window>();
+    let runtime = runtime.clone();
+    save_button.connect_clicked(glib::clone!(
+        @weak parent,
+        @weak window,
+        @weak error_label,
+        @weak url_row,
+        @weak port,
+        @weak timeout,
+        @weak max_retries,
+        @weak base_delay,
+        @weak theme_row,
+        @strong config_manager,
+        @strong server_manager,
+        @strong runtime
+        => move |save_button| {
+            // Commit half-typed values before reading them
+            for spin in [&port, &timeout, &max_retries, &base_delay] {
+                spin.update();
+            }
+
+            let url = url_row.text().trim().trim_end_matches('/').to_string();
+            if let Err(e) = check_backend_url(&url) {
+                show_error(&error_label, &format!("Backend URL: {:#}", e));
+                return;
+            }
+
+            let theme = Theme::ALL
+                .get(theme_row.selected() as usize)
+                .copied()
+                .unwrap_or_default();
+
+            // Start from the file as it is now so settings edited elsewhere aren't lost
+            let result = config_manager.load().and_then(|mut config| {
+                config.core.backend.url = url;
+                config.core.backend.port = port.value_as_int() as u16;
+                config.core.backend.timeout_secs = timeout.value_as_int() as u64;
+                config.restart_policy.max_retries = max_retries.value_as_int() as u32;
+                config.restart_policy.base_delay_ms = base_delay.value_as_int() as u64;
+                config.ui.theme = theme;
+                config.validate()?;
+                Ok(config)
+            });
+            let config = match result {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to save settings: {:#}", e);
+                    show_error(&error_label, &format!("Could not save settings: {:#}", e));
+                    return;
+                }
+            };
+
+            // Saved and then checked against the backend, which may now be elsewhere
+            save_button.set_sensitive(false);
+            let request = {
+                let server_manager = server_manager.clone();
+                runtime.spawn(async move { server_manager.apply_config(config).await })
+            };
+            glib::spawn_future_local(glib::clone!(
+                @weak parent,
+                @weak window,
+                @weak error_label,
+                @weak save_button,
+                @strong config_manager
+                => async move {
+                    let error = match request.await {
+                        Ok(Ok(outcome)) => {
+                            info!("Settings saved");
+                            apply_theme(theme);
+                            window.close();
+                            if let ApplyOutcome::Unreachable { previous, error } = outcome {
+                                offer_rollback(&parent, config_manager, previous, &error);
+                            }
+                            return;
+                        }
+                        Ok(Err(e)) => format!("{:#}", e),
+                        Err(e) => format!("The settings task failed: {}", e),
+                    };
+                    error!("Failed to save settings: {}", error);
+                    show_error(&error_label, &format!("Could not save settings: {}", error));
+                    save_button.set_sensitive(true);
+                }
+            ));
+        }
+    ));
+
+    window.add(&page);
+
+    if !config_manager.is_safe_mode() {
+        window.add(&api_keys_page(&window, &config, &runtime));
+    }
+    window.add(&test_completion_page(&config, server_manager, &runtime));
+
+    window.present();
+}
+
+/// Start at login switches; unlike the rest of the page they apply right away
+fn startup_group(error_label: &Label) -> PreferencesGroup {
+    let group = PreferencesGroup::builder()
+        .title("Startup")
+        .description("Starts minimized to the tray. Changes apply immediately.")
+        .build();
+
+    for (title, method) in [
+        ("Start at login", autostart::Method::DesktopEntry),
+        ("Run as a systemd user service", autostart::Method::SystemdUnit),
+    ] {
+        let switch = Switch::builder()
+            .active(method.is_enabled())
+            .valign(Align::Center)
+            .build();
+        switch.connect_active_notify(glib::clone!(@weak error_label => move |switch| {
+            let enable = switch.is_active();
+            // Also skips the notify from reverting after a failure
+            if method.is_enabled() == enable {
+                return;
+            }
+            let result = if enable {
+                method.enable().map(drop)
+            } else {
+                method.disable()
+            };
+            if let Err(e) = result {
+                error!("Failed to change autostart: {:#}", e);
+                show_error(&error_label, &format!("Autostart: {:#}", e));
+                switch.set_active(method.is_enabled());
+            }
+        }));
+
+        let row = ActionRow::builder().title(title).build();
+        row.add_suffix(&switch);
+        row.set_activatable_widget(Some(&switch));
+        group.add(&row);
+    }
+    group
+}
+
+/// An integer spin button row added to `group`
+fn spin_row(group: &PreferencesGroup, title: &str, value: f64, min: f64, max: f64) -> SpinButton {
+    let spin = SpinButton::with_range(min, max, 1.0);
+    spin.set_numeric(true);
+    spin.set_value(value);
+    spin.set_valign(Align::Center);
+
+    let row = ActionRow::builder().title(title).build();
+    row.add_suffix(&spin);
+    row.set_activatable_widget(Some(&spin));
+    group.add(&row);
+    spin
+}
+
+fn show_error(label: &Label, message: &str) {
+    label.set_label(message);
+    label.set_visible(true);
+}
@@ -0,0 +1,177 @@
+//! Test completion page of the settings window
+
+use super::show_error;
+use adw::prelude::*;
+use adw::{ComboRow, EntryRow, PreferencesGroup, PreferencesPage};
+use gtk::{glib, Align, Button, Label, ScrolledWindow, StringList, TextView, WrapMode};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::server_manager::ServerManager;
+
+/// Send a prompt through the backend and show the reply as it streams in
+///
+/// Offers the models the backend lists, or those in the config if it can't
+/// list them. Errors are shown as the provider sent them.
+pub(super) fn test_completion_page(
+    config: &Config,
+    server_manager: Arc<ServerManager>,
+    runtime: &Handle,
+) -> PreferencesPage {
+    let page = PreferencesPage::builder()
+        .title("Test")
+        .icon_name("mail-send-symbolic")
+        .build();
+
+    let group = PreferencesGroup::builder()
+        .title("Test Completion")
+        .description("Sends one chat message through the backend; providers may charge for it")
+        .build();
+    let model_list = StringList::new(&[]);
+    let model_row = ComboRow::builder()
+        .title("Model")
+        .model(&model_list)
+        .subtitle("Loading models…")
+        .build();
+    let prompt_row = EntryRow::builder()
+        .title("Prompt")
+        .text("Reply with one short sentence.")
+        .build();
+    let send_button = Button::builder()
+        .label("Send")
+        .css_classes(&["suggested-action"])
+        .halign(Align::End)
+        .margin_top(6)
+        .sensitive(false)
+        .build();
+    group.add(&model_row);
+    group.add(&prompt_row);
+    group.add(&send_button);
+    page.add(&group);
+
+    let error_label = Label::builder()
+        .css_classes(&["error"])
+        .wrap(true)
+        .selectable(true)
+        .xalign(0.0)
+        .visible(false)
+        .build();
+    let output = TextView::builder()
+        .editable(false)
+        .cursor_visible(false)
+        .monospace(true)
+        .wrap_mode(WrapMode::WordChar)
+        .top_margin(6)
+        .bottom_margin(6)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    let scrolled = ScrolledWindow::builder()
+        .child(&output)
+        .min_content_height(160)
+        .css_classes(&["card"])
+        .build();
+    let reply = PreferencesGroup::builder().title("Reply").build();
+    reply.add(&error_label);
+    reply.add(&scrolled);
+    page.add(&reply);
+
+    let configured: Vec<String> = config
+        .providers
+        .iter()
+        .flat_map(|provider| provider.models.iter().cloned())
+        .collect();
+    let request = runtime.spawn({
+        let server_manager = server_manager.clone();
+        async move { server_manager.models(false).await }
+    });
+    glib::spawn_future_local(glib::clone!(@weak model_row, @weak send_button => async move {
+        let (mut listed, note): (Vec<String>, _) = match request.await {
+            Ok(Ok(Some(models))) => (models.into_iter().map(|m| m.model).collect(), None),
+            Ok(Ok(None)) => (configured, Some("The backend doesn't list its models")),
+            Ok(Err(e)) => {
+                error!("Failed to list models: {:#}", e);
+                (configured, Some("Couldn't ask the backend for its models"))
+            }
+            Err(e) => {
+                error!("Model listing task failed: {}", e);
+                (configured, Some("Couldn't ask the backend for its models"))
+            }
+        };
+        listed.sort();
+        listed.dedup();
+        let names: Vec<&str> = listed.iter().map(String::as_str).collect();
+        model_list.splice(0, 0, &names);
+
+        let subtitle = match (note, listed.is_empty()) {
+            (_, true) => "No models to choose from".to_string(),
+            (Some(note), false) => format!("{}, showing the configured ones", note),
+            (None, false) => String::new(),
+        };
+        model_row.set_subtitle(&subtitle);
+        send_button.set_sensitive(!listed.is_empty());
+    }));
+
+    let runtime = runtime.clone();
+    send_button.connect_clicked(glib::clone!(
+        @weak model_row,
+        @weak prompt_row,
+        @weak output,
+        @weak error_label
+        => move |button| {
+            let Some(model) = model_row
+                .selected_item()
+                .and_downcast::<gtk::StringObject>()
+                .map(|item| item.string().to_string())
+            else {
+                return;
+            };
+            let prompt = prompt_row.text().trim().to_string();
+            if prompt.is_empty() {
+                show_error(&error_label, "Enter a prompt");
+                return;
+            }
+
+            output.buffer().set_text("");
+            error_label.set_visible(false);
+            button.set_sensitive(false);
+
+            // Pieces of the reply travel to the UI thread as they arrive
+            let (pieces, mut received) = mpsc::unbounded_channel::<String>();
+            let server_manager = server_manager.clone();
+            let request = runtime.spawn(async move {
+                server_manager
+                    .test_completion(&model, &prompt, |text| {
+                        let _ = pieces.send(text.to_string());
+                    })
+                    .await
+            });
+
+            glib::spawn_future_local(glib::clone!(
+                @weak button,
+                @weak output,
+                @weak error_label
+                => async move {
+                    // Ends when the request finishes and drops the sender
+                    while let Some(text) = received.recv().await {
+                        let buffer = output.buffer();
+                        buffer.insert(&mut buffer.end_iter(), &text);
+                    }
+                    match request.await {
+                        Ok(Ok(())) => info!("Test completion finished"),
+                        Ok(Err(e)) => {
+                            error!("Test completion failed: {:#}", e);
+                            show_error(&error_label, &format!("{:#}", e));
+                        }
+                        Err(e) => error!("Test completion task failed: {}", e),
+                    }
+                    button.set_sensitive(true);
+                }
+            ));
+        }
+    ));
+
+    page
+}
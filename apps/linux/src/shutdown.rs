@@ -0,0 +1,84 @@
+//! Ordered shutdown of background subsystems
+//!
+//! Subsystems register an async cleanup hook as they start. On quit the hooks
+//! run one at a time in reverse registration order, so something started
+//! later (and possibly depending on earlier subsystems) stops first. Each hook
+//! gets its own timeout; a hook that overruns is logged and skipped.
+
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+type HookFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+struct Hook {
+    name: String,
+    timeout: Duration,
+    run: Box<dyn FnOnce() -> HookFuture + Send>,
+}
+
+#[derive(Default)]
+pub struct ShutdownHooks {
+    hooks: Mutex<Vec<Hook>>,
+}
+
+impl ShutdownHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `hook` on shutdown, giving up on it after `timeout`
+    pub fn register<F, Fut>(&self, name: impl Into<String>, timeout: Duration, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.hooks.lock().unwrap().push(Hook {
+            name: name.into(),
+            timeout,
+            run: Box::new(move || Box::pin(hook())),
+        });
+    }
+
+    /// Run every registered hook, newest first; hooks only ever run once
+    pub async fn run(&self) {
+        let hooks = std::mem::take(&mut *self.hooks.lock().unwrap());
+
+        for hook in hooks.into_iter().rev() {
+            match tokio::time::timeout(hook.timeout, (hook.run)()).await {
+                Ok(Ok(())) => info!("Shutdown: {} stopped", hook.name),
+                Ok(Err(e)) => warn!("Shutdown: {} failed: {:#}", hook.name, e),
+                Err(_) => warn!("Shutdown: {} timed out after {:?}", hook.name, hook.timeout),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_hooks_run_in_reverse_and_time_out() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let hooks = ShutdownHooks::new();
+
+        for name in ["first", "second"] {
+            let order = order.clone();
+            hooks.register(name, Duration::from_secs(1), move || async move {
+                order.lock().unwrap().push(name);
+                Ok(())
+            });
+        }
+        hooks.register("stuck", Duration::from_millis(10), || {
+            std::future::pending::<Result<()>>()
+        });
+
+        hooks.run().await;
+        assert_eq!(*order.lock().unwrap(), ["second", "first"]);
+    }
+}
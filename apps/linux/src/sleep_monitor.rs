@@ -0,0 +1,43 @@
+//! System suspend/resume notifications
+//!
+//! logind broadcasts `PrepareForSleep(true)` on the system bus just before
+//! the machine suspends and `PrepareForSleep(false)` once it is back. The
+//! status poller pauses in between, so a closed lid isn't reported as a
+//! dead backend.
+
+use crate::server_manager::ServerManager;
+use anyhow::{Context, Result};
+use futures_util::StreamExt;
+use std::sync::Weak;
+use tracing::debug;
+
+const LOGIN1_SERVICE: &str = "org.freedesktop.login1";
+const LOGIN1_PATH: &str = "/org/freedesktop/login1";
+const LOGIN1_MANAGER: &str = "org.freedesktop.login1.Manager";
+
+/// Forward logind sleep notifications to `manager` until it is dropped
+///
+/// Fails if there is no system bus or logind, e.g. in a container.
+pub async fn run(manager: Weak<ServerManager>) -> Result<()> {
+    let connection = zbus::Connection::system()
+        .await
+        .context("Failed to connect to the system bus")?;
+    let proxy = zbus::Proxy::new(&connection, LOGIN1_SERVICE, LOGIN1_PATH, LOGIN1_MANAGER)
+        .await
+        .context("Failed to reach logind")?;
+    let mut signals = proxy
+        .receive_signal("PrepareForSleep")
+        .await
+        .context("Failed to subscribe to sleep notifications")?;
+
+    while let Some(message) = signals.next().await {
+        let Some(manager) = manager.upgrade() else {
+            break;
+        };
+        match message.body::<bool>() {
+            Ok(sleeping) => manager.set_suspended(sleeping),
+            Err(e) => debug!("Ignoring malformed PrepareForSleep signal: {}", e),
+        }
+    }
+    Ok(())
+}
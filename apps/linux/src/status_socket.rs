@@ -0,0 +1,79 @@
+//! Compact JSON status over a Unix socket
+//!
+//! Each connection gets the last known [`ServerStatus`] as one line of JSON
+//! and is closed, so local scripts can poll with `socat - UNIX-CONNECT:<path>`
+//! or `nc -U <path>` instead of going through HTTP. Answering doesn't contact
+//! the backend; the status is as fresh as the app's own health checks.
+
+use crate::config::StatusSocketSettings;
+use crate::server_manager::ServerStatus;
+use anyhow::{Context, Result};
+use directories::BaseDirs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// `$XDG_RUNTIME_DIR/vibeproxy/status.sock`, if the session has a runtime dir
+pub fn default_path() -> Option<PathBuf> {
+    let dirs = BaseDirs::new()?;
+    Some(dirs.runtime_dir()?.join("vibeproxy").join("status.sock"))
+}
+
+/// Socket path for `settings`, `None` when disabled or no path is available
+pub fn socket_path(settings: &StatusSocketSettings) -> Option<PathBuf> {
+    if !settings.enabled {
+        return None;
+    }
+    settings.path.clone().or_else(default_path)
+}
+
+/// Bind the socket and answer connections with the latest of `statuses`
+/// until the task is dropped
+pub async fn serve(statuses: watch::Receiver<Option<ServerStatus>>, path: PathBuf) -> Result<()> {
+    let listener = bind(&path)?;
+    info!("Status socket listening on {:?}", path);
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .context("Status socket accept failed")?;
+        let status = statuses.borrow().clone();
+        tokio::spawn(async move {
+            if let Err(e) = respond(stream, status).await {
+                warn!("Status socket client: {}", e);
+            }
+        });
+    }
+}
+
+fn bind(path: &Path) -> Result<UnixListener> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    }
+    // A socket left behind by a previous run would make bind fail
+    if path.exists() {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {:?}", path))?;
+    }
+
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Failed to bind status socket {:?}", path))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("Failed to restrict permissions on {:?}", path))?;
+    Ok(listener)
+}
+
+async fn respond(mut stream: UnixStream, status: Option<ServerStatus>) -> Result<()> {
+    let status = status
+        .unwrap_or_else(|| ServerStatus::unavailable("No health check yet".to_string()));
+
+    let mut line = serde_json::to_vec(&status)?;
+    line.push(b'\n');
+    stream.write_all(&line).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
@@ -1,46 +1,139 @@
 //! System tray implementation using libappindicator
 
-use crate::config_manager::ConfigManager;
-use crate::server_manager::ServerManager;
+use crate::ui::status_text;
 use anyhow::{Context, Result};
+use gtk::gio::prelude::*;
+use gtk::{gio, glib, Application};
 use libappindicator::{AppIndicator, AppIndicatorStatus};
+use std::cell::RefCell;
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::sync::Arc;
+use tokio::sync::watch;
 use tracing::{error, info};
+use vibeproxy_linux::backend_api::DiscoveredModel;
+use vibeproxy_linux::branding::Branding;
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::health::HealthLevel;
+use vibeproxy_linux::server_manager::{ServerStatus, Transition};
+
+/// Bus name of the StatusNotifier host that displays AppIndicator icons
+const STATUS_NOTIFIER_WATCHER: &str = "org.kde.StatusNotifierWatcher";
 
 pub struct SystemTray {
-    indicator: AppIndicator,
+    indicator: Rc<RefCell<AppIndicator>>,
     config_manager: Arc<ConfigManager>,
-    server_manager: Arc<ServerManager>,
+    app: Application,
+    statuses: watch::Receiver<Option<ServerStatus>>,
+    /// Whether the backend is started, shared with the main window's buttons
+    running: watch::Receiver<bool>,
+    /// Start or stop under way, which makes the Start/Stop item insensitive
+    transitions: watch::Receiver<Option<Transition>>,
+    /// Config reloads, `None` if the file isn't watched
+    configs: Option<watch::Receiver<Config>>,
+    models: watch::Receiver<Option<Option<Vec<DiscoveredModel>>>>,
+    /// Whether the icon follows the backend's health
+    state_icons: bool,
 }
 
 impl SystemTray {
+    /// `statuses` drives the status line and the icon, `running` and `transitions` the
+    /// Start/Stop item, `configs` and `models` the Profile and Providers submenus
     pub fn new(
         config_manager: Arc<ConfigManager>,
-        server_manager: Arc<ServerManager>,
+        app: Application,
+        statuses: watch::Receiver<Option<ServerStatus>>,
+        running: watch::Receiver<bool>,
+        transitions: watch::Receiver<Option<Transition>>,
+        configs: Option<watch::Receiver<Config>>,
+        models: watch::Receiver<Option<Option<Vec<DiscoveredModel>>>>,
     ) -> Result<Self> {
         // Create AppIndicator
-        let mut indicator = AppIndicator::new("vibeproxy", "icon");
+        let branding = Branding::current();
+        let mut indicator = AppIndicator::new(&branding.application_id, &branding.icon_name);
         indicator.set_status(AppIndicatorStatus::Active);
 
         Ok(Self {
-            indicator,
+            indicator: Rc::new(RefCell::new(indicator)),
             config_manager,
-            server_manager,
+            app,
+            statuses,
+            running,
+            transitions,
+            configs,
+            models,
+            state_icons: false,
         })
     }
 
+    /// Best-effort check that something will actually display the indicator
+    ///
+    /// libappindicator doesn't report failure: without a StatusNotifier
+    /// watcher on the session bus (stock GNOME, for one) the icon just never
+    /// shows up.
+    pub fn host_available() -> bool {
+        let Ok(connection) = gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE)
+        else {
+            return false;
+        };
+
+        connection
+            .call_sync(
+                Some("org.freedesktop.DBus"),
+                "/org/freedesktop/DBus",
+                "org.freedesktop.DBus",
+                "NameHasOwner",
+                Some(&(STATUS_NOTIFIER_WATCHER,).to_variant()),
+                Some(glib::VariantTy::new("(b)").unwrap()),
+                gio::DBusCallFlags::NONE,
+                1000,
+                gio::Cancellable::NONE,
+            )
+            .ok()
+            .and_then(|reply| reply.get::<(bool,)>())
+            .is_some_and(|(owned,)| owned)
+    }
+
+    /// Call `changed` whenever a tray host starts or goes away, e.g. a panel restart
+    ///
+    /// Also called once right away with the current state.
+    pub fn watch_host(changed: impl Fn(bool) + 'static) {
+        let changed = Rc::new(changed);
+        let appeared = changed.clone();
+        gio::bus_watch_name(
+            gio::BusType::Session,
+            STATUS_NOTIFIER_WATCHER,
+            gio::BusNameWatcherFlags::NONE,
+            move |_, _, _| appeared(true),
+            move |_, _| changed(false),
+        );
+    }
+
     pub fn setup(&mut self) -> Result<()> {
         info!("Setting up system tray");
 
         // Set icon (fallback to default if not found)
-        let icon_path = self.find_icon_path();
-        if let Some(path) = icon_path {
-            self.indicator.set_icon_full(&path, "VibeProxy");
-        } else {
-            // Use default icon name (system will find it)
-            self.indicator.set_icon("application-default-icon");
+        let branding = Branding::current();
+        let icon_path = self.find_icon_path(branding);
+        let mut indicator = self.indicator.borrow_mut();
+        // A branded icon is kept as is; otherwise the icon shows the backend's health
+        match Self::find_state_icon_dir().filter(|_| branding.icon_path.is_none()) {
+            Some(dir) => {
+                indicator.set_icon_theme_path(&dir.to_string_lossy());
+                indicator.set_icon_full(
+                    &state_icon_name(HealthLevel::Unknown),
+                    HealthLevel::Unknown.label(),
+                );
+                self.state_icons = true;
+            }
+            None => match icon_path {
+                Some(path) => indicator.set_icon_full(&path.to_string_lossy(), &branding.app_name),
+                // Use default icon name (system will find it)
+                None => indicator.set_icon("application-default-icon"),
+            },
         }
+        drop(indicator);
 
         // Create menu
         self.create_menu()?;
@@ -49,22 +142,34 @@ impl SystemTray {
         Ok(())
     }
 
-    fn find_icon_path(&self) -> Option<PathBuf> {
+    fn find_icon_path(&self, branding: &Branding) -> Option<PathBuf> {
+        if let Some(path) = &branding.icon_path {
+            return Some(path.clone());
+        }
+
         // Try common icon locations
         let possible_paths = vec![
-            "/usr/share/pixmaps/vibeproxy.png",
-            "/usr/share/icons/hicolor/48x48/apps/vibeproxy.png",
-            "./resources/icon.png",
-            "../resources/icon.png",
+            PathBuf::from(format!("/usr/share/pixmaps/{}.png", branding.icon_name)),
+            PathBuf::from(format!(
+                "/usr/share/icons/hicolor/48x48/apps/{}.png",
+                branding.icon_name
+            )),
+            PathBuf::from("./resources/icon.png"),
+            PathBuf::from("../resources/icon.png"),
         ];
 
-        for path in possible_paths {
-            if std::path::Path::new(path).exists() {
-                return Some(PathBuf::from(path));
-            }
-        }
+        possible_paths.into_iter().find(|path| path.exists())
+    }
 
-        None
+    /// Directory with the `vibeproxy-<level>.svg` health icons
+    fn find_state_icon_dir() -> Option<PathBuf> {
+        [
+            PathBuf::from("/usr/share/vibeproxy/tray"),
+            PathBuf::from("./resources/tray"),
+            PathBuf::from("../resources/tray"),
+        ]
+        .into_iter()
+        .find(|dir| dir.join(format!("{}.svg", state_icon_name(HealthLevel::Unknown))).exists())
     }
 
     fn create_menu(&mut self) -> Result<()> {
@@ -74,11 +179,12 @@ impl SystemTray {
         let menu = Menu::new();
 
         // Show Window
+        // Items activate the same app actions the window shortcuts use
         let show_item = MenuItem::with_label("Show Window");
-        let server_manager = self.server_manager.clone();
+        let app = self.app.clone();
         show_item.connect_activate(move |_| {
-            // TODO: Show main window
             info!("Show window requested");
+            app.activate_action("show-window", None);
         });
         menu.append(&show_item);
 
@@ -86,43 +192,263 @@ impl SystemTray {
         menu.append(&gtk::SeparatorMenuItem::new());
 
         // Server status
-        let status_item = MenuItem::with_label("Server: Stopped");
+        let health = self
+            .config_manager
+            .load()
+            .map(|config| config.health)
+            .unwrap_or_default();
+        // Without polling the line only changes on a start, stop or refresh; say so
+        let suffix = if health.polling_enabled { "" } else { " (polling disabled)" };
+        let running_label = move |running: bool| {
+            format!("Server: {}{}", if running { "Running" } else { "Stopped" }, suffix)
+        };
+        let status_item = MenuItem::with_label(&running_label(*self.running.borrow()));
+        status_item.set_sensitive(false);
         menu.append(&status_item);
 
         // Start/Stop Server
         let toggle_item = MenuItem::with_label("Start Server");
-        let server_manager_clone = self.server_manager.clone();
-        let status_item_clone = status_item.clone();
-        let toggle_item_clone = toggle_item.clone();
+        let app = self.app.clone();
         toggle_item.connect_activate(move |_| {
-            // Note: This requires a runtime handle - we'll need to pass it through
-            // For now, this is a placeholder that will be fixed when integrating with app
-            info!("Toggle server requested (requires runtime integration)");
+            info!("Toggle server requested");
+            app.activate_action("toggle-server", None);
         });
         menu.append(&toggle_item);
 
+        // Same running state as the main window's Start/Stop buttons
+        let mut running = self.running.clone();
+        glib::spawn_future_local(glib::clone!(
+            @weak status_item,
+            @weak toggle_item
+            => async move {
+                while running.changed().await.is_ok() {
+                    let running = *running.borrow_and_update();
+                    toggle_item.set_label(if running { "Stop Server" } else { "Start Server" });
+                    status_item.set_label(&running_label(running));
+                }
+            }
+        ));
+
+        // Shows the start or stop under way instead of offering the opposite
+        let mut transitions = self.transitions.clone();
+        let running = self.running.clone();
+        glib::spawn_future_local(glib::clone!(@weak toggle_item => async move {
+            while transitions.changed().await.is_ok() {
+                let transition = *transitions.borrow_and_update();
+                toggle_item.set_sensitive(transition.is_none());
+                match transition {
+                    Some(transition) => toggle_item.set_label(transition.label()),
+                    None if *running.borrow() => toggle_item.set_label("Stop Server"),
+                    None => toggle_item.set_label("Start Server"),
+                }
+            }
+        }));
+
+        // Follow the same status updates as the main window
+        let mut statuses = self.statuses.clone();
+        let indicator = self.state_icons.then(|| self.indicator.clone());
+        glib::spawn_future_local(glib::clone!(
+            @weak status_item
+            => async move {
+                while statuses.changed().await.is_ok() {
+                    if let Some(status) = statuses.borrow_and_update().as_ref() {
+                        status_item
+                            .set_label(&format!("Server: {}{}", status_text(status), suffix));
+                        if let Some(indicator) = &indicator {
+                            let level = HealthLevel::classify(
+                                Some(status.running),
+                                status.latency_ms,
+                                &health,
+                            );
+                            indicator
+                                .borrow_mut()
+                                .set_icon_full(&state_icon_name(level), level.label());
+                        }
+                    }
+                }
+            }
+        ));
+
+        // Backend profiles and providers, hidden until there are any
+        let profile_item = MenuItem::with_label("Profile");
+        menu.append(&profile_item);
+        let provider_item = MenuItem::with_label("Providers");
+        menu.append(&provider_item);
+
         // Separator
         menu.append(&gtk::SeparatorMenuItem::new());
 
         // Settings
         let settings_item = MenuItem::with_label("Settings");
-        settings_item.connect_activate(|_| {
-            // TODO: Open settings window
+        let app = self.app.clone();
+        settings_item.connect_activate(move |_| {
             info!("Settings requested");
+            app.activate_action("settings", None);
         });
         menu.append(&settings_item);
 
+        // Backend Logs
+        let logs_item = MenuItem::with_label("Backend Logs");
+        let app = self.app.clone();
+        logs_item.connect_activate(move |_| {
+            info!("Backend logs requested");
+            app.activate_action("show-logs", None);
+        });
+        menu.append(&logs_item);
+
+        // Reload Credentials
+        let reload_item = MenuItem::with_label("Reload Credentials");
+        let app = self.app.clone();
+        reload_item.connect_activate(move |_| {
+            info!("Credential reload requested");
+            app.activate_action("reload-credentials", None);
+        });
+        menu.append(&reload_item);
+
+        // About
+        let about_item = MenuItem::with_label(&format!("About {}", Branding::current().app_name));
+        let app = self.app.clone();
+        about_item.connect_activate(move |_| {
+            app.activate_action("about", None);
+        });
+        menu.append(&about_item);
+
         // Quit
         let quit_item = MenuItem::with_label("Quit");
-        quit_item.connect_activate(|_| {
-            info!("Quit requested");
-            gtk::main_quit();
+        let app = self.app.clone();
+        quit_item.connect_activate(move |_| {
+            app.activate_action("quit", None);
         });
         menu.append(&quit_item);
 
         menu.show_all();
-        self.indicator.set_menu(&menu);
+
+        // After show_all, which would reveal the submenus hidden while empty
+        let rebuild = glib::clone!(
+            @weak profile_item,
+            @weak provider_item,
+            @strong self.app as app
+            => move |config: &Config, models: Option<&[DiscoveredModel]>| {
+                set_submenu(&profile_item, profile_menu(&app, config));
+                set_submenu(&provider_item, provider_menu(&app, config, models));
+            }
+        );
+        let config = self.config_manager.load().unwrap_or_default();
+        rebuild(&config, self.models.borrow().clone().flatten().as_deref());
+
+        // Follow profile switches, provider toggles, hand edits and model refreshes.
+        // Unwatched, the config stays as loaded: the stand-in's sender is gone.
+        let mut configs = self
+            .configs
+            .clone()
+            .unwrap_or_else(|| watch::channel(config).1);
+        let mut models = self.models.clone();
+        glib::spawn_future_local(async move {
+            loop {
+                tokio::select! {
+                    Ok(()) = configs.changed() => {}
+                    Ok(()) = models.changed() => {}
+                    else => break,
+                }
+                let config = configs.borrow_and_update().clone();
+                let listing = models.borrow_and_update().clone().flatten();
+                rebuild(&config, listing.as_deref());
+            }
+        });
+
+        self.indicator.borrow_mut().set_menu(&menu);
 
         Ok(())
     }
 }
+
+/// Radio items for the backend profiles, `None` if there are none
+///
+/// "Custom" is checked while the backend isn't one of the profiles.
+fn profile_menu(app: &Application, config: &Config) -> Option<gtk::Menu> {
+    use gtk::prelude::*;
+    use gtk::RadioMenuItem;
+
+    if config.profiles.is_empty() {
+        return None;
+    }
+    let menu = gtk::Menu::new();
+    let custom_item = RadioMenuItem::with_label("Custom");
+    custom_item.set_sensitive(false);
+    menu.append(&custom_item);
+    for name in config.profiles.keys() {
+        let item = RadioMenuItem::with_label_from_widget(&custom_item, Some(name));
+        // Before connecting, so checking the active one doesn't switch to it again
+        item.set_active(config.active_profile.as_ref() == Some(name));
+        let app = app.clone();
+        let name = name.clone();
+        item.connect_toggled(move |item| {
+            if item.is_active() {
+                info!("Profile '{}' requested", name);
+                app.activate_action("select-profile", Some(&name.to_variant()));
+            }
+        });
+        menu.append(&item);
+    }
+    Some(menu)
+}
+
+/// Check items for routing to each provider, `None` if there are none
+///
+/// Providers the backend listed models for show how many.
+fn provider_menu(
+    app: &Application,
+    config: &Config,
+    models: Option<&[DiscoveredModel]>,
+) -> Option<gtk::Menu> {
+    use gtk::prelude::*;
+    use gtk::CheckMenuItem;
+
+    if config.providers.is_empty() {
+        return None;
+    }
+    let menu = gtk::Menu::new();
+    for provider in &config.providers {
+        let count = models.map_or(0, |models| {
+            models.iter().filter(|model| model.provider == provider.id).count()
+        });
+        let label = match count {
+            0 => provider.id.clone(),
+            1 => format!("{} (1 model)", provider.id),
+            n => format!("{} ({} models)", provider.id, n),
+        };
+        let item = CheckMenuItem::with_label(&label);
+        item.set_active(provider.enabled);
+        let app = app.clone();
+        let id = provider.id.clone();
+        item.connect_toggled(move |item| {
+            info!("Provider '{}' toggle requested", id);
+            let parameter = (id.as_str(), item.is_active()).to_variant();
+            app.activate_action("set-provider-enabled", Some(&parameter));
+        });
+        menu.append(&item);
+    }
+    Some(menu)
+}
+
+/// Show `item` with `submenu`, or hide it when there's nothing to pick from
+fn set_submenu(item: &gtk::MenuItem, submenu: Option<gtk::Menu>) {
+    use gtk::prelude::*;
+
+    match submenu {
+        Some(submenu) => {
+            submenu.show_all();
+            item.set_submenu(Some(&submenu));
+            item.show();
+        }
+        None => {
+            item.set_submenu(None::<&gtk::Menu>);
+            item.hide();
+        }
+    }
+}
+
+/// Icon for `level` in the state icon directory, e.g. `vibeproxy-degraded`
+fn state_icon_name(level: HealthLevel) -> String {
+    format!("vibeproxy-{}", level.label().to_lowercase())
+}
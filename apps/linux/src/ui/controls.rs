@@ -0,0 +1,60 @@
+//! Start and stop controls of the main window
+
+use gtk::prelude::*;
+use gtk::{glib, Box, Button, Label, Orientation};
+use std::sync::Arc;
+use vibeproxy_linux::server_manager::ServerManager;
+
+/// Start and Stop buttons with a spinner, reporting to `server_status`
+///
+/// The buttons trigger `app.start-server` and `app.stop-server`, which the
+/// application enables from the manager's state; the window only shows a
+/// start or stop from anywhere while it is under way.
+pub(super) fn server_controls(server_status: &Label, server_manager: &Arc<ServerManager>) -> Box {
+    let button_box = Box::new(Orientation::Horizontal, 6);
+
+    let start_button = Button::builder()
+        .label("Start Server")
+        .action_name("app.start-server")
+        .build();
+    let stop_button = Button::builder()
+        .label("Stop Server")
+        .action_name("app.stop-server")
+        .build();
+
+    // Shown while a start or stop is in flight
+    let spinner = gtk::Spinner::builder().visible(false).build();
+
+    button_box.append(&start_button);
+    button_box.append(&stop_button);
+    button_box.append(&spinner);
+
+    // Starts and stops from the buttons, the tray or at launch; the status
+    // updates published once they finish set the final label
+    let widgets = (spinner.downgrade(), server_status.downgrade());
+    let mut transitions = server_manager.transition_updates();
+    glib::spawn_future_local(async move {
+        loop {
+            let (Some(spinner), Some(server_status)) = (widgets.0.upgrade(), widgets.1.upgrade())
+            else {
+                break;
+            };
+            let pending = *transitions.borrow_and_update();
+            match pending {
+                Some(transition) => {
+                    spinner.start();
+                    server_status.set_label(transition.label());
+                }
+                None => spinner.stop(),
+            }
+            spinner.set_visible(pending.is_some());
+            drop((spinner, server_status));
+
+            if transitions.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    button_box
+}
@@ -0,0 +1,77 @@
+//! Estimated spend from the backend's metrics
+
+use gtk::prelude::*;
+use gtk::{glib, Box, Label, Orientation};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::{error, info};
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::server_manager::ServerManager;
+
+/// Share of the monthly budget at which the cost panel starts warning
+const BUDGET_WARNING_RATIO: f64 = 0.8;
+
+/// Estimated spend panel, filled in once the backend's metrics arrive
+pub(super) fn cost_panel(
+    config: &Config,
+    server_manager: &Arc<ServerManager>,
+    runtime: &Handle,
+) -> Box {
+    let panel = Box::new(Orientation::Vertical, 6);
+    panel.set_visible(false);
+
+    panel.append(
+        &Label::builder()
+            .label("Estimated Cost")
+            .css_classes(&["title-2"])
+            .build(),
+    );
+    let today = Label::new(None);
+    let month = Label::new(None);
+    let budget = Label::new(None);
+    budget.set_visible(false);
+    panel.append(&today);
+    panel.append(&month);
+    panel.append(&budget);
+
+    let request = {
+        let server_manager = server_manager.clone();
+        runtime.spawn(async move { server_manager.usage_metrics().await })
+    };
+    let monthly_budget = config.ui.monthly_budget_usd;
+
+    glib::spawn_future_local(glib::clone!(@weak panel => async move {
+        let metrics = match request.await {
+            Ok(Ok(Some(metrics))) => metrics,
+            Ok(Ok(None)) => return,
+            Ok(Err(e)) => {
+                info!("Cost metrics unavailable: {}", e);
+                return;
+            }
+            Err(e) => {
+                error!("Cost metrics task failed: {}", e);
+                return;
+            }
+        };
+
+        let month_total = metrics.total_month_usd();
+        today.set_label(&format!("Today: ${:.2}", metrics.total_today_usd()));
+        month.set_label(&format!("This month: ${:.2}", month_total));
+
+        if let Some(limit) = monthly_budget.filter(|limit| *limit > 0.0) {
+            budget.set_label(&format!(
+                "{:.0}% of ${:.2} monthly budget",
+                month_total / limit * 100.0,
+                limit
+            ));
+            if month_total >= limit * BUDGET_WARNING_RATIO {
+                budget.add_css_class("warning");
+            }
+            budget.set_visible(true);
+        }
+
+        panel.set_visible(true);
+    }));
+
+    panel
+}
@@ -0,0 +1,196 @@
+//! Confirmations and error reports
+
+use adw::prelude::*;
+use adw::{MessageDialog, ResponseAppearance};
+use gtk::prelude::*;
+use gtk::{glib, Application};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::{error, info};
+use vibeproxy_core::ClientError;
+use vibeproxy_linux::backend_api::AuthError;
+use vibeproxy_linux::branding::Branding;
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::config_manager::{ConfigManager, InlineSecret};
+use vibeproxy_linux::secret_store;
+use vibeproxy_linux::server_manager::TimeoutError;
+
+/// Report a failed start or stop with its cause and, where one is known, what to try
+pub fn show_server_error(app: &Application, heading: &str, error: &anyhow::Error) {
+    let mut body = format!("{:#}", error);
+    if let Some(hint) = remediation(error) {
+        body.push_str("\n\n");
+        body.push_str(hint);
+    }
+    // The tray can fail a start with the window hidden; the dialog then stands alone
+    let dialog = MessageDialog::new(app.active_window().as_ref(), Some(heading), Some(&body));
+    dialog.add_responses(&[("logs", "Backend Logs"), ("close", "Close")]);
+    dialog.set_default_response(Some("close"));
+    dialog.set_close_response("close");
+    dialog.connect_response(
+        Some("logs"),
+        glib::clone!(@weak app => move |_, _| app.activate_action("show-logs", None)),
+    );
+    dialog.present();
+}
+
+/// Suggestion for backend client errors; other errors already say what went wrong
+///
+/// A rejected API key is told apart from a backend that didn't answer, and
+/// a backend that hung from one that refused the connection.
+fn remediation(error: &anyhow::Error) -> Option<&'static str> {
+    if let Some(auth) = error.chain().find_map(|cause| cause.downcast_ref::<AuthError>()) {
+        return Some(match auth {
+            AuthError::Unauthorized => {
+                "The backend is running but didn't accept the API key. Check backend.api_key \
+                 in the config file, or the key stored in the keyring, against the backend's."
+            }
+            AuthError::Forbidden => {
+                "The API key was accepted but isn't allowed to do this. Use a key with \
+                 admin rights on the backend."
+            }
+        });
+    }
+    if error.downcast_ref::<TimeoutError>().is_some() {
+        return Some(
+            "The backend accepted the connection but never answered, so it may be hung. \
+             Restart it and check Backend Logs; if it is only slow, raise \
+             client.request_timeout_ms in the config file.",
+        );
+    }
+    let client_error = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ClientError>())?;
+    Some(match client_error {
+        ClientError::Unavailable => {
+            "The backend didn't answer. Check the backend URL and port in Settings; \
+             if VibeProxy starts the backend, Backend Logs shows why it didn't come up."
+        }
+        _ => "The backend answered but the request failed. Backend Logs may show the cause.",
+    })
+}
+
+/// Offer to revert to `previous` after the backend was unreachable with new settings
+pub fn offer_rollback(
+    parent: &impl IsA<gtk::Window>,
+    config_manager: Arc<ConfigManager>,
+    previous: Config,
+    error: &str,
+) {
+    let dialog = MessageDialog::new(
+        Some(parent),
+        Some("Backend Unreachable"),
+        Some(&format!(
+            "The backend could not be reached with the new settings:\n{}\n\nRevert to the previous settings?",
+            error
+        )),
+    );
+    dialog.add_responses(&[("keep", "Keep New Settings"), ("revert", "Revert")]);
+    dialog.set_response_appearance("revert", ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("revert"));
+
+    dialog.connect_response(None, move |_, response| {
+        if response == "revert" {
+            match config_manager.save(&previous) {
+                Ok(()) => info!("Reverted to previous configuration"),
+                Err(e) => error!("Failed to revert configuration: {}", e),
+            }
+        }
+    });
+
+    dialog.present();
+}
+
+/// Ask before putting back the config as it was before the last save
+///
+/// The running app picks the restored file up like any other edit.
+pub fn confirm_restore_config(app: &Application, config_manager: Arc<ConfigManager>) {
+    let parent = app.active_window();
+    if config_manager.latest_backup().is_none() {
+        let dialog = MessageDialog::new(
+            parent.as_ref(),
+            Some("No Previous Settings"),
+            Some(
+                "There is no backup of the config file yet. One is kept each time the \
+                 settings change.",
+            ),
+        );
+        dialog.add_response("close", "Close");
+        dialog.present();
+        return;
+    }
+
+    let dialog = MessageDialog::new(
+        parent.as_ref(),
+        Some("Restore Previous Settings?"),
+        Some(
+            "The config file will be replaced with the version from before the last change. \
+             The current settings are kept as a backup, so restoring again brings them back.",
+        ),
+    );
+    dialog.add_responses(&[("cancel", "Cancel"), ("restore", "Restore")]);
+    dialog.set_response_appearance("restore", ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    dialog.connect_response(Some("restore"), move |dialog, _| {
+        if let Err(e) = config_manager.restore_previous() {
+            error!("Failed to restore previous configuration: {:#}", e);
+            let failed = MessageDialog::new(
+                dialog.transient_for().as_ref(),
+                Some("Could Not Restore Settings"),
+                Some(&format!("{:#}", e)),
+            );
+            failed.add_response("close", "Close");
+            failed.present();
+        }
+    });
+    dialog.present();
+}
+
+/// Ask before moving credentials found in the config file into the keyring
+pub fn offer_secret_migration(
+    parent: Option<&gtk::Window>,
+    config_manager: Arc<ConfigManager>,
+    runtime: Handle,
+    secrets: Vec<InlineSecret>,
+) {
+    let fields: Vec<String> = secrets.iter().map(|s| format!("• {}", s.path)).collect();
+    let dialog = MessageDialog::new(
+        parent,
+        Some("Move Secrets to Keyring?"),
+        Some(&format!(
+            "Your config file contains credentials in plain text:\n\n{}\n\n\
+             {} can store them in the system keyring and remove them from \
+             the file. A backup of the original file is kept next to it.",
+            fields.join("\n"),
+            Branding::current().app_name
+        )),
+    );
+    dialog.add_responses(&[("later", "Not Now"), ("migrate", "Move to Keyring")]);
+    dialog.set_response_appearance("migrate", ResponseAppearance::Suggested);
+
+    dialog.connect_response(None, move |_, response| {
+        if response != "migrate" {
+            return;
+        }
+        let config_manager = config_manager.clone();
+        let secrets = secrets.clone();
+        runtime.spawn(async move {
+            let result = async {
+                let settings = config_manager.load()?.keyring;
+                let keyring = secret_store::open(&settings).await?;
+                config_manager
+                    .migrate_inline_secrets(keyring.as_ref(), &secrets)
+                    .await
+            }
+            .await;
+            match result {
+                Ok(backup) => info!("Secrets moved to keyring, original config at {:?}", backup),
+                Err(e) => error!("Failed to move secrets to keyring: {:#}", e),
+            }
+        });
+    });
+
+    dialog.present();
+}
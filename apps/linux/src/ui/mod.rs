@@ -0,0 +1,311 @@
+//! Main window UI
+
+mod controls;
+mod cost;
+mod dialogs;
+mod models;
+mod provider_import;
+mod provider_settings;
+mod providers;
+mod routing_rules;
+mod status;
+mod switchers;
+mod troubleshooting;
+
+pub use dialogs::{
+    confirm_restore_config, offer_rollback, offer_secret_migration, show_server_error,
+};
+pub use provider_import::{choose_provider_import, confirm_provider_import};
+pub use status::status_text;
+pub use troubleshooting::{copy_diagnostics, show_about};
+
+use adw::prelude::*;
+use adw::{ApplicationWindow, Banner, ColorScheme, HeaderBar, StyleManager};
+use gtk::prelude::*;
+use gtk::{gio, glib, Application, Box, Button, Label, MenuButton, Orientation, ScrolledWindow};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::{info, warn};
+use vibeproxy_linux::branding::Branding;
+use vibeproxy_linux::config::{Theme, WindowState};
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::server_manager::ServerManager;
+
+pub struct MainWindow {
+    window: ApplicationWindow,
+    config_manager: Arc<ConfigManager>,
+    server_manager: Arc<ServerManager>,
+    runtime: Handle,
+}
+
+impl MainWindow {
+    pub fn new(
+        app: &Application,
+        config_manager: Arc<ConfigManager>,
+        server_manager: Arc<ServerManager>,
+        runtime: &Handle,
+        tray_available: Rc<Cell<bool>>,
+    ) -> Self {
+        info!("Creating main window");
+
+        // Create application window at the size it was left at
+        let state = config_manager
+            .load()
+            .map(|config| config.ui.window)
+            .unwrap_or_default();
+        let (width, height) = match largest_monitor() {
+            Some((max_width, max_height)) => state.clamped(max_width, max_height),
+            None => (state.width, state.height),
+        };
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title(Branding::current().app_name.as_str())
+            .default_width(width)
+            .default_height(height)
+            .maximized(state.maximized)
+            .build();
+
+        // Quitting from the tray or Ctrl+Q doesn't close the window first
+        app.connect_shutdown(glib::clone!(
+            @weak window,
+            @strong config_manager
+            => move |_| save_window_state(&window, &config_manager)
+        ));
+
+        // Keep running in the tray; only the tray's Quit ends the app
+        window.connect_close_request(glib::clone!(@strong config_manager => move |window| {
+            save_window_state(window, &config_manager);
+            let close_to_tray = config_manager
+                .load()
+                .map(|config| config.ui.close_to_tray)
+                .unwrap_or(true);
+            if tray_available.get() && close_to_tray {
+                window.set_visible(false);
+                glib::Propagation::Stop
+            } else {
+                glib::Propagation::Proceed
+            }
+        }));
+
+        // A provider file dropped on the window is imported like one picked from the menu
+        let drop_target =
+            gtk::DropTarget::new(gio::File::static_type(), gtk::gdk::DragAction::COPY);
+        drop_target.connect_drop(glib::clone!(
+            @weak window,
+            @strong config_manager,
+            @strong runtime
+            => @default-return false,
+            move |_, value, _, _| {
+                let Some(path) = value.get::<gio::File>().ok().and_then(|file| file.path()) else {
+                    return false;
+                };
+                let parent = Some(window.upcast_ref::<gtk::Window>());
+                confirm_provider_import(parent, config_manager.clone(), runtime.clone(), &path);
+                true
+            }
+        ));
+        window.add_controller(drop_target);
+
+        // Create header bar
+        let header = HeaderBar::new();
+        window.set_titlebar(Some(&header));
+
+        // Create main content
+        let content = Box::new(Orientation::Vertical, 12);
+        content.set_margin_start(12);
+        content.set_margin_end(12);
+        content.set_margin_top(12);
+        content.set_margin_bottom(12);
+
+        let config = config_manager.load().unwrap_or_default();
+
+        header.pack_start(&switchers::endpoint_switcher(
+            &window,
+            &config,
+            &config_manager,
+            &server_manager,
+            runtime,
+        ));
+        if let Some(switcher) = switchers::profile_switcher(app, &config) {
+            header.pack_start(&switcher);
+        }
+
+        // Server status section
+        let (status, server_status) =
+            status::status_section(app, &window, &config, &server_manager, runtime);
+        content.append(&status);
+
+        let refresh_button = Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .action_name("win.refresh")
+            .tooltip_text("Check status now (F5 or Ctrl+R)")
+            .build();
+        header.pack_end(&primary_menu_button());
+        header.pack_end(&refresh_button);
+
+        let logs_button = Button::builder()
+            .icon_name("utilities-terminal-symbolic")
+            .action_name("app.show-logs")
+            .tooltip_text("Backend logs")
+            .build();
+        header.pack_end(&logs_button);
+
+        // Server control buttons
+        content.append(&controls::server_controls(&server_status, &server_manager));
+
+        // Providers section
+        content.append(&providers::providers_section(
+            &window,
+            &config,
+            &config_manager,
+            &server_manager,
+            runtime,
+        ));
+
+        content.append(&routing_rules::routing_rules_panel(&config, &server_manager, runtime));
+        content.append(&models::models_panel(&config, &server_manager, runtime));
+
+        // Cost section, hidden unless the backend reports spend
+        if config.health.polling_enabled {
+            content.append(&cost::cost_panel(&config, &server_manager, runtime));
+        }
+
+        // Settings section
+        let settings_label = Label::builder()
+            .label("Settings")
+            .css_classes(&["title-2"])
+            .build();
+        content.append(&settings_label);
+
+        let settings_button = Button::builder()
+            .label("Open Settings")
+            .action_name("app.settings")
+            .build();
+        content.append(&settings_button);
+
+        // Add content to window
+        let scrolled = ScrolledWindow::builder().vexpand(true).build();
+        scrolled.set_child(Some(&content));
+
+        let root = Box::new(Orientation::Vertical, 0);
+        if config_manager.is_safe_mode() {
+            root.append(
+                &Banner::builder()
+                    .title("Safe mode — config file and keyring ignored, backend start disabled. Restart normally once settings are fixed.")
+                    .revealed(true)
+                    .build(),
+            );
+        }
+        root.append(&scrolled);
+        window.set_content(Some(&root));
+
+        info!("Main window created");
+
+        Self {
+            window,
+            config_manager,
+            server_manager,
+            runtime: runtime.clone(),
+        }
+    }
+
+    pub fn present(&self) {
+        self.window.present();
+    }
+
+    /// Whether the window still belongs to the application, i.e. wasn't destroyed
+    pub fn is_open(&self) -> bool {
+        let window = self.window.upcast_ref::<gtk::Window>();
+        self.window
+            .application()
+            .is_some_and(|app| app.windows().iter().any(|w| w == window))
+    }
+}
+
+/// Apply the configured light/dark preference to every window
+pub fn apply_theme(theme: Theme) {
+    let scheme = match theme {
+        Theme::System => ColorScheme::Default,
+        Theme::Light => ColorScheme::ForceLight,
+        Theme::Dark => ColorScheme::ForceDark,
+    };
+    StyleManager::default().set_color_scheme(scheme);
+}
+
+fn dim_label(text: &str) -> Label {
+    Label::builder()
+        .label(text)
+        .css_classes(&["dim-label"])
+        .xalign(0.0)
+        .wrap(true)
+        .build()
+}
+
+/// Width and height of the biggest connected monitor
+///
+/// The window isn't placed yet, so its own monitor is unknown; the biggest
+/// one still catches a size saved on a display that is gone now.
+fn largest_monitor() -> Option<(i32, i32)> {
+    let display = gtk::gdk::Display::default()?;
+    display
+        .monitors()
+        .iter::<gtk::gdk::Monitor>()
+        .flatten()
+        .map(|monitor| monitor.geometry())
+        .map(|geometry| (geometry.width(), geometry.height()))
+        .max_by_key(|(width, height)| width * height)
+}
+
+/// Remember the window's size and maximized state for the next launch
+///
+/// The default size follows the user's resizing but keeps the unmaximized
+/// size while maximized, which is what should come back on unmaximize.
+fn save_window_state(window: &ApplicationWindow, config_manager: &ConfigManager) {
+    let state = WindowState {
+        width: window.default_width(),
+        height: window.default_height(),
+        maximized: window.is_maximized(),
+    };
+    let Ok(mut config) = config_manager.load() else {
+        return;
+    };
+    if config.ui.window == state {
+        return;
+    }
+    config.ui.window = state;
+    if let Err(e) = config_manager.save(&config) {
+        warn!("Failed to save window size: {}", e);
+    }
+}
+
+/// Header menu with the window's keyboard-reachable actions
+fn primary_menu_button() -> MenuButton {
+    let server = gio::Menu::new();
+    server.append(Some("Start Server"), Some("app.start-server"));
+    server.append(Some("Stop Server"), Some("app.stop-server"));
+
+    let app = gio::Menu::new();
+    app.append(Some("Settings"), Some("app.settings"));
+    app.append(Some("Backend Logs"), Some("app.show-logs"));
+    app.append(Some("Copy Diagnostics"), Some("app.copy-diagnostics"));
+    app.append(Some("Import Provider File…"), Some("app.import-providers"));
+    app.append(Some("Restore Previous Settings…"), Some("app.restore-config"));
+    app.append(Some("About"), Some("app.about"));
+
+    let quit = gio::Menu::new();
+    quit.append(Some("Quit"), Some("app.quit"));
+
+    let menu = gio::Menu::new();
+    menu.append_section(None, &server);
+    menu.append_section(None, &app);
+    menu.append_section(None, &quit);
+
+    MenuButton::builder()
+        .icon_name("open-menu-symbolic")
+        .menu_model(&menu)
+        .primary(true)
+        .tooltip_text("Main menu")
+        .build()
+}
@@ -0,0 +1,104 @@
+//! Models the backend serves
+
+use super::dim_label;
+use gtk::prelude::*;
+use gtk::{glib, Box, Button, Label, Orientation};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::{error, info};
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::server_manager::ServerManager;
+
+/// The models the backend serves, with a button to ask again
+pub(super) fn models_panel(
+    config: &Config,
+    server_manager: &Arc<ServerManager>,
+    runtime: &Handle,
+) -> Box {
+    let panel = Box::new(Orientation::Vertical, 6);
+
+    let header = Box::new(Orientation::Horizontal, 6);
+    header.append(
+        &Label::builder()
+            .label("Models")
+            .css_classes(&["title-2"])
+            .hexpand(true)
+            .xalign(0.0)
+            .build(),
+    );
+    let refresh_button = Button::builder()
+        .icon_name("view-refresh-symbolic")
+        .tooltip_text("List models again")
+        .build();
+    header.append(&refresh_button);
+    panel.append(&header);
+
+    let list = Box::new(Orientation::Vertical, 2);
+    panel.append(&list);
+
+    refresh_button.connect_clicked(glib::clone!(
+        @weak list,
+        @strong server_manager,
+        @strong runtime
+        => move |button| load_models(&list, button, &server_manager, &runtime, true)
+    ));
+
+    // Without polling, only ask the backend when told to
+    if config.health.polling_enabled {
+        load_models(&list, &refresh_button, server_manager, runtime, false);
+    } else {
+        list.append(&dim_label("Press refresh to list the backend's models"));
+    }
+
+    panel
+}
+
+/// Fill `list` with the backend's models, from the cache unless `refresh`
+fn load_models(
+    list: &Box,
+    button: &Button,
+    server_manager: &Arc<ServerManager>,
+    runtime: &Handle,
+    refresh: bool,
+) {
+    button.set_sensitive(false);
+    let request = {
+        let server_manager = server_manager.clone();
+        runtime.spawn(async move { server_manager.models(refresh).await })
+    };
+
+    glib::spawn_future_local(glib::clone!(@weak list, @weak button => async move {
+        let result = request.await;
+        button.set_sensitive(true);
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+
+        match result {
+            Ok(Ok(Some(models))) if models.is_empty() => {
+                list.append(&dim_label("The backend reports no models"));
+            }
+            Ok(Ok(Some(models))) => {
+                for model in &models {
+                    let mut text = format!("{} · {}", model.model, model.provider);
+                    if let Some(tokens) = model.context_length {
+                        text.push_str(&format!(" · {} tokens", tokens));
+                    }
+                    list.append(
+                        &Label::builder()
+                            .label(text)
+                            .xalign(0.0)
+                            .selectable(true)
+                            .build(),
+                    );
+                }
+            }
+            Ok(Ok(None)) => list.append(&dim_label("Model listing not available")),
+            Ok(Err(e)) => {
+                info!("Model list unavailable: {:#}", e);
+                list.append(&dim_label(&format!("Could not list models: {:#}", e)));
+            }
+            Err(e) => error!("Model list task failed: {}", e),
+        }
+    }));
+}
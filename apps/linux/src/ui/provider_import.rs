@@ -0,0 +1,119 @@
+//! Importing a provider file, from the menu or dropped on the window
+
+use adw::prelude::*;
+use adw::{MessageDialog, ResponseAppearance};
+use gtk::prelude::*;
+use gtk::{gio, glib, Application};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::{error, warn};
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::provider_import::ProviderImport;
+
+/// Pick a provider import file, then preview it
+pub fn choose_provider_import(
+    app: &Application,
+    config_manager: Arc<ConfigManager>,
+    runtime: Handle,
+) {
+    let json = gtk::FileFilter::new();
+    json.set_name(Some("JSON files"));
+    json.add_mime_type("application/json");
+    json.add_suffix("json");
+    let filters = gio::ListStore::new::<gtk::FileFilter>();
+    filters.append(&json);
+    let dialog = gtk::FileDialog::builder()
+        .title("Import Provider File")
+        .modal(true)
+        .filters(&filters)
+        .build();
+
+    let parent = app.active_window();
+    dialog.open(
+        parent.as_ref(),
+        gio::Cancellable::NONE,
+        glib::clone!(@strong parent => move |result| match result {
+            Ok(file) => match file.path() {
+                Some(path) => {
+                    confirm_provider_import(parent.as_ref(), config_manager, runtime, &path)
+                }
+                None => warn!("Only local files can be imported, not {}", file.uri()),
+            },
+            Err(e) if e.matches(gtk::DialogError::Dismissed) => {}
+            Err(e) => error!("Failed to choose a provider file: {}", e),
+        }),
+    );
+}
+
+/// Show what a provider import file would change and import it on confirmation
+///
+/// The file is checked against the current config first, so a file that
+/// can't be imported is reported before anything is asked.
+pub fn confirm_provider_import(
+    parent: Option<&gtk::Window>,
+    config_manager: Arc<ConfigManager>,
+    runtime: Handle,
+    path: &Path,
+) {
+    let checked = ProviderImport::read(path).and_then(|import| {
+        let mut config = config_manager.load()?;
+        let changes = import.preview(&config);
+        import.apply(&mut config)?;
+        Ok((import, changes))
+    });
+    let (import, changes) = match checked {
+        Ok(checked) => checked,
+        Err(e) => {
+            warn!("Not importing {:?}: {:#}", path, e);
+            let failed = MessageDialog::new(
+                parent,
+                Some("Could Not Import Provider File"),
+                Some(&format!("{:#}", e)),
+            );
+            failed.add_response("close", "Close");
+            failed.present();
+            return;
+        }
+    };
+
+    let lines: Vec<String> = changes.iter().map(|change| format!("• {}", change)).collect();
+    let dialog = MessageDialog::new(
+        parent,
+        Some("Import Provider File?"),
+        Some(&format!(
+            "{}\n\nKeys are stored in the keyring, everything else in the config file.",
+            lines.join("\n")
+        )),
+    );
+    dialog.add_responses(&[("cancel", "Cancel"), ("import", "Import")]);
+    dialog.set_response_appearance("import", ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let import = Arc::new(import);
+    dialog.connect_response(Some("import"), move |dialog, _| {
+        let task = {
+            let import = import.clone();
+            let config_manager = config_manager.clone();
+            runtime.spawn(async move { import.commit(&config_manager).await })
+        };
+        let parent = dialog.transient_for();
+        glib::spawn_future_local(async move {
+            let error = match task.await {
+                Ok(Ok(())) => return,
+                Ok(Err(e)) => format!("{:#}", e),
+                Err(e) => e.to_string(),
+            };
+            error!("Failed to import provider file: {}", error);
+            let failed = MessageDialog::new(
+                parent.as_ref(),
+                Some("Could Not Import Provider File"),
+                Some(&error),
+            );
+            failed.add_response("close", "Close");
+            failed.present();
+        });
+    });
+    dialog.present();
+}
@@ -0,0 +1,116 @@
+//! Advanced per-provider settings
+
+use adw::prelude::*;
+use adw::{ApplicationWindow, MessageDialog, ResponseAppearance};
+use gtk::{glib, Box, Entry, Label, Orientation};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::error;
+use vibeproxy_linux::config::parse_http_url;
+use vibeproxy_linux::server_manager::ServerManager;
+
+/// Edit where a provider's traffic goes, e.g. a local mock for testing
+///
+/// `base_url` is the saved override; `base_url_label` shows it in the
+/// provider's row and is updated once the change is in.
+pub(super) fn show_provider_settings(
+    window: &ApplicationWindow,
+    provider_id: &str,
+    base_url: Option<&str>,
+    base_url_label: &Label,
+    server_manager: &Arc<ServerManager>,
+    runtime: &Handle,
+) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        Some(&format!("{} Settings", provider_id)),
+        Some(
+            "Send this provider's traffic to another endpoint, such as a local mock. Leave \
+             it empty to use the usual one.",
+        ),
+    );
+
+    let content = Box::new(Orientation::Vertical, 6);
+    let entry = Entry::builder()
+        .placeholder_text("Base URL, e.g. http://localhost:9000/v1")
+        .text(base_url.unwrap_or_default())
+        .activates_default(true)
+        .build();
+    let error_label = Label::builder()
+        .css_classes(&["error"])
+        .xalign(0.0)
+        .wrap(true)
+        .visible(false)
+        .build();
+    content.append(&entry);
+    content.append(&error_label);
+    dialog.set_extra_child(Some(&content));
+
+    dialog.add_responses(&[("cancel", "Cancel"), ("save", "Save")]);
+    dialog.set_response_appearance("save", ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("save"));
+    dialog.set_close_response("cancel");
+
+    entry.connect_changed(glib::clone!(@weak dialog, @weak error_label => move |entry| {
+        let text = entry.text();
+        let problem = match text.trim() {
+            "" => None,
+            url => parse_http_url(url).err(),
+        };
+        match &problem {
+            Some(e) => {
+                error_label.set_label(&format!("{:#}", e));
+                entry.add_css_class("error");
+            }
+            None => entry.remove_css_class("error"),
+        }
+        error_label.set_visible(problem.is_some());
+        dialog.set_response_enabled("save", problem.is_none());
+    }));
+
+    let provider_id = provider_id.to_string();
+    let server_manager = server_manager.clone();
+    let runtime = runtime.clone();
+    dialog.connect_response(
+        Some("save"),
+        glib::clone!(@weak window, @weak entry, @weak base_url_label => move |_, _| {
+            let base_url = Some(entry.text().trim().to_string()).filter(|url| !url.is_empty());
+            let request = {
+                let server_manager = server_manager.clone();
+                let provider_id = provider_id.clone();
+                let base_url = base_url.clone();
+                runtime.spawn(async move {
+                    server_manager.set_provider_base_url(&provider_id, base_url).await
+                })
+            };
+
+            let provider_id = provider_id.clone();
+            glib::spawn_future_local(async move {
+                let message = match request.await {
+                    Ok(Ok(())) => {
+                        show_base_url(&base_url_label, base_url.as_deref());
+                        return;
+                    }
+                    Ok(Err(e)) => format!("{:#}", e),
+                    Err(e) => format!("The settings task failed: {}", e),
+                };
+                error!("Failed to save the base URL of '{}': {}", provider_id, message);
+                let dialog = MessageDialog::new(
+                    Some(&window),
+                    Some("Base URL Not Saved"),
+                    Some(&message),
+                );
+                dialog.add_response("close", "Close");
+                dialog.present();
+            });
+        }),
+    );
+    dialog.present();
+}
+
+/// Show `base_url` in a provider's row, hiding the label without one
+pub(super) fn show_base_url(label: &Label, base_url: Option<&str>) {
+    label.set_label(&format!("→ {}", base_url.unwrap_or_default()));
+    label.set_tooltip_text(Some("Traffic goes to this base URL instead of the provider"));
+    label.set_visible(base_url.is_some());
+}
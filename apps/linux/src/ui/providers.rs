@@ -0,0 +1,302 @@
+//! Providers section of the main window
+
+use super::provider_settings::{show_base_url, show_provider_settings};
+use adw::prelude::*;
+use adw::{ApplicationWindow, MessageDialog, ResponseAppearance};
+use gtk::prelude::*;
+use gtk::{glib, Box, Button, Label, Orientation, Switch};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::{error, info};
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::health::HealthLevel;
+use vibeproxy_linux::server_manager::ServerManager;
+
+/// Provider rows with a health legend and a button to discover more
+pub(super) fn providers_section(
+    window: &ApplicationWindow,
+    config: &Config,
+    config_manager: &Arc<ConfigManager>,
+    server_manager: &Arc<ServerManager>,
+    runtime: &Handle,
+) -> Box {
+    let section = Box::new(Orientation::Vertical, 12);
+
+    let providers_label = Label::builder()
+        .label("Providers")
+        .css_classes(&["title-2"])
+        .build();
+    section.append(&providers_label);
+
+    let providers_box = Box::new(Orientation::Vertical, 6);
+    if config.providers.is_empty() {
+        providers_box.append(
+            &Label::builder()
+                .label("No providers configured")
+                .css_classes(&["dim-label"])
+                .build(),
+        );
+    }
+    let indicators =
+        provider_rows(window, &providers_box, config, config_manager, server_manager, runtime);
+    section.append(&providers_box);
+    follow_provider_health(indicators, config, server_manager);
+
+    let legend = Box::new(Orientation::Horizontal, 18);
+    for level in HealthLevel::ALL {
+        legend.append(&HealthIndicator::new(level).container);
+    }
+    section.append(&legend);
+
+    let discover_button = Button::with_label("Discover Providers…");
+    discover_button.connect_clicked(glib::clone!(
+        @weak window,
+        @strong config_manager,
+        @strong server_manager,
+        @strong runtime
+        => move |_| {
+            discover_providers(&window, config_manager.clone(), server_manager.clone(), &runtime)
+        }
+    ));
+    section.append(&discover_button);
+
+    section
+}
+
+/// Query the backend for its models and offer to merge them into the config
+fn discover_providers(
+    window: &ApplicationWindow,
+    config_manager: Arc<ConfigManager>,
+    server_manager: Arc<ServerManager>,
+    runtime: &Handle,
+) {
+    let request = runtime.spawn(async move { server_manager.discover_models().await });
+
+    glib::spawn_future_local(glib::clone!(@weak window => async move {
+        let discovery = match request.await {
+            Ok(Ok(discovery)) => discovery,
+            Ok(Err(e)) => {
+                error!("Provider discovery failed: {:#}", e);
+                return;
+            }
+            Err(e) => {
+                error!("Provider discovery task failed: {}", e);
+                return;
+            }
+        };
+
+        let config = match config_manager.load() {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Failed to load configuration: {}", e);
+                return;
+            }
+        };
+        let models: Vec<(String, String)> = discovery
+            .models
+            .iter()
+            .map(|m| (m.provider.clone(), m.model.clone()))
+            .collect();
+        let preview = config
+            .preview_provider_merge(models.iter().map(|(p, m)| (p.as_str(), m.as_str())));
+
+        let mut body = if preview.is_empty() {
+            "Everything the backend serves is already configured.".to_string()
+        } else {
+            let mut body = format!(
+                "{} new providers, {} new models:\n",
+                preview.new_providers.len(),
+                preview.new_models.len()
+            );
+            for (provider, model) in &preview.new_models {
+                body.push_str(&format!("\n• {} → {}", provider, model));
+            }
+            body
+        };
+        if discovery.skipped > 0 {
+            body.push_str(&format!(
+                "\n\n{} entries without a recognizable provider were skipped.",
+                discovery.skipped
+            ));
+        }
+        if discovery.truncated {
+            body.push_str("\n\nThe backend's list was incomplete; only part of it is shown.");
+        }
+
+        let dialog = MessageDialog::new(Some(&window), Some("Discovered Providers"), Some(&body));
+        dialog.add_response("cancel", "Cancel");
+        if !preview.is_empty() {
+            dialog.add_response("apply", "Merge into Config");
+            dialog.set_response_appearance("apply", ResponseAppearance::Suggested);
+        }
+
+        dialog.connect_response(None, move |_, response| {
+            if response != "apply" {
+                return;
+            }
+            // Reload in case the file changed while the dialog was open
+            let result = config_manager.load().and_then(|mut config| {
+                config.merge_providers(models.iter().map(|(p, m)| (p.as_str(), m.as_str())));
+                config_manager.save(&config)
+            });
+            match result {
+                Ok(()) => info!("Merged discovered providers into configuration"),
+                Err(e) => error!("Failed to merge discovered providers: {}", e),
+            }
+        });
+
+        dialog.present();
+    }));
+}
+
+/// Keep each provider's indicator at the health the backend last reported for it
+///
+/// Follows the same status updates as the status section, which does the
+/// polling; providers the backend doesn't report on stay unknown.
+fn follow_provider_health(
+    indicators: Vec<(String, HealthIndicator)>,
+    config: &Config,
+    server_manager: &ServerManager,
+) {
+    let settings = config.health.clone();
+    let mut statuses = server_manager.status_updates();
+    // Weak so the subscription goes away with the window
+    let indicators: Vec<_> = indicators
+        .into_iter()
+        .map(|(id, indicator)| (id, indicator.symbol.downgrade(), indicator.text.downgrade()))
+        .collect();
+    glib::spawn_future_local(async move {
+        loop {
+            {
+                let status = statuses.borrow_and_update();
+                let details = status.as_ref().and_then(|status| status.details.as_ref());
+                for (id, symbol, text) in &indicators {
+                    let (Some(symbol), Some(text)) = (symbol.upgrade(), text.upgrade()) else {
+                        return;
+                    };
+                    show_level(&symbol, &text, HealthLevel::of_provider(details, id, &settings));
+                }
+            }
+            if statuses.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// One row per provider: routing switch, health and base URL
+///
+/// Returns each provider's health indicator, for [`follow_provider_health`].
+fn provider_rows(
+    window: &ApplicationWindow,
+    providers_box: &Box,
+    config: &Config,
+    config_manager: &Arc<ConfigManager>,
+    server_manager: &Arc<ServerManager>,
+    runtime: &Handle,
+) -> Vec<(String, HealthIndicator)> {
+    let mut indicators = Vec::new();
+    for provider in &config.providers {
+        let row = Box::new(Orientation::Horizontal, 12);
+
+        let enabled_switch = Switch::builder()
+            .active(provider.enabled)
+            .tooltip_text("Include in backend routing")
+            .build();
+        let provider_id = provider.id.clone();
+        enabled_switch.connect_state_set(glib::clone!(
+            @strong server_manager,
+            @strong runtime
+            => move |_, enabled| {
+                // Saved either way; the backend catches up on the next startup
+                let request = {
+                    let server_manager = server_manager.clone();
+                    let provider_id = provider_id.clone();
+                    runtime.spawn(async move {
+                        server_manager.set_provider_enabled(&provider_id, enabled).await
+                    })
+                };
+                let provider_id = provider_id.clone();
+                glib::spawn_future_local(async move {
+                    match request.await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => {
+                            error!("Failed to update routing for '{}': {:#}", provider_id, e)
+                        }
+                        Err(e) => error!("Routing update task failed: {}", e),
+                    }
+                });
+                glib::Propagation::Proceed
+            }
+        ));
+
+        let base_url_label = Label::builder().css_classes(&["dim-label"]).build();
+        show_base_url(&base_url_label, provider.base_url_override.as_deref());
+        let settings_button = Button::builder()
+            .icon_name("emblem-system-symbolic")
+            .tooltip_text("Advanced settings")
+            .css_classes(&["flat"])
+            .build();
+        let provider_id = provider.id.clone();
+        settings_button.connect_clicked(glib::clone!(
+            @weak window,
+            @weak base_url_label,
+            @strong config_manager,
+            @strong server_manager,
+            @strong runtime
+            => move |_| {
+                // The file may have changed since the row was built
+                let base_url = config_manager.load().ok().and_then(|config| {
+                    let provider = config.providers.into_iter().find(|p| p.id == provider_id)?;
+                    provider.base_url_override
+                });
+                show_provider_settings(
+                    &window,
+                    &provider_id,
+                    base_url.as_deref(),
+                    &base_url_label,
+                    &server_manager,
+                    &runtime,
+                );
+            }
+        ));
+
+        let indicator = HealthIndicator::new(HealthLevel::Unknown);
+        row.append(&enabled_switch);
+        row.append(&Label::new(Some(&provider.id)));
+        row.append(&indicator.container);
+        row.append(&base_url_label);
+        row.append(&settings_button);
+        providers_box.append(&row);
+        indicators.push((provider.id.clone(), indicator));
+    }
+    indicators
+}
+
+/// Colored shape plus a text label, so the status doesn't rely on color alone
+struct HealthIndicator {
+    container: Box,
+    symbol: Label,
+    text: Label,
+}
+
+impl HealthIndicator {
+    fn new(level: HealthLevel) -> Self {
+        let indicator = Self {
+            container: Box::new(Orientation::Horizontal, 6),
+            symbol: Label::new(None),
+            text: Label::new(None),
+        };
+        indicator.container.append(&indicator.symbol);
+        indicator.container.append(&indicator.text);
+        show_level(&indicator.symbol, &indicator.text, level);
+        indicator
+    }
+}
+
+fn show_level(symbol: &Label, text: &Label, level: HealthLevel) {
+    symbol.set_label(level.symbol());
+    symbol.set_css_classes(&[level.css_class()]);
+    text.set_label(level.label());
+}
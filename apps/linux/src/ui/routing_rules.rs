@@ -0,0 +1,304 @@
+//! Routing rules editor
+
+use super::dim_label;
+use adw::prelude::*;
+use adw::{MessageDialog, ResponseAppearance};
+use gtk::{glib, Box, Button, DropDown, Grid, Label, Orientation, SpinButton};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::error;
+use vibeproxy_linux::config::{Config, RoutingRule};
+use vibeproxy_linux::server_manager::ServerManager;
+
+/// Editor for the model aliases pinned to a provider
+///
+/// Every change is validated, saved and pushed to the backend at once; a
+/// rejected change leaves the list as it was.
+pub(super) fn routing_rules_panel(
+    config: &Config,
+    server_manager: &Arc<ServerManager>,
+    runtime: &Handle,
+) -> Box {
+    let panel = Box::new(Orientation::Vertical, 6);
+    panel.append(
+        &Label::builder()
+            .label("Routing Rules")
+            .css_classes(&["title-2"])
+            .xalign(0.0)
+            .build(),
+    );
+    if config.providers.is_empty() {
+        panel.append(&dim_label("Add providers to pin models to them"));
+        return panel;
+    }
+    panel.append(&dim_label("The backend uses the first rule whose pattern matches the model"));
+
+    let editor = Rc::new(RoutingRulesEditor {
+        list: Box::new(Orientation::Vertical, 2),
+        error_label: Label::builder()
+            .css_classes(&["error"])
+            .xalign(0.0)
+            .wrap(true)
+            .visible(false)
+            .build(),
+        rules: RefCell::new(config.routing_rules.clone()),
+        provider_ids: config.providers.iter().map(|p| p.id.clone()).collect(),
+        server_manager: server_manager.clone(),
+        runtime: runtime.clone(),
+    });
+    panel.append(&editor.list);
+    panel.append(&editor.error_label);
+
+    let form = Box::new(Orientation::Horizontal, 6);
+    let pattern_entry = gtk::Entry::builder()
+        .placeholder_text("Model pattern, e.g. gpt-4*")
+        .hexpand(true)
+        .build();
+    let provider_ids: Vec<&str> = config.providers.iter().map(|p| p.id.as_str()).collect();
+    let provider_dropdown = DropDown::from_strings(&provider_ids);
+    let model_entry = gtk::Entry::builder()
+        .placeholder_text("Provider model (optional)")
+        .hexpand(true)
+        .build();
+    let add_button = Button::builder()
+        .icon_name("list-add-symbolic")
+        .tooltip_text("Add rule")
+        .build();
+    add_button.connect_clicked(glib::clone!(
+        @weak editor,
+        @weak pattern_entry,
+        @weak provider_dropdown,
+        @weak model_entry
+        => move |_| {
+            let pattern = pattern_entry.text().trim().to_string();
+            let Some(provider) = provider_dropdown
+                .selected_item()
+                .and_downcast::<gtk::StringObject>()
+                .map(|item| item.string().to_string())
+            else {
+                return;
+            };
+            let model = model_entry.text().trim().to_string();
+            let mut rules = editor.rules.borrow().clone();
+            rules.push(RoutingRule {
+                pattern,
+                provider,
+                model: Some(model).filter(|model| !model.is_empty()),
+                weights: BTreeMap::new(),
+            });
+            editor.apply(rules, Some((pattern_entry.clone(), model_entry.clone())));
+        }
+    ));
+    form.append(&pattern_entry);
+    form.append(&provider_dropdown);
+    form.append(&model_entry);
+    form.append(&add_button);
+    panel.append(&form);
+
+    editor.render();
+    panel
+}
+
+struct RoutingRulesEditor {
+    list: Box,
+    error_label: Label,
+    /// As last saved
+    rules: RefCell<Vec<RoutingRule>>,
+    provider_ids: Vec<String>,
+    server_manager: Arc<ServerManager>,
+    runtime: Handle,
+}
+
+impl RoutingRulesEditor {
+    fn render(self: &Rc<Self>) {
+        while let Some(child) = self.list.first_child() {
+            self.list.remove(&child);
+        }
+
+        let rules = self.rules.borrow();
+        if rules.is_empty() {
+            self.list
+                .append(&dim_label("No rules; the backend spreads traffic over the providers"));
+        }
+        for (index, rule) in rules.iter().enumerate() {
+            let row = Box::new(Orientation::Horizontal, 6);
+            row.append(
+                &Label::builder()
+                    .label(rule.to_string())
+                    .xalign(0.0)
+                    .hexpand(true)
+                    .selectable(true)
+                    .build(),
+            );
+            for (icon, tooltip, enabled, change) in [
+                ("view-continuous-symbolic", "Split traffic…", true, RuleChange::Split),
+                ("go-up-symbolic", "Move up", index > 0, RuleChange::Up),
+                ("go-down-symbolic", "Move down", index + 1 < rules.len(), RuleChange::Down),
+                ("user-trash-symbolic", "Remove rule", true, RuleChange::Remove),
+            ] {
+                let button = Button::builder()
+                    .icon_name(icon)
+                    .tooltip_text(tooltip)
+                    .sensitive(enabled)
+                    .css_classes(&["flat"])
+                    .build();
+                button.connect_clicked(glib::clone!(@weak self as this => move |_| {
+                    let mut rules = this.rules.borrow().clone();
+                    match change {
+                        RuleChange::Split => return this.split_traffic(index),
+                        RuleChange::Up => rules.swap(index - 1, index),
+                        RuleChange::Down => rules.swap(index, index + 1),
+                        RuleChange::Remove => {
+                            rules.remove(index);
+                        }
+                    }
+                    this.apply(rules, None);
+                }));
+                row.append(&button);
+            }
+            self.list.append(&row);
+        }
+    }
+
+    /// Ask how to split the traffic of rule `index` across the providers, then save it
+    ///
+    /// The whole split is saved and pushed at once, so going from one
+    /// provider to 70/30 never passes through a half-set state.
+    fn split_traffic(self: &Rc<Self>, index: usize) {
+        let Some(rule) = self.rules.borrow().get(index).cloned() else {
+            return;
+        };
+        let parent = self.list.root().and_downcast::<gtk::Window>();
+        let dialog = MessageDialog::new(
+            parent.as_ref(),
+            Some("Split Traffic"),
+            Some(&format!(
+                "Relative share of the requests matching {} each provider gets, e.g. 70 and \
+                 30. Set a provider to 0 to leave it out.",
+                rule.pattern
+            )),
+        );
+
+        let grid = Grid::builder().row_spacing(6).column_spacing(12).build();
+        let mut targets = Vec::new();
+        for (row, provider_id) in self.provider_ids.iter().enumerate() {
+            let weight = match rule.weights.get(provider_id) {
+                Some(&weight) => weight,
+                None if rule.weights.is_empty() && *provider_id == rule.provider => 100,
+                None => 0,
+            };
+            let spin = SpinButton::with_range(0.0, 1000.0, 1.0);
+            spin.set_value(weight as f64);
+            let share = dim_label("");
+            let row = row as i32;
+            grid.attach(
+                &Label::builder().label(provider_id).xalign(0.0).hexpand(true).build(),
+                0,
+                row,
+                1,
+                1,
+            );
+            grid.attach(&spin, 1, row, 1, 1);
+            grid.attach(&share, 2, row, 1, 1);
+            targets.push((provider_id.clone(), spin, share));
+        }
+        dialog.set_extra_child(Some(&grid));
+        dialog.add_responses(&[("cancel", "Cancel"), ("save", "Save")]);
+        dialog.set_response_appearance("save", ResponseAppearance::Suggested);
+        dialog.set_close_response("cancel");
+
+        let targets = Rc::new(targets);
+        let split = {
+            let targets = targets.clone();
+            move || {
+                let weights = targets
+                    .iter()
+                    .map(|(id, spin, _)| (id.clone(), spin.value_as_int().max(0) as u32))
+                    .collect();
+                rule.clone().with_weights(weights)
+            }
+        };
+        // Show the effective distribution as the weights change
+        let show_shares = {
+            let split = split.clone();
+            let targets = targets.clone();
+            move |dialog: &MessageDialog| {
+                let shares = split().shares();
+                let any = targets.iter().any(|(_, spin, _)| spin.value_as_int() > 0);
+                for (id, _, label) in targets.iter() {
+                    let share = shares.iter().find(|(provider, _)| provider == id);
+                    match share {
+                        Some((_, share)) if any => {
+                            label.set_label(&format!("{:.0}%", share * 100.0))
+                        }
+                        _ => label.set_label("—"),
+                    }
+                }
+                dialog.set_response_enabled("save", any);
+            }
+        };
+        for (_, spin, _) in targets.iter() {
+            let show_shares = show_shares.clone();
+            spin.connect_value_changed(glib::clone!(@weak dialog => move |_| show_shares(&dialog)));
+        }
+        show_shares(&dialog);
+
+        dialog.connect_response(
+            Some("save"),
+            glib::clone!(@weak self as this => move |_, _| {
+                let mut rules = this.rules.borrow().clone();
+                if let Some(rule) = rules.get_mut(index) {
+                    *rule = split();
+                }
+                this.apply(rules, None);
+            }),
+        );
+        dialog.present();
+    }
+
+    /// Save and push `rules`, clearing `entries` once they are in
+    fn apply(
+        self: &Rc<Self>,
+        rules: Vec<RoutingRule>,
+        entries: Option<(gtk::Entry, gtk::Entry)>,
+    ) {
+        self.list.set_sensitive(false);
+        let request = {
+            let server_manager = self.server_manager.clone();
+            let rules = rules.clone();
+            self.runtime.spawn(async move { server_manager.set_routing_rules(rules).await })
+        };
+
+        glib::spawn_future_local(glib::clone!(@weak self as this => async move {
+            let result = request.await;
+            this.list.set_sensitive(true);
+            match result {
+                Ok(Ok(())) => {
+                    this.error_label.set_visible(false);
+                    *this.rules.borrow_mut() = rules;
+                    if let Some((pattern_entry, model_entry)) = entries {
+                        pattern_entry.set_text("");
+                        model_entry.set_text("");
+                    }
+                    this.render();
+                }
+                Ok(Err(e)) => {
+                    this.error_label.set_label(&format!("Rules not saved: {:#}", e));
+                    this.error_label.set_visible(true);
+                }
+                Err(e) => error!("Routing rules task failed: {}", e),
+            }
+        }));
+    }
+}
+
+#[derive(Clone, Copy)]
+enum RuleChange {
+    Split,
+    Up,
+    Down,
+    Remove,
+}
@@ -0,0 +1,330 @@
+//! Server status section of the main window
+
+use adw::prelude::*;
+use adw::ApplicationWindow;
+use gtk::prelude::*;
+use gtk::{gio, glib, Application, Box, Expander, Label, Orientation};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tracing::error;
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::health::HealthLevel;
+use vibeproxy_linux::process_usage::ResourceUsage;
+use vibeproxy_linux::recent_checks::{CheckResult, Uptime};
+use vibeproxy_linux::server_manager::{ServerManager, ServerStatus};
+
+/// Status line, recent checks and details, kept current from the manager
+///
+/// Also registers `win.refresh`. Returns the section and its status line,
+/// which the server controls report to as well.
+pub(super) fn status_section(
+    app: &Application,
+    window: &ApplicationWindow,
+    config: &Config,
+    server_manager: &Arc<ServerManager>,
+    runtime: &Handle,
+) -> (Box, Label) {
+    let section = Box::new(Orientation::Vertical, 12);
+
+    let status_label = Label::builder()
+        .label("Server Status")
+        .css_classes(&["title-2"])
+        .build();
+    section.append(&status_label);
+
+    let server_status = Label::builder()
+        .label("Stopped")
+        .css_classes(&["body"])
+        .build();
+    section.append(&server_status);
+
+    // CPU and memory of a backend VibeProxy started, to spot a runaway one
+    let usage_label = Label::builder()
+        .css_classes(&["caption", "dim-label"])
+        .visible(false)
+        .build();
+    section.append(&usage_label);
+
+    // Why the last check failed, or what the backend said
+    let status_message = Label::builder()
+        .css_classes(&["caption", "dim-label"])
+        .wrap(true)
+        .selectable(true)
+        .visible(false)
+        .build();
+    section.append(&status_message);
+
+    // One bar per recent health check, to spot a flaky backend at a glance
+    let checks: Rc<RefCell<Vec<CheckResult>>> = Rc::default();
+    let check_strip = gtk::DrawingArea::builder()
+        .content_height(24)
+        .hexpand(true)
+        .visible(config.health.recent_checks > 0)
+        .build();
+    let strip_capacity = config.health.recent_checks;
+    check_strip.set_draw_func(glib::clone!(@strong checks => move |_, cr, width, height| {
+        draw_checks(cr, width, height, &checks.borrow(), strip_capacity);
+    }));
+    section.append(&check_strip);
+    let uptime_label = Label::builder()
+        .css_classes(&["caption", "dim-label"])
+        .visible(config.health.recent_checks > 0)
+        .build();
+    section.append(&uptime_label);
+
+    // Version, uptime and provider reachability, for backends that report them
+    let details_label = Label::builder()
+        .label("No details yet")
+        .css_classes(&["dim-label"])
+        .xalign(0.0)
+        .selectable(true)
+        .build();
+    let details = Expander::builder()
+        .label("Details")
+        .child(&details_label)
+        .build();
+    section.append(&details);
+
+    // F5 or Ctrl+R: check now instead of waiting for the next poll
+    let refresh_action = gio::SimpleAction::new("refresh", None);
+    refresh_action.connect_activate(glib::clone!(
+        @strong server_manager,
+        @strong runtime,
+        @weak server_status,
+        @weak status_message
+        => move |action, _| {
+            // Disabled until the check finishes so repeated presses don't queue up
+            action.set_enabled(false);
+            let request = {
+                let server_manager = server_manager.clone();
+                runtime.spawn(async move { server_manager.refresh_status().await })
+            };
+            glib::spawn_future_local(glib::clone!(
+                @weak action,
+                @weak server_status,
+                @weak status_message
+                => async move {
+                    match request.await {
+                        Ok(Some(Ok(status))) => {
+                            show_status(&server_status, &status_message, &status)
+                        }
+                        Ok(Some(Err(e))) => server_status.set_label(&format!("Error: {}", e)),
+                        Ok(None) => {}
+                        Err(e) => error!("Health check task failed: {}", e),
+                    }
+                    action.set_enabled(true);
+                }
+            ));
+        }
+    ));
+    window.add_action(&refresh_action);
+    app.set_accels_for_action("win.refresh", &["F5", "<Primary>r"]);
+
+    if !config.health.polling_enabled {
+        section.append(
+            &Label::builder()
+                .label(
+                    "Polling disabled — press F5 or the refresh button to check the status now",
+                )
+                .css_classes(&["dim-label"])
+                .wrap(true)
+                .build(),
+        );
+    }
+
+    // Same updates the tray menu follows, so the two never disagree
+    let mut statuses = if config.health.polling_enabled {
+        server_manager.watch_status(Duration::from_secs(config.health.interval_secs))
+    } else {
+        server_manager.status_updates()
+    };
+    // Weak so the subscription goes away with the window
+    let widgets = (
+        server_status.downgrade(),
+        details_label.downgrade(),
+        status_message.downgrade(),
+    );
+    glib::spawn_future_local(async move {
+        while statuses.changed().await.is_ok() {
+            let (Some(label), Some(details_label), Some(message)) =
+                (widgets.0.upgrade(), widgets.1.upgrade(), widgets.2.upgrade())
+            else {
+                break;
+            };
+            if let Some(status) = statuses.borrow_and_update().as_ref() {
+                show_status(&label, &message, status);
+                details_label.set_label(&details_text(status));
+            }
+        }
+    });
+
+    // On the polling interval, so CPU use covers one interval; statuses
+    // only arrive when something changed
+    let usage_widget = usage_label.downgrade();
+    let usage_source = server_manager.clone();
+    let usage_interval = Duration::from_secs(config.health.interval_secs.max(1));
+    glib::spawn_future_local(async move {
+        loop {
+            let Some(usage_label) = usage_widget.upgrade() else {
+                break;
+            };
+            let usage = usage_source.resource_usage();
+            usage_label.set_visible(usage.is_some());
+            if let Some(usage) = usage {
+                usage_label.set_label(&usage_text(&usage));
+            }
+            drop(usage_label);
+            glib::timeout_future(usage_interval).await;
+        }
+    });
+
+    // Same interval: every check is recorded, changed or not
+    let strip_widgets = (check_strip.downgrade(), uptime_label.downgrade());
+    let checks_source = server_manager.clone();
+    let uptime_window = config.health.uptime_window();
+    if config.health.recent_checks > 0 {
+        glib::spawn_future_local(async move {
+            loop {
+                let (Some(check_strip), Some(uptime_label)) =
+                    (strip_widgets.0.upgrade(), strip_widgets.1.upgrade())
+                else {
+                    break;
+                };
+                *checks.borrow_mut() = checks_source.recent_checks();
+                check_strip.queue_draw();
+                let uptime = checks_source.uptime(uptime_window);
+                uptime_label.set_label(&uptime_text(uptime, uptime_window));
+                drop((check_strip, uptime_label));
+                glib::timeout_future(usage_interval).await;
+            }
+        });
+    }
+
+    (section, server_status)
+}
+
+/// Status label text, with latency while the backend is up
+pub fn status_text(status: &ServerStatus) -> String {
+    if status.running {
+        format!("{} ({} ms)", status.label(), status.latency_ms)
+    } else {
+        status.label().to_string()
+    }
+}
+
+/// `CPU 3.2% · 148 MB (pid 4242)`; CPU is left out until there are two samples
+fn usage_text(usage: &ResourceUsage) -> String {
+    let memory = format!("{:.0} MB", usage.rss_bytes as f64 / (1024.0 * 1024.0));
+    match usage.cpu_percent {
+        Some(cpu) => format!("CPU {:.1}% · {} (pid {})", cpu, memory, usage.pid),
+        None => format!("{} (pid {})", memory, usage.pid),
+    }
+}
+
+/// `Uptime 98.3% over the last 60 min (57 of 58 checks)`
+fn uptime_text(uptime: Option<Uptime>, window: Duration) -> String {
+    let Some(uptime) = uptime else {
+        return "No health checks yet".to_string();
+    };
+    format!(
+        "Uptime {:.1}% over the last {} min ({} of {} checks)",
+        uptime.percent(),
+        window.as_secs().div_ceil(60),
+        uptime.up,
+        uptime.total
+    )
+}
+
+/// Bars for `checks`, newest on the right: green scaled by latency, full-height red when down
+///
+/// Each bar takes `1 / capacity` of the width, so the strip fills up from the
+/// right as checks come in.
+fn draw_checks(
+    cr: &gtk::cairo::Context,
+    width: i32,
+    height: i32,
+    checks: &[CheckResult],
+    capacity: usize,
+) {
+    let slots = capacity.max(checks.len()).max(1) as f64;
+    let bar_width = width as f64 / slots;
+    let slowest = checks.iter().filter_map(|c| c.latency_ms).max().unwrap_or(1).max(1) as f64;
+    let first_slot = slots - checks.len() as f64;
+
+    for up in [true, false] {
+        for (index, check) in checks.iter().enumerate().filter(|(_, c)| c.is_up() == up) {
+            // A fast check still shows as a sliver
+            let share = check
+                .latency_ms
+                .map_or(1.0, |latency| (latency as f64 / slowest).max(0.15));
+            let bar_height = height as f64 * share;
+            let x = (first_slot + index as f64) * bar_width;
+            cr.rectangle(x, height as f64 - bar_height, (bar_width - 1.0).max(1.0), bar_height);
+        }
+        if up {
+            cr.set_source_rgb(0.15, 0.64, 0.41);
+        } else {
+            cr.set_source_rgb(0.75, 0.11, 0.16);
+        }
+        let _ = cr.fill();
+    }
+}
+
+/// Status line with latency, red while the backend is down, and its message below
+fn show_status(label: &Label, message: &Label, status: &ServerStatus) {
+    label.set_label(&status_text(status));
+    if status.running {
+        label.remove_css_class("error");
+    } else {
+        label.add_css_class("error");
+    }
+    message.set_label(status.message.as_deref().unwrap_or_default());
+    message.set_visible(status.message.is_some());
+}
+
+/// Contents of the "Details" expander
+fn details_text(status: &ServerStatus) -> String {
+    if !status.running {
+        return "Backend is not running".to_string();
+    }
+    let Some(details) = &status.details else {
+        return "This backend doesn't report details".to_string();
+    };
+
+    let mut lines = vec![
+        format!("Version: {}", details.version.as_deref().unwrap_or("unknown")),
+        format!(
+            "Uptime: {}",
+            details
+                .uptime_secs
+                .map(format_uptime)
+                .unwrap_or_else(|| "unknown".to_string())
+        ),
+    ];
+    if !details.providers.is_empty() {
+        lines.push("Providers:".to_string());
+    }
+    for provider in &details.providers {
+        let (level, detail) = match (provider.reachable, provider.latency_ms, &provider.error) {
+            (true, Some(latency), _) => (HealthLevel::Healthy, format!("{} ms", latency)),
+            (true, None, _) => (HealthLevel::Healthy, "reachable".to_string()),
+            (false, _, Some(error)) => (HealthLevel::Critical, error.clone()),
+            (false, _, None) => (HealthLevel::Critical, "unreachable".to_string()),
+        };
+        lines.push(format!("  {} {}: {}", level.symbol(), provider.id, detail));
+    }
+    lines.join("\n")
+}
+
+/// `3d 4h`, `4h 12m` or `12m`
+fn format_uptime(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
@@ -0,0 +1,149 @@
+//! Header bar switchers for the backend endpoint and profile
+
+use super::offer_rollback;
+use adw::prelude::*;
+use adw::ApplicationWindow;
+use gtk::prelude::*;
+use gtk::{glib, Application, Box, DropDown, Orientation, ToggleButton};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::{error, info};
+use vibeproxy_linux::config::Config;
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::server_manager::{ApplyOutcome, ServerManager};
+
+/// Header bar dropdown of pinned and recent backend endpoints, plus a pin toggle
+pub(super) fn endpoint_switcher(
+    window: &ApplicationWindow,
+    config: &Config,
+    config_manager: &Arc<ConfigManager>,
+    server_manager: &Arc<ServerManager>,
+    runtime: &Handle,
+) -> Box {
+    let current = config.backend_endpoint();
+    let mut choices = config.recent_endpoints.choices();
+    if !choices.contains(&current) {
+        choices.insert(0, current.clone());
+    }
+    let selected = choices.iter().position(|e| *e == current).unwrap_or(0);
+
+    let labels: Vec<&str> = choices.iter().map(String::as_str).collect();
+    let dropdown = DropDown::from_strings(&labels);
+    dropdown.set_selected(selected as u32);
+    dropdown.set_tooltip_text(Some("Backend endpoint"));
+
+    let pin_button = ToggleButton::builder()
+        .icon_name("starred-symbolic")
+        .active(config.recent_endpoints.is_pinned(&current))
+        .tooltip_text("Pin this endpoint")
+        .build();
+
+    dropdown.connect_selected_notify(glib::clone!(
+        @weak window,
+        @weak pin_button,
+        @strong config_manager,
+        @strong server_manager,
+        @strong runtime,
+        @strong choices
+        => move |dropdown| {
+            let Some(endpoint) = choices.get(dropdown.selected() as usize).cloned() else {
+                return;
+            };
+            let mut config = match config_manager.load() {
+                Ok(config) => config,
+                Err(e) => {
+                    error!("Failed to load config: {}", e);
+                    return;
+                }
+            };
+            if config.backend_endpoint() == endpoint {
+                return;
+            }
+            if let Err(e) = config.switch_backend(&endpoint) {
+                error!("Cannot switch to {}: {:#}", endpoint, e);
+                return;
+            }
+
+            info!("Switching backend to {}", endpoint);
+            let request = {
+                let server_manager = server_manager.clone();
+                runtime.spawn(async move { server_manager.apply_config(config).await })
+            };
+            glib::spawn_future_local(glib::clone!(
+                @weak window,
+                @weak pin_button,
+                @strong config_manager
+                => async move {
+                    let outcome = request.await;
+                    // Follow whichever endpoint ended up saved
+                    if let Ok(config) = config_manager.load() {
+                        let endpoint = config.backend_endpoint();
+                        pin_button.set_active(config.recent_endpoints.is_pinned(&endpoint));
+                    }
+                    match outcome {
+                        Ok(Ok(ApplyOutcome::Applied)) => {}
+                        Ok(Ok(ApplyOutcome::Unreachable { previous, error })) => {
+                            offer_rollback(&window, config_manager, previous, &error)
+                        }
+                        Ok(Err(e)) => error!("Failed to switch backend: {:#}", e),
+                        Err(e) => error!("Backend switch task failed: {}", e),
+                    }
+                }
+            ));
+        }
+    ));
+
+    pin_button.connect_toggled(glib::clone!(
+        @strong config_manager
+        => move |pin_button| {
+            let result = config_manager.load().and_then(|mut config| {
+                let endpoint = config.backend_endpoint();
+                config.recent_endpoints.set_pinned(&endpoint, pin_button.is_active());
+                config_manager.save(&config)
+            });
+            if let Err(e) = result {
+                error!("Failed to save pinned endpoint: {}", e);
+            }
+        }
+    ));
+
+    let switcher = Box::new(Orientation::Horizontal, 0);
+    switcher.add_css_class("linked");
+    switcher.append(&dropdown);
+    switcher.append(&pin_button);
+    switcher
+}
+
+/// Header bar dropdown of backend profiles, if any are configured
+///
+/// A "Custom" entry stands for a backend that isn't one of the profiles.
+pub(super) fn profile_switcher(app: &Application, config: &Config) -> Option<DropDown> {
+    if config.profiles.is_empty() {
+        return None;
+    }
+
+    let mut choices: Vec<Option<String>> = config.profiles.keys().cloned().map(Some).collect();
+    if config.active_profile.is_none() {
+        choices.insert(0, None);
+    }
+    let selected = choices
+        .iter()
+        .position(|name| *name == config.active_profile)
+        .unwrap_or(0);
+
+    let labels: Vec<&str> = choices
+        .iter()
+        .map(|name| name.as_deref().unwrap_or("Custom"))
+        .collect();
+    let dropdown = DropDown::from_strings(&labels);
+    dropdown.set_selected(selected as u32);
+    dropdown.set_tooltip_text(Some("Backend profile"));
+
+    dropdown.connect_selected_notify(glib::clone!(@weak app => move |dropdown| {
+        if let Some(Some(name)) = choices.get(dropdown.selected() as usize) {
+            info!("Switching to profile '{}'", name);
+            app.activate_action("select-profile", Some(&name.to_variant()));
+        }
+    }));
+    Some(dropdown)
+}
@@ -0,0 +1,108 @@
+//! Diagnostics report and About dialog
+
+use adw::prelude::*;
+use adw::{AboutWindow, MessageDialog};
+use gtk::prelude::*;
+use gtk::{gio, glib, Application};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tracing::{error, info, warn};
+use vibeproxy_linux::backend_api::last_failed_request_id;
+use vibeproxy_linux::branding::Branding;
+use vibeproxy_linux::config_manager::ConfigManager;
+use vibeproxy_linux::diagnostics;
+use vibeproxy_linux::server_manager::ServerManager;
+
+/// Put a diagnostics report on the clipboard, with the option to save it too
+///
+/// The report is built on the runtime since it checks the backend first.
+pub fn copy_diagnostics(
+    app: &Application,
+    config_manager: Arc<ConfigManager>,
+    server_manager: Arc<ServerManager>,
+    runtime: Handle,
+) {
+    let report = runtime.spawn(async move {
+        diagnostics::report(&config_manager, &server_manager).await
+    });
+    glib::spawn_future_local(glib::clone!(@weak app => async move {
+        let report = match report.await {
+            Ok(report) => report,
+            Err(e) => {
+                error!("Diagnostics report failed: {}", e);
+                return;
+            }
+        };
+        if let Some(display) = gtk::gdk::Display::default() {
+            display.clipboard().set_text(&report);
+        }
+
+        let dialog = MessageDialog::new(
+            app.active_window().as_ref(),
+            Some("Diagnostics Copied"),
+            Some(
+                "The report is on the clipboard, ready to paste into a bug report. API keys, \
+                 tokens and other credentials have been removed, but please look it over \
+                 before posting it publicly.",
+            ),
+        );
+        dialog.add_responses(&[("save", "Save to File…"), ("close", "Close")]);
+        dialog.set_default_response(Some("close"));
+        dialog.set_close_response("close");
+        dialog.connect_response(Some("save"), move |dialog, _| {
+            save_diagnostics(dialog.transient_for().as_ref(), report.clone());
+        });
+        dialog.present();
+    }));
+}
+
+fn save_diagnostics(parent: Option<&gtk::Window>, report: String) {
+    let dialog = gtk::FileDialog::builder()
+        .title("Save Diagnostics")
+        .initial_name("vibeproxy-diagnostics.txt")
+        .modal(true)
+        .build();
+    dialog.save(parent, gio::Cancellable::NONE, move |result| {
+        let path = match result {
+            Ok(file) => file.path(),
+            Err(e) if e.matches(gtk::DialogError::Dismissed) => return,
+            Err(e) => {
+                error!("Failed to choose where to save diagnostics: {}", e);
+                return;
+            }
+        };
+        let Some(path) = path else {
+            warn!("Diagnostics can only be saved to a local file");
+            return;
+        };
+        match std::fs::write(&path, &report) {
+            Ok(()) => info!("Diagnostics saved to {:?}", path),
+            Err(e) => error!("Failed to save diagnostics to {:?}: {}", path, e),
+        }
+    });
+}
+
+/// About dialog, using the configured branding
+pub fn show_about(parent: Option<&gtk::Window>) {
+    let branding = Branding::current();
+    let about = AboutWindow::builder()
+        .application_name(branding.app_name.as_str())
+        .application_icon(branding.icon_name.as_str())
+        .version(env!("CARGO_PKG_VERSION"))
+        .license_type(gtk::License::MitX11)
+        .debug_info(debug_info())
+        .build();
+    about.set_transient_for(parent);
+    about.present();
+}
+
+/// Troubleshooting details for the About dialog
+fn debug_info() -> String {
+    let last_failed = last_failed_request_id().unwrap_or_else(|| "none".to_string());
+    format!(
+        "Version: {}\nLast failed backend request id: {}\nLogs: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        last_failed,
+        diagnostics::log_usage()
+    )
+}